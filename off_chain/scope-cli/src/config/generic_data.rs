@@ -0,0 +1,486 @@
+//! Conversion between the oracle type specific configuration of a token and the
+//! generic data stored onchain for its entry.
+
+use std::{fmt, num::NonZeroU64, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use scope::{
+    oracles::{
+        clmm::{ClmmConfig, ClmmPool},
+        constant_product_lp::{ConstantProductLpConfig, ConstantProductPool},
+        fixed_price::{self, FixedPriceConfig},
+        pyth_pull::PythPullConfig,
+        scope_chain::ScopeChainConfig,
+        scope_median::ScopeMedianConfig,
+        scope_ratio::{ScopeRatioConfig, ScopeReciprocalConfig},
+        scope_twap::ScopeTwapConfig,
+        spl_stake::SplStakeConfig,
+        OracleGenericData, OracleType,
+    },
+    GENERIC_DATA_SIZE,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{utils::serde_string, TokenConfig};
+
+/// Configuration of a [`OracleType::ScopeMedian`] token
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct MedianConfig {
+    /// Index of the scope entries used as sources
+    pub sources: Vec<u16>,
+    /// Maximum divergence from the median (in bps) before a source is rejected
+    pub max_divergence_bps: u16,
+    /// Minimum number of valid sources required to compute a price
+    pub min_sources: u16,
+    /// Sources older than this number of slots are ignored (0 or absent to disable)
+    #[serde(default)]
+    pub max_source_age_slots: u32,
+}
+
+impl TryFrom<&MedianConfig> for ScopeMedianConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(conf: &MedianConfig) -> Result<Self> {
+        let mut median =
+            ScopeMedianConfig::new(&conf.sources, conf.max_divergence_bps, conf.min_sources)
+                .map_err(|_| anyhow!("Too many median sources: {}", conf.sources.len()))?;
+        median.max_source_age_slots = conf.max_source_age_slots;
+        Ok(median)
+    }
+}
+
+impl From<&ScopeMedianConfig> for MedianConfig {
+    fn from(median: &ScopeMedianConfig) -> Self {
+        Self {
+            sources: median.used_sources().collect(),
+            max_divergence_bps: median.max_divergence_bps,
+            min_sources: median.min_sources,
+            max_source_age_slots: median.max_source_age_slots,
+        }
+    }
+}
+
+/// Configuration of a [`OracleType::ScopeTwap`] token
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TwapConfig {
+    /// Index of the scope entry to average
+    pub source: u16,
+    /// Duration of the averaging window in seconds
+    pub window_s: u32,
+    /// Minimum number of observations in the window required to compute a price
+    pub min_samples: u16,
+}
+
+impl From<&TwapConfig> for ScopeTwapConfig {
+    fn from(conf: &TwapConfig) -> Self {
+        Self {
+            source_entry: conf.source,
+            min_samples: conf.min_samples,
+            window_s: conf.window_s,
+        }
+    }
+}
+
+impl From<&ScopeTwapConfig> for TwapConfig {
+    fn from(twap: &ScopeTwapConfig) -> Self {
+        Self {
+            source: twap.source_entry,
+            window_s: twap.window_s,
+            min_samples: twap.min_samples,
+        }
+    }
+}
+
+/// Configuration of a [`OracleType::ScopeRatio`] token
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct RatioConfig {
+    /// Index of the scope entry divided
+    pub numerator: u16,
+    /// Index of the scope entry dividing
+    pub denominator: u16,
+}
+
+impl From<&RatioConfig> for ScopeRatioConfig {
+    fn from(conf: &RatioConfig) -> Self {
+        Self {
+            numerator: conf.numerator,
+            denominator: conf.denominator,
+        }
+    }
+}
+
+impl From<&ScopeRatioConfig> for RatioConfig {
+    fn from(ratio: &ScopeRatioConfig) -> Self {
+        Self {
+            numerator: ratio.numerator,
+            denominator: ratio.denominator,
+        }
+    }
+}
+
+/// Configuration of a [`OracleType::ConstantProductLp`] token
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct LpConfig {
+    /// Kind of the pool referenced by the oracle mapping
+    pub pool: ConstantProductPool,
+    /// Index of the scope entry pricing token A of the pool
+    pub price_a: u16,
+    /// Index of the scope entry pricing token B of the pool
+    pub price_b: u16,
+}
+
+impl From<&LpConfig> for ConstantProductLpConfig {
+    fn from(conf: &LpConfig) -> Self {
+        Self {
+            pool: conf.pool.into(),
+            price_a: conf.price_a,
+            price_b: conf.price_b,
+        }
+    }
+}
+
+impl TryFrom<&ConstantProductLpConfig> for LpConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(lp: &ConstantProductLpConfig) -> Result<Self> {
+        Ok(Self {
+            pool: ConstantProductPool::try_from(lp.pool)
+                .map_err(|_| anyhow!("Unknown constant product pool kind {}", lp.pool))?,
+            price_a: lp.price_a,
+            price_b: lp.price_b,
+        })
+    }
+}
+
+/// Configuration of a [`OracleType::ClmmSpot`] token
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ClmmSpotConfig {
+    /// Kind of the pool referenced by the oracle mapping
+    pub pool: ClmmPool,
+    /// Price token B in token A instead of token A in token B
+    #[serde(default)]
+    pub invert: bool,
+    /// Minimum virtual reserve of the quote token backing the in-range liquidity, in its
+    /// smallest unit
+    #[serde(default)]
+    pub min_quote_depth: u64,
+    /// Duration in seconds of the average of the observations of a Raydium pool, 0 or absent
+    /// for the spot price
+    #[serde(default)]
+    pub twap_duration_s: u32,
+}
+
+impl From<&ClmmSpotConfig> for ClmmConfig {
+    fn from(conf: &ClmmSpotConfig) -> Self {
+        Self {
+            min_quote_depth: conf.min_quote_depth,
+            pool: conf.pool.into(),
+            invert: conf.invert.into(),
+            twap_duration_s: conf.twap_duration_s,
+        }
+    }
+}
+
+impl TryFrom<&ClmmConfig> for ClmmSpotConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(clmm: &ClmmConfig) -> Result<Self> {
+        Ok(Self {
+            pool: clmm
+                .pool()
+                .map_err(|_| anyhow!("Unknown concentrated liquidity pool kind {}", clmm.pool))?,
+            invert: clmm.is_inverted(),
+            min_quote_depth: clmm.min_quote_depth,
+            twap_duration_s: clmm.twap_duration_s,
+        })
+    }
+}
+
+/// Price of a [`OracleType::FixedPrice`] token
+///
+/// Written as a decimal literal (e.g. "1.0005") in the configuration files, the number of
+/// decimals written is the exponent of the price.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FixedPrice {
+    pub value: u64,
+    pub exp: u64,
+}
+
+impl FromStr for FixedPrice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (integer, decimals) = s.split_once('.').unwrap_or((s, ""));
+        if integer.is_empty()
+            || !integer
+                .chars()
+                .chain(decimals.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            bail!("Invalid fixed price {s:?}, expected a decimal number like \"1.0\"");
+        }
+        let value = format!("{integer}{decimals}")
+            .parse()
+            .map_err(|_| anyhow!("Fixed price {s:?} does not fit in a u64"))?;
+        let exp = decimals.len().try_into()?;
+        if exp > fixed_price::MAX_EXP {
+            bail!(
+                "Fixed price {s:?} has more than {} decimals",
+                fixed_price::MAX_EXP
+            );
+        }
+        Ok(Self { value, exp })
+    }
+}
+
+impl fmt::Display for FixedPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = format!("{:0>width$}", self.value, width = self.exp as usize + 1);
+        let (integer, decimals) = digits.split_at(digits.len() - self.exp as usize);
+        if decimals.is_empty() {
+            write!(f, "{integer}")
+        } else {
+            write!(f, "{integer}.{decimals}")
+        }
+    }
+}
+
+impl Serialize for FixedPrice {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FixedPrice {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_string::deserialize(deserializer)
+    }
+}
+
+impl From<&FixedPrice> for FixedPriceConfig {
+    fn from(price: &FixedPrice) -> Self {
+        Self {
+            value: price.value,
+            exp: price.exp,
+        }
+    }
+}
+
+impl From<&FixedPriceConfig> for FixedPrice {
+    fn from(config: &FixedPriceConfig) -> Self {
+        Self {
+            value: config.value,
+            exp: config.exp,
+        }
+    }
+}
+
+/// Feed id of a [`OracleType::PythPull`] token
+///
+/// Written as an hexadecimal string (e.g. "0xef0d...b56d") in the configuration files, as in the
+/// pyth documentation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PythFeedId(pub [u8; 32]);
+
+impl FromStr for PythFeedId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        if hex.len() != 64 || !hex.is_ascii() {
+            bail!("Invalid pyth feed id {s:?}, expected 32 bytes in hexadecimal");
+        }
+        let mut feed_id = [0; 32];
+        for (byte, digits) in feed_id.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let digits = std::str::from_utf8(digits)?;
+            *byte = u8::from_str_radix(digits, 16)
+                .map_err(|_| anyhow!("Invalid pyth feed id {s:?}, expected hexadecimal digits"))?;
+        }
+        Ok(Self(feed_id))
+    }
+}
+
+impl fmt::Display for PythFeedId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("0x")?;
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl Serialize for PythFeedId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PythFeedId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_string::deserialize(deserializer)
+    }
+}
+
+/// Build the generic data of an entry from its token configuration
+pub fn generic_data_from_config(conf: &TokenConfig) -> Result<[u8; GENERIC_DATA_SIZE]> {
+    match conf.oracle_type {
+        OracleType::ScopeMedian => {
+            let median = conf
+                .median
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing median configuration for {}", conf.label))?;
+            Ok(ScopeMedianConfig::try_from(median)?.to_generic_data())
+        }
+        OracleType::ScopeTwap => {
+            let twap = conf
+                .twap
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing TWAP configuration for {}", conf.label))?;
+            Ok(ScopeTwapConfig::from(twap).to_generic_data())
+        }
+        OracleType::FixedPrice => {
+            let price = conf
+                .fixed_price
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing fixed price for {}", conf.label))?;
+            Ok(FixedPriceConfig::from(price).to_generic_data())
+        }
+        OracleType::ScopeChain => {
+            let chain = conf
+                .chain
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing chain configuration for {}", conf.label))?;
+            let chain = ScopeChainConfig::new(chain)
+                .map_err(|_| anyhow!("Scope chain too long: {}", chain.len()))?;
+            Ok(chain.to_generic_data())
+        }
+        OracleType::ScopeReciprocal => {
+            let source = conf
+                .reciprocal
+                .ok_or_else(|| anyhow!("Missing reciprocal source for {}", conf.label))?;
+            Ok(ScopeReciprocalConfig { source }.to_generic_data())
+        }
+        OracleType::ScopeRatio => {
+            let ratio = conf
+                .ratio
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing ratio configuration for {}", conf.label))?;
+            Ok(ScopeRatioConfig::from(ratio).to_generic_data())
+        }
+        OracleType::PythPull => {
+            let PythFeedId(feed_id) = conf
+                .pyth_feed_id
+                .ok_or_else(|| anyhow!("Missing pyth feed id for {}", conf.label))?;
+            Ok(PythPullConfig { feed_id }.to_generic_data())
+        }
+        OracleType::SplStake => Ok(SplStakeConfig {
+            epoch_grace_period_s: conf.epoch_grace_period_s.map_or(0, NonZeroU64::get),
+        }
+        .to_generic_data()),
+        OracleType::ConstantProductLp => {
+            let lp = conf
+                .lp
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing LP configuration for {}", conf.label))?;
+            Ok(ConstantProductLpConfig::from(lp).to_generic_data())
+        }
+        OracleType::ClmmSpot => {
+            let clmm = conf
+                .clmm
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing CLMM configuration for {}", conf.label))?;
+            Ok(ClmmConfig::from(clmm).to_generic_data())
+        }
+        _ => Ok([0; GENERIC_DATA_SIZE]),
+    }
+}
+
+/// Set the oracle type specific configuration of a token from its entry generic data
+pub fn set_config_from_generic_data(conf: &mut TokenConfig, data: &[u8; GENERIC_DATA_SIZE]) {
+    match conf.oracle_type {
+        OracleType::ScopeMedian => {
+            conf.median = Some((&ScopeMedianConfig::from_generic_data(data)).into());
+        }
+        OracleType::ScopeTwap => {
+            conf.twap = Some((&ScopeTwapConfig::from_generic_data(data)).into());
+        }
+        OracleType::FixedPrice => {
+            conf.fixed_price = Some((&FixedPriceConfig::from_generic_data(data)).into());
+        }
+        OracleType::ScopeChain => {
+            conf.chain = Some(
+                ScopeChainConfig::from_generic_data(data)
+                    .used_links()
+                    .collect(),
+            );
+        }
+        OracleType::ScopeReciprocal => {
+            conf.reciprocal = Some(ScopeReciprocalConfig::from_generic_data(data).source);
+        }
+        OracleType::ScopeRatio => {
+            conf.ratio = Some((&ScopeRatioConfig::from_generic_data(data)).into());
+        }
+        OracleType::PythPull => {
+            conf.pyth_feed_id = Some(PythFeedId(PythPullConfig::from_generic_data(data).feed_id));
+        }
+        OracleType::SplStake => {
+            conf.epoch_grace_period_s =
+                NonZeroU64::new(SplStakeConfig::from_generic_data(data).epoch_grace_period_s);
+        }
+        OracleType::ConstantProductLp => {
+            conf.lp = (&ConstantProductLpConfig::from_generic_data(data))
+                .try_into()
+                .ok();
+        }
+        OracleType::ClmmSpot => {
+            conf.clmm = (&ClmmConfig::from_generic_data(data)).try_into().ok();
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_price_from_str() {
+        let price: FixedPrice = "1.0005".parse().unwrap();
+        assert_eq!(
+            price,
+            FixedPrice {
+                value: 10_005,
+                exp: 4
+            }
+        );
+        let price: FixedPrice = "42".parse().unwrap();
+        assert_eq!(price, FixedPrice { value: 42, exp: 0 });
+        assert!("".parse::<FixedPrice>().is_err());
+        assert!(".5".parse::<FixedPrice>().is_err());
+        assert!("-1.0".parse::<FixedPrice>().is_err());
+        assert!("1e6".parse::<FixedPrice>().is_err());
+        assert!("18446744073709551616".parse::<FixedPrice>().is_err());
+        assert!("0.0000000000000000001".parse::<FixedPrice>().is_err());
+    }
+
+    #[test]
+    fn pyth_feed_id_from_str() {
+        let hex = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+        let feed_id: PythFeedId = hex.parse().unwrap();
+        assert_eq!(feed_id.0[0], 0xef);
+        assert_eq!(feed_id.0[31], 0x6d);
+        assert_eq!(feed_id.to_string(), hex);
+        // The prefix is optional
+        assert_eq!(hex[2..].parse::<PythFeedId>().unwrap(), feed_id);
+        assert!(hex[..64].parse::<PythFeedId>().is_err());
+        assert!(format!("{hex}00").parse::<PythFeedId>().is_err());
+        assert!(hex.replace('e', "g").parse::<PythFeedId>().is_err());
+    }
+
+    #[test]
+    fn fixed_price_display() {
+        for s in ["1.0005", "42", "0.001", "1.0"] {
+            assert_eq!(s.parse::<FixedPrice>().unwrap().to_string(), s);
+        }
+        let price = FixedPrice { value: 5, exp: 3 };
+        assert_eq!(price.to_string(), "0.005");
+    }
+}
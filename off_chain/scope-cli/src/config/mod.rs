@@ -1,3 +1,4 @@
+pub mod generic_data;
 pub mod scope_config;
 pub mod token_config;
 pub use scope_config::*;
@@ -56,6 +56,7 @@ mod tests {
                 oracle_mapping: Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix")
                     .unwrap(),
                 oracle_type: OracleType::Pyth,
+                ..Default::default()
             },
         );
         token_conf_list.tokens.insert(
@@ -66,6 +67,7 @@ mod tests {
                 oracle_mapping: Pubkey::from_str("EdVCmQ9FSPcVe5YySXDPCRmc8aDQLKJ9xvYBMZPie1Vw")
                     .unwrap(),
                 oracle_type: OracleType::SwitchboardV1,
+                ..Default::default()
             },
         );
         token_conf_list.tokens.insert(
@@ -76,6 +78,7 @@ mod tests {
                 oracle_mapping: Pubkey::from_str("9LNYQZLJG5DAyeACCTzBFG6H3sDhehP5xtYLdhrZtQkA")
                     .unwrap(),
                 oracle_type: OracleType::SwitchboardV2,
+                ..Default::default()
             },
         );
         token_conf_list.tokens.insert(
@@ -86,6 +89,7 @@ mod tests {
                 oracle_mapping: Pubkey::from_str("9LNYQZLJG5DAyeACCTzBFG6H3sDhehP5xtYLdhrZtQkA")
                     .unwrap(),
                 oracle_type: OracleType::CToken,
+                ..Default::default()
             },
         );
         token_conf_list.tokens.insert(
@@ -96,6 +100,7 @@ mod tests {
                 oracle_mapping: Pubkey::from_str("VF45TSF5WPAay9qy2zr1hPYgieBv7r17vYLRK6v1RmB")
                     .unwrap(),
                 oracle_type: OracleType::KToken,
+                ..Default::default()
             },
         );
 
@@ -1,9 +1,14 @@
-use std::num::NonZeroU64;
+use std::num::{NonZeroU16, NonZeroU64};
 
 use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType};
 use serde::{Deserialize, Serialize};
 
-use super::utils::serde_string;
+use super::{
+    generic_data::{
+        ClmmSpotConfig, FixedPrice, LpConfig, MedianConfig, PythFeedId, RatioConfig, TwapConfig,
+    },
+    utils::serde_string,
+};
 
 /// Configuration of the tokens
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -16,9 +21,72 @@ pub struct TokenConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Optional specific token max age (in number of slot).
     pub max_age: Option<NonZeroU64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Optional max relative change of the price per slot (in bps), larger changes are rejected.
+    pub max_change_bps_per_slot: Option<NonZeroU16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Optional max confidence interval relative to the price (in bps), less confident prices are
+    /// rejected. Defaults to 2% onchain.
+    pub confidence_bps: Option<NonZeroU16>,
     /// Onchain account used as source for the exchange rate.
     #[serde(with = "serde_string")] // Use bs58 for serialization
     pub oracle_mapping: Pubkey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Sources of a [`OracleType::ScopeMedian`] token.
+    pub median: Option<MedianConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Source and window of a [`OracleType::ScopeTwap`] token.
+    pub twap: Option<TwapConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Price of a [`OracleType::FixedPrice`] token, as a decimal literal (e.g. "1.0").
+    pub fixed_price: Option<FixedPrice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Entries of a [`OracleType::ScopeChain`] token, their prices are multiplied in order.
+    pub chain: Option<Vec<u16>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Entry inverted by a [`OracleType::ScopeReciprocal`] token.
+    pub reciprocal: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Entries divided by a [`OracleType::ScopeRatio`] token.
+    pub ratio: Option<RatioConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Pyth feed id of a [`OracleType::PythPull`] token, in hexadecimal.
+    pub pyth_feed_id: Option<PythFeedId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Optional grace period (in seconds) after the start of an epoch during which the rate of a
+    /// [`OracleType::SplStake`] pool not updated yet is projected from the previous epoch.
+    pub epoch_grace_period_s: Option<NonZeroU64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Pool kind and entries pricing the pool tokens of a [`OracleType::ConstantProductLp`] token.
+    pub lp: Option<LpConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Pool kind, direction and minimum depth of a [`OracleType::ClmmSpot`] token.
+    pub clmm: Option<ClmmSpotConfig>,
+}
+
+/// Pyth token without any optional setting, the oracle type specific settings are added with the
+/// struct update syntax
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            oracle_type: OracleType::Pyth,
+            max_age: None,
+            max_change_bps_per_slot: None,
+            confidence_bps: None,
+            oracle_mapping: Pubkey::default(),
+            median: None,
+            twap: None,
+            fixed_price: None,
+            chain: None,
+            reciprocal: None,
+            ratio: None,
+            pyth_feed_id: None,
+            epoch_grace_period_s: None,
+            lp: None,
+            clmm: None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -36,6 +104,7 @@ mod tests {
             oracle_mapping: Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix")
                 .unwrap(),
             oracle_type: OracleType::Pyth,
+            ..Default::default()
         };
 
         let json = r#"{
@@ -51,4 +120,101 @@ mod tests {
         let deserialized = serde_json::to_string(&token_conf).unwrap();
         assert_eq!(remove_whitespace(&deserialized), remove_whitespace(json));
     }
+
+    #[test]
+    fn median_conf_de_ser() {
+        let token_conf = TokenConfig {
+            label: "SOL/USD".to_string(),
+            max_age: None,
+            oracle_mapping: Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix")
+                .unwrap(),
+            oracle_type: OracleType::ScopeMedian,
+            median: Some(MedianConfig {
+                sources: vec![0, 3, 7],
+                max_divergence_bps: 100,
+                min_sources: 2,
+                max_source_age_slots: 150,
+            }),
+            ..Default::default()
+        };
+
+        let json = r#"{
+              "label": "SOL/USD",
+              "oracle_type": "ScopeMedian",
+              "oracle_mapping": "J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix",
+              "median": {
+                "sources": [0, 3, 7],
+                "max_divergence_bps": 100,
+                "min_sources": 2,
+                "max_source_age_slots": 150
+              }
+            }
+            "#;
+
+        let serialized: TokenConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(token_conf, serialized);
+
+        let deserialized = serde_json::to_string(&token_conf).unwrap();
+        assert_eq!(remove_whitespace(&deserialized), remove_whitespace(json));
+    }
+
+    #[test]
+    fn fixed_price_conf_de_ser() {
+        let token_conf = TokenConfig {
+            label: "USDH/USD".to_string(),
+            max_age: None,
+            oracle_mapping: Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix")
+                .unwrap(),
+            oracle_type: OracleType::FixedPrice,
+            fixed_price: Some(FixedPrice {
+                value: 10_005,
+                exp: 4,
+            }),
+            ..Default::default()
+        };
+
+        let json = r#"{
+              "label": "USDH/USD",
+              "oracle_type": "FixedPrice",
+              "oracle_mapping": "J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix",
+              "fixed_price": "1.0005"
+            }
+            "#;
+
+        let serialized: TokenConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(token_conf, serialized);
+
+        let deserialized = serde_json::to_string(&token_conf).unwrap();
+        assert_eq!(remove_whitespace(&deserialized), remove_whitespace(json));
+    }
+
+    #[test]
+    fn pyth_pull_conf_de_ser() {
+        let feed_id = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+        let token_conf = TokenConfig {
+            label: "SOL/USD".to_string(),
+            max_age: None,
+            oracle_mapping: Pubkey::from_str("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE")
+                .unwrap(),
+            oracle_type: OracleType::PythPull,
+            pyth_feed_id: Some(feed_id.parse().unwrap()),
+            ..Default::default()
+        };
+
+        let json = format!(
+            r#"{{
+              "label": "SOL/USD",
+              "oracle_type": "PythPull",
+              "oracle_mapping": "7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE",
+              "pyth_feed_id": "{feed_id}"
+            }}
+            "#
+        );
+
+        let serialized: TokenConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(token_conf, serialized);
+
+        let deserialized = serde_json::to_string(&token_conf).unwrap();
+        assert_eq!(remove_whitespace(&deserialized), remove_whitespace(&json));
+    }
 }
@@ -15,6 +15,7 @@ use anchor_client::{
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use orbit_link::{async_client::AsyncClient, OrbitLink};
+use scope::FreezeTarget;
 use scope_client::{utils::get_clock, ScopeClient, ScopeConfig};
 use tokio::time::sleep;
 use tracing::{error, info, trace, warn};
@@ -130,6 +131,75 @@ enum Actions {
         #[clap(long, env, parse(from_os_str))]
         mapping: Option<PathBuf>,
     },
+
+    /// Propose a new admin for the price feed, first step of the admin handover.
+    /// This requires the current admin keypair
+    #[clap(arg_required_else_help = true)]
+    SetAdminCached {
+        /// Pubkey of the proposed new admin
+        #[clap(long, env, parse(try_from_str))]
+        new_admin: Pubkey,
+    },
+
+    /// Accept the admin role of the price feed, second step of the admin handover.
+    /// This requires the keypair of the proposed admin
+    #[clap()]
+    ApproveAdminCached,
+
+    /// Set the guardian of the price feed, only allowed to freeze entries or pause the feed.
+    /// This requires the admin keypair
+    #[clap(arg_required_else_help = true)]
+    SetGuardian {
+        /// Pubkey of the new guardian
+        #[clap(long, env, parse(try_from_str))]
+        new_guardian: Pubkey,
+    },
+
+    /// Set the programs whose instructions may precede a refresh in a transaction.
+    /// This requires the admin keypair
+    #[clap()]
+    SetAllowedPrecedingPrograms {
+        /// Pubkeys of the allowed programs, none to only allow compute budget instructions
+        #[clap(long, multiple_values = true, parse(try_from_str))]
+        programs: Vec<Pubkey>,
+    },
+
+    /// Create the TWAPs account of the feed, required by `ScopeTwap` entries. Feeds record no
+    /// TWAPs by default, it has to be done before resizing the feed. This requires the admin keypair
+    #[clap()]
+    InitOracleTwaps,
+
+    /// Grow a mappings account created before per entry generic data to the current layout.
+    /// This requires the admin keypair, which pays the additional rent
+    #[clap()]
+    MigrateOracleMappings,
+
+    /// Grow the feed to hold more than the default 512 entries.
+    /// This requires the admin keypair, which pays the rent of the new entries
+    #[clap()]
+    ResizeFeed {
+        /// New number of entries of the feed
+        #[clap(long)]
+        num_entries: u16,
+    },
+
+    /// Freeze an entry, or pause the whole feed if no token is provided.
+    /// This requires the admin or the guardian keypair
+    #[clap()]
+    Freeze {
+        /// Index of the entry to freeze
+        #[clap(long)]
+        token: Option<u16>,
+    },
+
+    /// Unfreeze an entry, or unpause the whole feed if no token is provided.
+    /// This requires the admin keypair
+    #[clap()]
+    Unfreeze {
+        /// Index of the entry to unfreeze
+        #[clap(long)]
+        token: Option<u16>,
+    },
 }
 
 #[tokio::main]
@@ -200,10 +270,25 @@ async fn main() -> Result<()> {
                 .await
             }
             Actions::GetPubkeys { mapping } => get_pubkeys(&mut scope, &mapping).await,
+            Actions::SetAdminCached { new_admin } => scope.set_admin_cached(&new_admin).await,
+            Actions::ApproveAdminCached => scope.approve_admin_cached().await,
+            Actions::SetGuardian { new_guardian } => scope.set_guardian(&new_guardian).await,
+            Actions::SetAllowedPrecedingPrograms { programs } => {
+                scope.set_allowed_preceding_programs(programs).await
+            }
+            Actions::InitOracleTwaps => scope.init_oracle_twaps().await,
+            Actions::MigrateOracleMappings => scope.migrate_oracle_mappings().await,
+            Actions::ResizeFeed { num_entries } => scope.resize_feed(num_entries).await,
+            Actions::Freeze { token } => scope.update_freeze(freeze_target(token), true).await,
+            Actions::Unfreeze { token } => scope.update_freeze(freeze_target(token), false).await,
         }
     }
 }
 
+fn freeze_target(token: Option<u16>) -> FreezeTarget {
+    token.map_or(FreezeTarget::Feed, FreezeTarget::Entry)
+}
+
 async fn init<T: AsyncClient, S: Signer>(
     client: OrbitLink<T, S>,
     program_id: &Pubkey,
@@ -0,0 +1,156 @@
+//! Implementation of helper for the spot price of concentrated liquidity pools
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::{
+    anchor_lang::{__private::bytemuck, AccountDeserialize},
+    solana_sdk::clock,
+};
+use anyhow::{anyhow, Context, Result};
+use orbit_link::async_client::AsyncClient;
+use scope::{
+    anchor_lang::prelude::Pubkey,
+    oracles::{
+        clmm::{self, ClmmConfig, ClmmPool},
+        OracleGenericData, OracleType,
+    },
+    yvaults::{
+        raydium_amm_v3::states::PoolState as RaydiumPool,
+        whirlpool::state::Whirlpool as OrcaWhirlpool,
+    },
+    DatedPrice, GENERIC_DATA_SIZE,
+};
+
+use super::{onchain_max_age, OracleHelper, TokenEntry};
+use crate::config::{generic_data::generic_data_from_config, TokenConfig};
+
+#[derive(Debug)]
+pub struct ClmmOracle {
+    label: String,
+    /// Pubkey to the orca whirlpool or raydium clmm pool account
+    mapping: Pubkey,
+
+    /// Extra accounts are:
+    /// 0. The mint of token A.
+    /// 1. The mint of token B.
+    /// 2. The observation account of a Raydium pool, only for a time-weighted price.
+    extra_accounts: Vec<Pubkey>,
+
+    /// Pool kind, direction, minimum depth and TWAP duration of the price
+    generic_data: [u8; GENERIC_DATA_SIZE],
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    /// Max age set onchain, if configured
+    onchain_max_age: Option<u32>,
+
+    /// Configured max relative change per slot (in bps)
+    max_change_bps_per_slot: u16,
+
+    /// Configured max confidence interval (in bps)
+    confidence_bps: u16,
+}
+
+impl ClmmOracle {
+    pub async fn new(
+        conf: &TokenConfig,
+        default_max_age: clock::Slot,
+        rpc: &dyn AsyncClient,
+    ) -> Result<Self> {
+        let mapping = conf.oracle_mapping;
+        let generic_data = generic_data_from_config(conf)?;
+        let config = ClmmConfig::from_generic_data(&generic_data);
+        let pool = clmm::validate_config(&config)
+            .and_then(|_| config.pool())
+            .map_err(|e| anyhow!("Invalid CLMM configuration for {}: {e:?}", conf.label))?;
+
+        let pool_account_raw = rpc
+            .get_account(&mapping)
+            .await
+            .context("Retrieving CLMM pool account")?;
+        let extra_accounts = match pool {
+            ClmmPool::OrcaWhirlpool => {
+                let whirlpool =
+                    OrcaWhirlpool::try_deserialize(&mut pool_account_raw.data.as_slice())
+                        .context("Deserializing orca whirlpool")?;
+                vec![whirlpool.token_mint_a, whirlpool.token_mint_b]
+            }
+            ClmmPool::RaydiumClmm => {
+                let pool_state: &RaydiumPool = bytemuck::from_bytes(&pool_account_raw.data[8..]);
+                let mut accounts = vec![pool_state.token_mint_0, pool_state.token_mint_1];
+                if config.is_twap() {
+                    accounts.push(pool_state.observation_key);
+                }
+                accounts
+            }
+        };
+
+        Ok(Self {
+            label: conf.label.clone(),
+            mapping,
+            extra_accounts,
+            generic_data,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            onchain_max_age: onchain_max_age(conf)?,
+            max_change_bps_per_slot: conf.max_change_bps_per_slot.map_or(0, |nz| nz.into()),
+            confidence_bps: conf.confidence_bps.map_or(0, |nz| nz.into()),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for ClmmOracle {
+    fn get_type(&self) -> OracleType {
+        OracleType::ClmmSpot
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        self.extra_accounts.len()
+    }
+
+    fn get_mapping_account(&self) -> &Pubkey {
+        &self.mapping
+    }
+
+    fn get_generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        self.generic_data
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        // Mints and observation account of a pool never change
+        Ok(self.extra_accounts.clone())
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_onchain_max_age(&self) -> Option<u32> {
+        self.onchain_max_age
+    }
+
+    fn get_max_change_bps_per_slot(&self) -> u16 {
+        self.max_change_bps_per_slot
+    }
+
+    fn get_confidence_bps(&self) -> u16 {
+        self.confidence_bps
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+impl Display for ClmmOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for ClmmOracle {}
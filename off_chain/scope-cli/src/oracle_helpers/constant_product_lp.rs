@@ -0,0 +1,141 @@
+//! Implementation of helper for the LP tokens of constant product AMM pools
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::clock;
+use anyhow::{anyhow, Context, Result};
+use orbit_link::async_client::AsyncClient;
+use scope::{
+    anchor_lang::prelude::Pubkey,
+    oracles::{
+        constant_product_lp::{ConstantProductLpConfig, PoolState, NB_EXTRA_ACCOUNTS},
+        OracleGenericData, OracleType,
+    },
+    DatedPrice, GENERIC_DATA_SIZE,
+};
+
+use super::{onchain_max_age, OracleHelper, TokenEntry};
+use crate::config::{generic_data::generic_data_from_config, TokenConfig};
+
+#[derive(Debug)]
+pub struct ConstantProductLpOracle {
+    label: String,
+    /// Pubkey to the orca token swap or raydium amm pool account
+    mapping: Pubkey,
+
+    /// Extra accounts are:
+    /// 0. The vault of token A.
+    /// 1. The vault of token B.
+    /// 2. The mint of token A.
+    /// 3. The mint of token B.
+    /// 4. The LP token mint.
+    extra_accounts: [Pubkey; NB_EXTRA_ACCOUNTS],
+
+    /// Pool kind and scope entries of the pool tokens
+    generic_data: [u8; GENERIC_DATA_SIZE],
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    /// Max age set onchain, if configured
+    onchain_max_age: Option<u32>,
+
+    /// Configured max relative change per slot (in bps)
+    max_change_bps_per_slot: u16,
+
+    /// Configured max confidence interval (in bps)
+    confidence_bps: u16,
+}
+
+impl ConstantProductLpOracle {
+    pub async fn new(
+        conf: &TokenConfig,
+        default_max_age: clock::Slot,
+        rpc: &dyn AsyncClient,
+    ) -> Result<Self> {
+        let mapping = conf.oracle_mapping;
+        let generic_data = generic_data_from_config(conf)?;
+        let pool = ConstantProductLpConfig::from_generic_data(&generic_data)
+            .pool()
+            .map_err(|e| anyhow!("Invalid LP configuration for {}: {e:?}", conf.label))?;
+
+        let pool_account_raw = rpc
+            .get_account(&mapping)
+            .await
+            .context("Retrieving constant product pool account")?;
+        let pool_state = PoolState::from_data(pool, &pool_account_raw.data)
+            .map_err(|e| anyhow!("Invalid pool account for {}: {e:?}", conf.label))?;
+        let extra_accounts = pool_state
+            .accounts
+            .to_vec()
+            .try_into()
+            .expect("Pool accounts count mismatch");
+
+        Ok(Self {
+            label: conf.label.clone(),
+            mapping,
+            extra_accounts,
+            generic_data,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            onchain_max_age: onchain_max_age(conf)?,
+            max_change_bps_per_slot: conf.max_change_bps_per_slot.map_or(0, |nz| nz.into()),
+            confidence_bps: conf.confidence_bps.map_or(0, |nz| nz.into()),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for ConstantProductLpOracle {
+    fn get_type(&self) -> OracleType {
+        OracleType::ConstantProductLp
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        NB_EXTRA_ACCOUNTS
+    }
+
+    fn get_mapping_account(&self) -> &Pubkey {
+        &self.mapping
+    }
+
+    fn get_generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        self.generic_data
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        // Vaults and mints of a pool never change
+        Ok(self.extra_accounts.to_vec())
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_onchain_max_age(&self) -> Option<u32> {
+        self.onchain_max_age
+    }
+
+    fn get_max_change_bps_per_slot(&self) -> u16 {
+        self.max_change_bps_per_slot
+    }
+
+    fn get_confidence_bps(&self) -> u16 {
+        self.confidence_bps
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+impl Display for ConstantProductLpOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for ConstantProductLpOracle {}
@@ -12,7 +12,7 @@ use scope::{
     DatedPrice,
 };
 
-use super::{OracleHelper, TokenEntry};
+use super::{onchain_max_age, OracleHelper, TokenEntry};
 use crate::config::TokenConfig;
 
 const NB_EXTRA_ACCOUNT: usize = 5;
@@ -33,6 +33,15 @@ pub struct KTokenOracle {
 
     /// Configured max age
     max_age: clock::Slot,
+
+    /// Max age set onchain, if configured
+    onchain_max_age: Option<u32>,
+
+    /// Configured max relative change per slot (in bps)
+    max_change_bps_per_slot: u16,
+
+    /// Configured max confidence interval (in bps)
+    confidence_bps: u16,
 }
 
 impl KTokenOracle {
@@ -66,6 +75,9 @@ impl KTokenOracle {
             label: conf.label.clone(),
             mapping,
             max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            onchain_max_age: onchain_max_age(conf)?,
+            max_change_bps_per_slot: conf.max_change_bps_per_slot.map_or(0, |nz| nz.into()),
+            confidence_bps: conf.confidence_bps.map_or(0, |nz| nz.into()),
             extra_accounts: [global_config, collateral_infos, pool, position, prices],
         })
     }
@@ -104,6 +116,18 @@ impl OracleHelper for KTokenOracle {
         self.max_age
     }
 
+    fn get_onchain_max_age(&self) -> Option<u32> {
+        self.onchain_max_age
+    }
+
+    fn get_max_change_bps_per_slot(&self) -> u16 {
+        self.max_change_bps_per_slot
+    }
+
+    fn get_confidence_bps(&self) -> u16 {
+        self.confidence_bps
+    }
+
     async fn need_refresh(
         &self,
         _scope_price: &DatedPrice,
@@ -9,12 +9,16 @@
 //! - [`std::fmt::Debug`] for detailled debug and error logs.
 
 use anchor_client::solana_sdk::clock;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use orbit_link::async_client::AsyncClient;
-use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice};
+use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice, GENERIC_DATA_SIZE};
 
+#[cfg(feature = "yvaults")]
+pub mod clmm;
+pub mod constant_product_lp;
 #[cfg(feature = "yvaults")]
 pub mod ktokens;
+pub mod pyth_pull;
 pub mod single_account_oracle;
 
 pub use single_account_oracle::SingleAccountOracle;
@@ -40,6 +44,11 @@ pub trait OracleHelper: Sync {
     /// mapping).
     fn get_mapping_account(&self) -> &Pubkey;
 
+    /// Get the oracle type specific data stored in the entry of the oracle mapping
+    fn get_generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        [0; GENERIC_DATA_SIZE]
+    }
+
     /// Get the extra accounts needed for the refresh price ix
     async fn get_extra_accounts(&self, rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>>;
 
@@ -52,6 +61,16 @@ pub trait OracleHelper: Sync {
     /// being considered stalled by the user of the scope feed.
     fn get_max_age(&self) -> clock::Slot;
 
+    /// Get the max age (in slots) to set onchain for the consumers of the price, `None` if the
+    /// configuration of the token does not set one (the default max age is only used by the crank).
+    fn get_onchain_max_age(&self) -> Option<u32>;
+
+    /// Get the max relative change of the price per slot (in bps) accepted onchain, 0 if unlimited.
+    fn get_max_change_bps_per_slot(&self) -> u16;
+
+    /// Get the max confidence interval relative to the price (in bps) accepted onchain, 0 for the default.
+    fn get_confidence_bps(&self) -> u16;
+
     /// Tell if a price has changed and need to be refreshed.
     ///
     /// **Note:** For prices that constantly changes implementation
@@ -64,6 +83,21 @@ pub trait OracleHelper: Sync {
     }
 }
 
+/// Max age set in the configuration of the token, as stored onchain
+pub fn onchain_max_age(token_conf: &TokenConfig) -> Result<Option<u32>> {
+    token_conf
+        .max_age
+        .map(|max_age| {
+            u32::try_from(max_age.get()).map_err(|_| {
+                anyhow!(
+                    "Max age of {} does not fit onchain: {max_age}",
+                    token_conf.label
+                )
+            })
+        })
+        .transpose()
+}
+
 pub async fn entry_from_config(
     token_conf: &TokenConfig,
     default_max_age: clock::Slot,
@@ -75,7 +109,34 @@ pub async fn entry_from_config(
         | OracleType::SwitchboardV2
         | OracleType::CToken
         | OracleType::SplStake
-        | OracleType::PythEMA => Box::new(SingleAccountOracle::new(token_conf, default_max_age)),
+        | OracleType::PythEMA
+        | OracleType::ScopeMedian
+        | OracleType::ScopeTwap
+        | OracleType::FixedPrice
+        | OracleType::ScopeChain
+        | OracleType::ScopeReciprocal
+        | OracleType::ScopeRatio
+        | OracleType::SwitchboardOnDemand
+        | OracleType::MsolStake
+        | OracleType::KaminoLendCollateral
+        | OracleType::MarginfiCollateral => {
+            Box::new(SingleAccountOracle::new(token_conf, default_max_age)?)
+        }
+        OracleType::PythPull => {
+            Box::new(pyth_pull::PythPullOracle::new(token_conf, default_max_age)?)
+        }
+        OracleType::ConstantProductLp => Box::new(
+            constant_product_lp::ConstantProductLpOracle::new(token_conf, default_max_age, rpc)
+                .await?,
+        ),
+        #[cfg(feature = "yvaults")]
+        OracleType::ClmmSpot => {
+            Box::new(clmm::ClmmOracle::new(token_conf, default_max_age, rpc).await?)
+        }
+        #[cfg(not(feature = "yvaults"))]
+        OracleType::ClmmSpot => {
+            panic!("yvaults feature is not enabled, ClmmOracle is not available")
+        }
         #[cfg(feature = "yvaults")]
         OracleType::KToken => {
             Box::new(ktokens::KTokenOracle::new(token_conf, default_max_age, rpc).await?)
@@ -0,0 +1,98 @@
+//! Implementation of helper for pyth pull prices (`PriceUpdateV2` accounts)
+//!
+//! Pull prices are only updated when someone posts a new price update, refreshing
+//! the scope price in between would only copy the same price again.
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::clock;
+use anyhow::{Context, Result};
+use orbit_link::async_client::AsyncClient;
+use scope::{
+    anchor_lang::{prelude::Pubkey, AccountDeserialize},
+    oracles::{pyth_pull::PriceUpdateV2, OracleType},
+    DatedPrice, GENERIC_DATA_SIZE,
+};
+
+use super::{OracleHelper, SingleAccountOracle, TokenEntry};
+use crate::config::TokenConfig;
+
+pub struct PythPullOracle {
+    /// The price update account is the only account needed for a refresh
+    inner: SingleAccountOracle,
+}
+
+impl PythPullOracle {
+    pub fn new(conf: &TokenConfig, default_max_age: clock::Slot) -> Result<Self> {
+        Ok(Self {
+            inner: SingleAccountOracle::new(conf, default_max_age)?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for PythPullOracle {
+    fn get_type(&self) -> OracleType {
+        OracleType::PythPull
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        0_usize
+    }
+
+    fn get_mapping_account(&self) -> &Pubkey {
+        self.inner.get_mapping_account()
+    }
+
+    fn get_generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        self.inner.get_generic_data()
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(Vec::with_capacity(0))
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.inner.get_max_age()
+    }
+
+    fn get_onchain_max_age(&self) -> Option<u32> {
+        self.inner.get_onchain_max_age()
+    }
+
+    fn get_max_change_bps_per_slot(&self) -> u16 {
+        self.inner.get_max_change_bps_per_slot()
+    }
+
+    fn get_confidence_bps(&self) -> u16 {
+        self.inner.get_confidence_bps()
+    }
+
+    async fn need_refresh(&self, scope_price: &DatedPrice, rpc: &dyn AsyncClient) -> Result<bool> {
+        let price_update_raw = rpc
+            .get_account(self.get_mapping_account())
+            .await
+            .context("Retrieving pyth price update account")?;
+        let price_update = PriceUpdateV2::try_deserialize(&mut price_update_raw.data.as_slice())
+            .context("Deserializing pyth price update account")?;
+
+        Ok(price_update.posted_slot > scope_price.last_updated_slot)
+    }
+}
+
+impl Display for PythPullOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner, f)
+    }
+}
+
+impl Debug for PythPullOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PythPullOracle")
+            .field("label", &self.inner.label)
+            .field("oracle_account", &self.inner.oracle_account)
+            .finish()
+    }
+}
+
+impl TokenEntry for PythPullOracle {}
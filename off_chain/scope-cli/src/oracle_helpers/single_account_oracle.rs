@@ -1,31 +1,40 @@
 //! Provides a generic implementation for all oracle prices that only requires
 //! one oracle account to perform a price refresh (such as pyth and switchboard)
+//! or that compute their price from other scope prices.
 
 use std::fmt::{Debug, Display};
 
 use anchor_client::solana_sdk::clock;
 use anyhow::Result;
 use orbit_link::async_client::AsyncClient;
-use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice};
+use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice, GENERIC_DATA_SIZE};
 
-use super::{OracleHelper, TokenEntry};
-use crate::config::TokenConfig;
+use super::{onchain_max_age, OracleHelper, TokenEntry};
+use crate::config::{generic_data::generic_data_from_config, TokenConfig};
 
 pub struct SingleAccountOracle {
     pub label: String,
     pub oracle_account: Pubkey,
     pub oracle_type: OracleType,
     pub max_age: clock::Slot,
+    pub onchain_max_age: Option<u32>,
+    pub max_change_bps_per_slot: u16,
+    pub confidence_bps: u16,
+    pub generic_data: [u8; GENERIC_DATA_SIZE],
 }
 
 impl SingleAccountOracle {
-    pub fn new(conf: &TokenConfig, default_max_age: clock::Slot) -> Self {
-        Self {
+    pub fn new(conf: &TokenConfig, default_max_age: clock::Slot) -> Result<Self> {
+        Ok(Self {
             label: conf.label.clone(),
             oracle_account: conf.oracle_mapping,
             oracle_type: conf.oracle_type,
             max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
-        }
+            onchain_max_age: onchain_max_age(conf)?,
+            max_change_bps_per_slot: conf.max_change_bps_per_slot.map_or(0, |nz| nz.into()),
+            confidence_bps: conf.confidence_bps.map_or(0, |nz| nz.into()),
+            generic_data: generic_data_from_config(conf)?,
+        })
     }
 }
 
@@ -43,6 +52,10 @@ impl OracleHelper for SingleAccountOracle {
         &self.oracle_account
     }
 
+    fn get_generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        self.generic_data
+    }
+
     async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
         Ok(Vec::with_capacity(0))
     }
@@ -51,6 +64,18 @@ impl OracleHelper for SingleAccountOracle {
         self.max_age
     }
 
+    fn get_onchain_max_age(&self) -> Option<u32> {
+        self.onchain_max_age
+    }
+
+    fn get_max_change_bps_per_slot(&self) -> u16 {
+        self.max_change_bps_per_slot
+    }
+
+    fn get_confidence_bps(&self) -> u16 {
+        self.confidence_bps
+    }
+
     async fn need_refresh(
         &self,
         _scope_price: &DatedPrice,
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 use std::mem::size_of;
+use std::num::{NonZeroU16, NonZeroU64};
 
 use anchor_client::{
     anchor_lang::ToAccountMetas,
@@ -17,11 +18,17 @@ use anyhow::{anyhow, bail, Context, Result};
 use futures::future::join_all;
 use nohash_hasher::IntMap;
 use orbit_link::{async_client::AsyncClient, OrbitLink};
-use scope::{accounts, instruction, Configuration, OracleMappings, OraclePrices};
+use scope::{
+    accounts,
+    anchor_lang::Discriminator,
+    extension::{self, FeedAccount, MappingEntry},
+    instruction, Configuration, DatedPrice, EntryConfigUpdate, FreezeTarget, OracleMappings,
+    OraclePrices, OracleTwaps, GENERIC_DATA_SIZE, MAX_ENTRIES,
+};
 use tracing::{debug, error, info, trace, warn};
 
 use crate::{
-    config::{ScopeConfig, TokenConfig, TokenList},
+    config::{generic_data::set_config_from_generic_data, ScopeConfig, TokenConfig, TokenList},
     oracle_helpers::{entry_from_config, TokenEntry},
     utils::{get_clock, price_to_f64},
 };
@@ -30,6 +37,8 @@ use crate::{
 const MAX_REFRESH_CHUNK_SIZE: usize = 24;
 /// Token gap to max age that still trigger refresh (in slots)
 const REMAINING_AGE_TO_REFRESH: i64 = 10;
+/// Max number of entries added to a feed per resize ix (limited by the size of the TWAP entries)
+const MAX_RESIZE_STEP: usize = 9;
 
 type TokenEntryList = IntMap<u16, Box<dyn TokenEntry>>;
 
@@ -40,6 +49,7 @@ pub struct ScopeClient<T: AsyncClient, S: Signer> {
     configuration_acc: Pubkey,
     oracle_prices_acc: Pubkey,
     oracle_mappings_acc: Pubkey,
+    oracle_twaps_acc: Pubkey,
     tokens: TokenEntryList,
 }
 
@@ -58,11 +68,11 @@ where
         let (configuration_acc, _) =
             Pubkey::find_program_address(&[b"conf", price_feed.as_bytes()], &program_id);
 
-        let Configuration { oracle_mappings, oracle_prices, .. } = client
+        let Configuration { oracle_mappings, oracle_prices, oracle_twaps, .. } = client
             .get_anchor_account::<Configuration>(&configuration_acc).await
             .context("Error while retrieving program configuration account, the program might be uninitialized")?;
 
-        debug!(%oracle_prices, %oracle_mappings, %configuration_acc, %price_feed);
+        debug!(%oracle_prices, %oracle_mappings, %oracle_twaps, %configuration_acc, %price_feed);
 
         Ok(Self {
             client,
@@ -71,6 +81,7 @@ where
             configuration_acc,
             oracle_prices_acc: oracle_prices,
             oracle_mappings_acc: oracle_mappings,
+            oracle_twaps_acc: oracle_twaps,
             tokens: IntMap::default(),
         })
     }
@@ -109,6 +120,7 @@ where
             configuration_acc,
             oracle_prices_acc: oracle_prices_acc.pubkey(),
             oracle_mappings_acc: oracle_mappings_acc.pubkey(),
+            oracle_twaps_acc: Pubkey::default(),
             tokens: IntMap::default(),
         })
     }
@@ -135,20 +147,57 @@ where
     /// Update the remote oracle mapping from the local
     pub async fn upload_oracle_mapping(&self) -> Result<()> {
         let program_mapping = self.get_program_mapping().await?;
-        let onchain_accounts_mapping = program_mapping.price_info_accounts;
-        let onchain_price_type_mapping = program_mapping.price_types;
 
         // For all "token" local and remote
         for (&token_idx, local_entry) in &self.tokens {
-            let idx: usize = token_idx.try_into().unwrap();
-            let rem_mapping = &onchain_accounts_mapping[idx];
-            let rem_price_type = onchain_price_type_mapping[idx];
+            let idx: usize = token_idx.into();
+            let remote = program_mapping.get(idx).ok_or_else(|| {
+                anyhow!(
+                    "Token {token_idx} is beyond the {} entries of the feed, resize it first",
+                    program_mapping.len()
+                )
+            })?;
             // Update remote in case of difference
             let local_mapping_pk = local_entry.get_mapping_account();
             let loc_price_type_u8: u8 = local_entry.get_type().into();
-            if rem_mapping != local_mapping_pk || rem_price_type != loc_price_type_u8 {
-                self.ix_update_mapping(local_mapping_pk, token_idx.into(), loc_price_type_u8)
+            let loc_generic_data = local_entry.get_generic_data();
+            if remote.price_info_account != *local_mapping_pk
+                || remote.price_type != loc_price_type_u8
+                || remote.generic_data != loc_generic_data
+            {
+                self.ix_update_mapping(
+                    local_mapping_pk,
+                    token_idx.into(),
+                    loc_price_type_u8,
+                    loc_generic_data,
+                )
+                .await?;
+            }
+            // Only an explicitly configured max age is set onchain for the consumers
+            if let Some(loc_max_age) = local_entry.get_onchain_max_age() {
+                if remote.max_age_slots != loc_max_age {
+                    self.ix_update_entry_config(
+                        token_idx.into(),
+                        EntryConfigUpdate::MaxAgeSlots(loc_max_age),
+                    )
                     .await?;
+                }
+            }
+            let loc_max_change = local_entry.get_max_change_bps_per_slot();
+            if remote.max_change_bps_per_slot != loc_max_change {
+                self.ix_update_entry_config(
+                    token_idx.into(),
+                    EntryConfigUpdate::MaxChangeBpsPerSlot(loc_max_change),
+                )
+                .await?;
+            }
+            let loc_confidence = local_entry.get_confidence_bps();
+            if remote.confidence_bps != loc_confidence {
+                self.ix_update_entry_config(
+                    token_idx.into(),
+                    EntryConfigUpdate::ConfidenceBps(loc_confidence),
+                )
+                .await?;
             }
         }
         Ok(())
@@ -157,27 +206,36 @@ where
     /// Update the local oracle mapping from the on-chain version
     pub async fn download_oracle_mapping(&mut self, default_max_age: clock::Slot) -> Result<()> {
         let onchain_oracle_mapping = self.get_program_mapping().await?;
-        let onchain_mapping = onchain_oracle_mapping.price_info_accounts;
-        let onchain_types = onchain_oracle_mapping.price_types;
 
         let zero_pk = Pubkey::default();
         let rpc = self.get_rpc();
 
-        let entry_builders = onchain_mapping
-            .iter()
+        let entry_builders = onchain_oracle_mapping
+            .into_iter()
             .enumerate()
-            .zip(onchain_types)
-            .filter(|((_, &oracle_mapping), _)| oracle_mapping != zero_pk)
-            .map(|((idx, &oracle_mapping), oracle_type)| async move {
-                let id: u16 = idx.try_into()?;
-                let oracle_conf = TokenConfig {
-                    label: "".to_string(),
-                    oracle_type: oracle_type.try_into()?,
-                    max_age: None,
-                    oracle_mapping,
-                };
-                let entry = entry_from_config(&oracle_conf, default_max_age, rpc).await?;
-                Result::<(u16, Box<dyn TokenEntry>)>::Ok((id, entry))
+            .filter(|(_, mapping)| mapping.price_info_account != zero_pk)
+            .map(|(idx, mapping)| {
+                let oracle_mapping = mapping.price_info_account;
+                let oracle_type = mapping.price_type;
+                let generic_data = mapping.generic_data;
+                let max_age = mapping.max_age_slots;
+                let max_change = mapping.max_change_bps_per_slot;
+                let confidence = mapping.confidence_bps;
+                async move {
+                    let id: u16 = idx.try_into()?;
+                    let mut oracle_conf = TokenConfig {
+                        label: "".to_string(),
+                        oracle_type: oracle_type.try_into()?,
+                        max_age: NonZeroU64::new(max_age.into()),
+                        max_change_bps_per_slot: NonZeroU16::new(max_change),
+                        confidence_bps: NonZeroU16::new(confidence),
+                        oracle_mapping,
+                        ..Default::default()
+                    };
+                    set_config_from_generic_data(&mut oracle_conf, &generic_data);
+                    let entry = entry_from_config(&oracle_conf, default_max_age, rpc).await?;
+                    Result::<(u16, Box<dyn TokenEntry>)>::Ok((id, entry))
+                }
             });
 
         self.tokens = join_all(entry_builders)
@@ -193,15 +251,17 @@ where
             .tokens
             .iter()
             .map(|(id, entry)| {
-                (
-                    *id,
-                    TokenConfig {
-                        label: entry.to_string(),
-                        oracle_mapping: *entry.get_mapping_account(),
-                        oracle_type: entry.get_type(),
-                        max_age: None,
-                    },
-                )
+                let mut token_conf = TokenConfig {
+                    label: entry.to_string(),
+                    oracle_mapping: *entry.get_mapping_account(),
+                    oracle_type: entry.get_type(),
+                    max_age: NonZeroU64::new(entry.get_max_age()),
+                    max_change_bps_per_slot: NonZeroU16::new(entry.get_max_change_bps_per_slot()),
+                    confidence_bps: NonZeroU16::new(entry.get_confidence_bps()),
+                    ..Default::default()
+                };
+                set_config_from_generic_data(&mut token_conf, &entry.get_generic_data());
+                (*id, token_conf)
             })
             .collect();
         Ok(ScopeConfig {
@@ -253,6 +313,10 @@ where
     /// if some room is left.
     #[tracing::instrument(skip(self))]
     pub async fn refresh_old_prices(&self) -> Result<()> {
+        if self.get_configuration().await?.paused != 0 {
+            warn!("Price feed is paused, skipping refresh");
+            return Ok(());
+        }
         let mut prices_ttl: Vec<(u16, i64)> = self.get_prices_ttl().await?.collect();
         // TODO: filter prices that cannot be refreshed
         // Sort the prices ttl from the smallest to biggest.
@@ -298,6 +362,8 @@ where
     ///
     /// i.e. the number of slot until at the price currently known by scope has reached its `max_age`
     /// Note: negative `price_ttl` gives how much expired is the price
+    /// Note: frozen prices are skipped as they cannot be refreshed
+    /// Note: tokens beyond the size of the feed are skipped
     pub async fn get_prices_ttl(&self) -> Result<impl Iterator<Item = (u16, i64)> + '_> {
         let oracle_prices = self.get_prices().await?;
 
@@ -305,8 +371,11 @@ where
 
         let current_slot = get_clock(rpc).await?.slot;
 
-        let it = self.tokens.iter().map(move |(id, entry)| {
-            let price = &oracle_prices.prices[usize::from(*id)];
+        let it = self.tokens.iter().filter_map(move |(id, entry)| {
+            let price = oracle_prices.get(usize::from(*id))?;
+            if price.is_frozen() {
+                return None;
+            }
             let price_slot = price.last_updated_slot;
             // default to age == 0 if "updated in the future"
             let age = current_slot.saturating_sub(price_slot);
@@ -321,7 +390,7 @@ where
                 // No overflow possible thanks to the previous checks
                 entry.get_max_age() as i64 - age as i64
             };
-            (*id, remaining_slots)
+            Some((*id, remaining_slots))
         });
         Ok(it)
     }
@@ -343,10 +412,19 @@ where
     /// Log current prices
     /// Note: this uses local mapping
     pub async fn log_prices(&self, current_slot: u64) -> Result<()> {
-        let prices = self.get_prices().await?.prices;
+        let prices = self.get_prices().await?;
 
         for (&id, entry) in &self.tokens {
-            let dated_price = prices[usize::from(id)];
+            let dated_price = match prices.get(usize::from(id)) {
+                Some(&dated_price) => dated_price,
+                None => {
+                    println!(
+                        "id={id}, entry='{entry}', not in the {} entries of the feed",
+                        prices.len()
+                    );
+                    continue;
+                }
+            };
             let price = price_to_f64(&dated_price.price);
             let exponent = (dated_price.price.exp + 1) as usize;
             let price_type = entry.get_type();
@@ -357,8 +435,17 @@ where
             } else {
                 format!("\x1b[32m{age_in_slots}\x1b[0m")
             };
+            // Errors are cleared by a successful refresh
+            let last_error = if dated_price.last_error_slot != 0 {
+                format!(
+                    ", last_error={}, last_error_slot={}",
+                    dated_price.last_error_code, dated_price.last_error_slot
+                )
+            } else {
+                String::new()
+            };
             // For easier parsing of these logs don't use tracing here.
-            println!("id={id}, entry='{entry}', price='{price:.exponent$}', price_type='{price_type:?}', age={age_in_slots}, age_c={age_string}, max_age={max_age}");
+            println!("id={id}, entry='{entry}', price='{price:.exponent$}', price_type='{price_type:?}', age={age_in_slots}, age_c={age_string}, max_age={max_age}{last_error}");
         }
         Ok(())
     }
@@ -401,22 +488,35 @@ where
         &self.client.client
     }
 
-    /// Get all prices
-    async fn get_prices(&self) -> Result<OraclePrices> {
-        let prices: OraclePrices = self
+    /// Get the feed configuration
+    async fn get_configuration(&self) -> Result<Configuration> {
+        let configuration: Configuration = self
             .client
-            .get_anchor_account(&self.oracle_prices_acc)
+            .get_anchor_account(&self.configuration_acc)
             .await?;
-        Ok(prices)
+        Ok(configuration)
     }
 
-    /// Get program oracle mapping
-    async fn get_program_mapping(&self) -> Result<OracleMappings> {
-        let mapping: OracleMappings = self
-            .client
-            .get_anchor_account(&self.oracle_mappings_acc)
-            .await?;
-        Ok(mapping)
+    /// Get all prices, including the entries added by resizing the feed
+    async fn get_prices(&self) -> Result<Vec<DatedPrice>> {
+        self.get_feed_entries::<OraclePrices>(&self.oracle_prices_acc)
+            .await
+    }
+
+    /// Get program oracle mapping, including the entries added by resizing the feed
+    async fn get_program_mapping(&self) -> Result<Vec<MappingEntry>> {
+        self.get_feed_entries::<OracleMappings>(&self.oracle_mappings_acc)
+            .await
+    }
+
+    /// Get all the entries of a feed account
+    async fn get_feed_entries<A: FeedAccount>(&self, account: &Pubkey) -> Result<Vec<A::Entry>> {
+        let data = self.get_rpc().get_account(account).await?.data;
+        if !data.starts_with(&A::DISCRIMINATOR) {
+            bail!("Account {account} has an invalid discriminator");
+        }
+        extension::read_entries::<A>(&data)
+            .map_err(|e| anyhow!("Could not read the entries of account {account}: {e:?}"))
     }
 
     #[tracing::instrument(skip(client))]
@@ -488,6 +588,7 @@ where
         oracle_account: &Pubkey,
         token: u64,
         price_type: u8,
+        generic_data: [u8; GENERIC_DATA_SIZE],
     ) -> Result<()> {
         let update_accounts = accounts::UpdateOracleMapping {
             admin: self.client.payer(),
@@ -502,10 +603,11 @@ where
             .add_anchor_ix(
                 &self.program_id,
                 update_accounts,
-                instruction::UpdateMapping {
+                instruction::UpdateMappingAndGenericData {
                     token,
                     price_type,
                     feed_name: self.feed_name.clone(),
+                    generic_data,
                 },
             )
             .build_with_budget_and_fee(&[])
@@ -528,6 +630,427 @@ where
         Ok(())
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn ix_update_entry_config(&self, token: u64, update: EntryConfigUpdate) -> Result<()> {
+        let update_accounts = accounts::UpdateEntryConfig {
+            admin: self.client.payer(),
+            configuration: self.configuration_acc,
+            oracle_mappings: self.oracle_mappings_acc,
+        };
+
+        let request = self.client.tx_builder();
+
+        let tx = request
+            .add_anchor_ix(
+                &self.program_id,
+                update_accounts,
+                instruction::UpdateEntryConfig {
+                    token,
+                    feed_name: self.feed_name.clone(),
+                    update,
+                },
+            )
+            .build_with_budget_and_fee(&[])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => info!(%signature, "Entry config updated successfully"),
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "Entry config update failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm entry config update transaction");
+                bail!("Could not confirm entry config update transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Propose a new admin, it will have to approve the handover with [`Self::approve_admin_cached`]
+    #[tracing::instrument(skip(self))]
+    pub async fn set_admin_cached(&self, new_admin: &Pubkey) -> Result<()> {
+        let set_accounts = accounts::SetAdminCached {
+            admin: self.client.payer(),
+            configuration: self.configuration_acc,
+        };
+
+        let tx = self
+            .client
+            .tx_builder()
+            .add_anchor_ix(
+                &self.program_id,
+                set_accounts,
+                instruction::SetAdminCached {
+                    new_admin: *new_admin,
+                    feed_name: self.feed_name.clone(),
+                },
+            )
+            .build_with_budget_and_fee(&[])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => info!(%signature, %new_admin, "Admin cached set successfully"),
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "Set admin cached failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm set admin cached transaction");
+                bail!("Could not confirm set admin cached transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Become the admin of the feed, the payer must be the admin cached
+    #[tracing::instrument(skip(self))]
+    pub async fn approve_admin_cached(&self) -> Result<()> {
+        let approve_accounts = accounts::ApproveAdminCached {
+            admin_cached: self.client.payer(),
+            configuration: self.configuration_acc,
+        };
+
+        let tx = self
+            .client
+            .tx_builder()
+            .add_anchor_ix(
+                &self.program_id,
+                approve_accounts,
+                instruction::ApproveAdminCached {
+                    feed_name: self.feed_name.clone(),
+                },
+            )
+            .build_with_budget_and_fee(&[])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => info!(%signature, "Admin handover approved successfully"),
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "Approve admin cached failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm approve admin cached transaction");
+                bail!("Could not confirm approve admin cached transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the guardian of the feed, the payer must be the admin
+    #[tracing::instrument(skip(self))]
+    pub async fn set_guardian(&self, new_guardian: &Pubkey) -> Result<()> {
+        let set_accounts = accounts::SetGuardian {
+            admin: self.client.payer(),
+            configuration: self.configuration_acc,
+        };
+
+        let tx = self
+            .client
+            .tx_builder()
+            .add_anchor_ix(
+                &self.program_id,
+                set_accounts,
+                instruction::SetGuardian {
+                    new_guardian: *new_guardian,
+                    feed_name: self.feed_name.clone(),
+                },
+            )
+            .build_with_budget_and_fee(&[])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => info!(%signature, %new_guardian, "Guardian set successfully"),
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "Set guardian failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm set guardian transaction");
+                bail!("Could not confirm set guardian transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the programs allowed before a refresh, the payer must be the admin
+    #[tracing::instrument(skip(self))]
+    pub async fn set_allowed_preceding_programs(&self, programs: Vec<Pubkey>) -> Result<()> {
+        let set_accounts = accounts::SetAllowedPrecedingPrograms {
+            admin: self.client.payer(),
+            configuration: self.configuration_acc,
+        };
+
+        let tx = self
+            .client
+            .tx_builder()
+            .add_anchor_ix(
+                &self.program_id,
+                set_accounts,
+                instruction::SetAllowedPrecedingPrograms {
+                    programs: programs.clone(),
+                    feed_name: self.feed_name.clone(),
+                },
+            )
+            .build_with_budget_and_fee(&[])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => {
+                info!(%signature, ?programs, "Allowed preceding programs set successfully")
+            }
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "Set allowed preceding programs failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm set allowed preceding programs transaction");
+                bail!("Could not confirm set allowed preceding programs transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the TWAPs account of the feed so its refreshes record TWAPs, the payer must be the admin
+    #[tracing::instrument(skip(self))]
+    pub async fn init_oracle_twaps(&mut self) -> Result<()> {
+        if self.oracle_twaps_acc != Pubkey::default() {
+            bail!(
+                "The feed already has the TWAPs account {}",
+                self.oracle_twaps_acc
+            );
+        }
+
+        let oracle_twaps_acc = Keypair::new();
+        let init_accounts = accounts::InitOracleTwaps {
+            admin: self.client.payer(),
+            configuration: self.configuration_acc,
+            oracle_prices: self.oracle_prices_acc,
+            oracle_mappings: self.oracle_mappings_acc,
+            oracle_twaps: oracle_twaps_acc.pubkey(),
+        };
+
+        let tx = self
+            .client
+            .tx_builder()
+            .add_ix_with_budget(
+                self.client
+                    .create_account_ix(
+                        &oracle_twaps_acc.pubkey(),
+                        size_of::<OracleTwaps>() + 8,
+                        &self.program_id,
+                    )
+                    .await?,
+                50_000,
+            )
+            .add_anchor_ix(
+                &self.program_id,
+                init_accounts,
+                instruction::InitOracleTwaps {
+                    feed_name: self.feed_name.clone(),
+                },
+            )
+            .build_with_budget_and_fee(&[&oracle_twaps_acc])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => {
+                info!(%signature, oracle_twaps = %oracle_twaps_acc.pubkey(), "TWAPs account initialized");
+                self.oracle_twaps_acc = oracle_twaps_acc.pubkey();
+            }
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "TWAPs account initialization failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm TWAPs account initialization transaction");
+                bail!("Could not confirm TWAPs account initialization transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Grow a legacy mappings account to the current layout, the payer must be the admin
+    ///
+    /// Accounts can only grow by 10 KiB per ix, the migration is repeated until the program
+    /// reports the account as migrated.
+    #[tracing::instrument(skip(self))]
+    pub async fn migrate_oracle_mappings(&self) -> Result<()> {
+        let target_size = extension::account_size::<OracleMappings>(MAX_ENTRIES);
+        loop {
+            let size = self
+                .get_rpc()
+                .get_account(&self.oracle_mappings_acc)
+                .await?
+                .data
+                .len();
+            if size >= target_size {
+                info!(size, "Oracle mappings account is migrated");
+                return Ok(());
+            }
+
+            let migrate_accounts = accounts::MigrateOracleMappings {
+                admin: self.client.payer(),
+                configuration: self.configuration_acc,
+                oracle_mappings: self.oracle_mappings_acc,
+                system_program: system_program::ID,
+            };
+
+            let tx = self
+                .client
+                .tx_builder()
+                .add_anchor_ix(
+                    &self.program_id,
+                    migrate_accounts,
+                    instruction::MigrateOracleMappings {
+                        feed_name: self.feed_name.clone(),
+                    },
+                )
+                .build_with_budget_and_fee(&[])
+                .await?;
+
+            let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+            match res {
+                Some(Ok(())) => info!(%signature, size, "Oracle mappings account grown"),
+                Some(Err(err)) => {
+                    error!(%signature, err = ?err, size, "Oracle mappings migration failed");
+                    bail!(err);
+                }
+                None => {
+                    error!(%signature, "Could not confirm oracle mappings migration transaction");
+                    bail!("Could not confirm oracle mappings migration transaction");
+                }
+            }
+        }
+    }
+
+    /// Grow the feed to hold `num_entries` entries, the payer must be the admin
+    ///
+    /// Accounts can only grow by a few entries per ix, the resize is split in several ixs.
+    #[tracing::instrument(skip(self))]
+    pub async fn resize_feed(&self, num_entries: u16) -> Result<()> {
+        let current_num_entries = self.get_prices().await?.len();
+        let target = usize::from(num_entries);
+        if target <= current_num_entries {
+            bail!("The feed already holds {current_num_entries} entries");
+        }
+
+        let mut size = current_num_entries;
+        while size < target {
+            size = target.min(size + MAX_RESIZE_STEP);
+            let resize_accounts = accounts::ResizeFeed {
+                admin: self.client.payer(),
+                configuration: self.configuration_acc,
+                oracle_prices: self.oracle_prices_acc,
+                oracle_mappings: self.oracle_mappings_acc,
+                oracle_twaps: self.oracle_twaps_account(),
+                system_program: system_program::ID,
+            };
+
+            let tx = self
+                .client
+                .tx_builder()
+                .add_anchor_ix(
+                    &self.program_id,
+                    resize_accounts,
+                    instruction::ResizeFeed {
+                        num_entries: size.try_into()?,
+                        feed_name: self.feed_name.clone(),
+                    },
+                )
+                .build_with_budget_and_fee(&[])
+                .await?;
+
+            let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+            match res {
+                Some(Ok(())) => info!(%signature, size, "Feed resized successfully"),
+                Some(Err(err)) => {
+                    error!(%signature, err = ?err, size, "Feed resize failed");
+                    bail!(err);
+                }
+                None => {
+                    error!(%signature, "Could not confirm feed resize transaction");
+                    bail!("Could not confirm feed resize transaction");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Freeze or unfreeze an entry or the whole feed
+    ///
+    /// The payer must be the admin, or the guardian to freeze.
+    #[tracing::instrument(skip(self))]
+    pub async fn update_freeze(&self, target: FreezeTarget, frozen: bool) -> Result<()> {
+        let freeze_accounts = accounts::UpdateFreeze {
+            authority: self.client.payer(),
+            configuration: self.configuration_acc,
+            oracle_prices: self.oracle_prices_acc,
+        };
+
+        let tx = self
+            .client
+            .tx_builder()
+            .add_anchor_ix(
+                &self.program_id,
+                freeze_accounts,
+                instruction::UpdateFreeze {
+                    feed_name: self.feed_name.clone(),
+                    target,
+                    frozen,
+                },
+            )
+            .build_with_budget_and_fee(&[])
+            .await?;
+
+        let (signature, res) = self.client.send_retry_and_confirm_transaction(tx).await?;
+
+        match res {
+            Some(Ok(())) => info!(%signature, ?target, frozen, "Freeze updated successfully"),
+            Some(Err(err)) => {
+                error!(%signature, err = ?err, "Freeze update failed");
+                bail!(err);
+            }
+            None => {
+                error!(%signature, "Could not confirm freeze update transaction");
+                bail!("Could not confirm freeze update transaction");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// TWAPs account expected by the program, the prices account if the feed does not record TWAPs
+    fn oracle_twaps_account(&self) -> Pubkey {
+        if self.oracle_twaps_acc == Pubkey::default() {
+            self.oracle_prices_acc
+        } else {
+            self.oracle_twaps_acc
+        }
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn ix_refresh_one_price(&self, token: u16) -> Result<()> {
         let entry = self
@@ -537,6 +1060,8 @@ where
         let mut refresh_accounts = accounts::RefreshOne {
             oracle_prices: self.oracle_prices_acc,
             oracle_mappings: self.oracle_mappings_acc,
+            oracle_twaps: self.oracle_twaps_account(),
+            configuration: self.configuration_acc,
             price_info: *entry.get_mapping_account(),
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
@@ -579,6 +1104,8 @@ where
         let mut refresh_accounts = accounts::RefreshList {
             oracle_prices: self.oracle_prices_acc,
             oracle_mappings: self.oracle_mappings_acc,
+            oracle_twaps: self.oracle_twaps_account(),
+            configuration: self.configuration_acc,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -18,8 +18,10 @@ pub fn initialize(
             slot: Clock::get()?.slot,
             stale: false,
         },
-        lending_market: Pubkey::default(),
+        // Scope only checks that the reserve is attached to a market and a mint
+        lending_market: *ctoken_account.key,
         liquidity: ReserveLiquidity {
+            mint_pubkey: *ctoken_account.key,
             available_amount: total_liquidity,
             ..Default::default()
         },
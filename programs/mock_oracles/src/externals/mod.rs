@@ -1,2 +1,3 @@
 pub mod solend;
+pub mod switchboard_on_demand;
 pub mod switchboard_v2;
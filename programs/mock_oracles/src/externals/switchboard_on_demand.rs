@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+pub const DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+/// Number of decimals of the values of pull feeds
+pub const PRECISION: u32 = 18;
+
+#[zero_copy]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct OracleSubmission {
+    pub oracle: Pubkey,
+    pub slot: u64,
+    pub landed_at: u64,
+    pub value: i128,
+}
+
+#[zero_copy]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq)]
+pub struct CompactResult {
+    pub std_dev: f32,
+    pub mean: f32,
+    pub slot: u64,
+}
+
+#[zero_copy]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct CurrentResult {
+    pub value: i128,
+    pub std_dev: i128,
+    pub mean: i128,
+    pub range: i128,
+    pub min_value: i128,
+    pub max_value: i128,
+    pub num_samples: u8,
+    pub submission_idx: u8,
+    pub padding1: [u8; 6],
+    pub slot: u64,
+    pub min_slot: u64,
+    pub max_slot: u64,
+}
+
+#[zero_copy]
+#[repr(packed)]
+#[derive(Debug)]
+pub struct PullFeedAccountData {
+    pub submissions: [OracleSubmission; 32],
+    pub authority: Pubkey,
+    pub queue: Pubkey,
+    pub feed_hash: [u8; 32],
+    pub initialized_at: i64,
+    pub permissions: u64,
+    pub max_variance: u64,
+    pub min_responses: u32,
+    pub name: [u8; 32],
+    pub _padding1: [u8; 2],
+    pub historical_result_idx: u8,
+    pub min_sample_size: u8,
+    pub last_update_timestamp: i64,
+    pub lut_slot: u64,
+    pub _reserved1: [u8; 32],
+    pub result: CurrentResult,
+    pub max_staleness: u32,
+    pub _padding2: [u8; 12],
+    pub historical_results: [CompactResult; 32],
+    pub _ebuf4: [u8; 8],
+    pub _ebuf3: [u8; 24],
+    pub _ebuf2: [u8; 256],
+}
+
+unsafe impl Pod for PullFeedAccountData {}
+unsafe impl Zeroable for PullFeedAccountData {}
+
+/// Scale a decimal `mantissa * 10^-scale` to the pull feeds precision
+pub fn to_precision(mantissa: i128, scale: u32) -> i128 {
+    mantissa * 10_i128.pow(PRECISION - scale)
+}
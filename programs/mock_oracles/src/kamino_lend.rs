@@ -0,0 +1,25 @@
+//! Mock of the Kamino Lend `Reserve` account, only the fields read by scope are set
+use anchor_lang::prelude::{AccountInfo, Clock, Result, SolanaSysvar};
+
+const DISCRIMINATOR: [u8; 8] = [43, 242, 204, 202, 26, 247, 59, 127];
+// Offsets including the discriminator
+const LAST_UPDATE_SLOT_OFFSET: usize = 16;
+const AVAILABLE_AMOUNT_OFFSET: usize = 224;
+const COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET: usize = 2592;
+
+/// Set the reserve liquidity (all available, nothing borrowed) and collateral supply
+pub fn set_reserve(
+    reserve_account: &AccountInfo,
+    mint_total_supply: u64,
+    total_liquidity: u64,
+) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    let mut data = reserve_account.data.borrow_mut();
+    data[..8].copy_from_slice(&DISCRIMINATOR);
+    data[LAST_UPDATE_SLOT_OFFSET..LAST_UPDATE_SLOT_OFFSET + 8].copy_from_slice(&slot.to_le_bytes());
+    data[AVAILABLE_AMOUNT_OFFSET..AVAILABLE_AMOUNT_OFFSET + 8]
+        .copy_from_slice(&total_liquidity.to_le_bytes());
+    data[COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET..COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET + 8]
+        .copy_from_slice(&mint_total_supply.to_le_bytes());
+    Ok(())
+}
@@ -1,7 +1,11 @@
 #![allow(clippy::result_large_err)] //Needed because we can't change Anchor result type
 mod ctokens;
 mod externals;
+mod kamino_lend;
+mod marginfi;
+mod msol_stake;
 pub mod pc;
+mod pyth_pull;
 mod spl_stake;
 
 use std::{convert::TryInto, ops::Div};
@@ -13,7 +17,10 @@ use switchboard_program::{
 };
 
 use crate::{
-    externals::switchboard_v2::{AggregatorAccountData, SwitchboardDecimal},
+    externals::{
+        switchboard_on_demand::{self, PullFeedAccountData},
+        switchboard_v2::{AggregatorAccountData, SwitchboardDecimal},
+    },
     pc::{Price, PriceStatus},
 };
 
@@ -128,6 +135,31 @@ pub mod mock_oracles {
         Ok(())
     }
 
+    pub fn initialize_switchboard_on_demand(
+        ctx: Context<Initialize>,
+        mantissa: i128,
+        scale: u32,
+    ) -> Result<()> {
+        let mut account_data = ctx.accounts.oracle_account.data.borrow_mut();
+        account_data[..8].copy_from_slice(&switchboard_on_demand::DISCRIMINATOR);
+        let pull_feed: &mut PullFeedAccountData = bytemuck::from_bytes_mut(&mut account_data[8..]);
+        pull_feed.min_sample_size = 1;
+        pull_feed.max_staleness = 250;
+        pull_feed.result.value = switchboard_on_demand::to_precision(mantissa, scale);
+        pull_feed.result.std_dev = 0;
+        pull_feed.result.num_samples = 1;
+        let slot = ctx.accounts.clock.slot;
+        pull_feed.result.slot = slot;
+        pull_feed.last_update_timestamp = ctx.accounts.clock.unix_timestamp;
+        let key = &ctx.accounts.oracle_account.key.to_string();
+        msg!(
+            "Switchboard On-Demand price {} initialized at slot {}",
+            key,
+            slot
+        );
+        Ok(())
+    }
+
     pub fn initialize_ctoken(
         ctx: Context<Initialize>,
         mint_total_supply: u64,
@@ -166,6 +198,73 @@ pub mod mock_oracles {
         Ok(())
     }
 
+    pub fn initialize_msol_stake(ctx: Context<Initialize>, msol_price: u64) -> Result<()> {
+        msol_stake::set_msol_price(&ctx.accounts.oracle_account, msol_price)?;
+        msg!(
+            "Marinade state {} initialized with msol_price: {}",
+            ctx.accounts.oracle_account.key(),
+            msol_price
+        );
+        Ok(())
+    }
+
+    pub fn initialize_kamino_lend_reserve(
+        ctx: Context<Initialize>,
+        mint_total_supply: u64,
+        total_liquidity: u64,
+    ) -> Result<()> {
+        kamino_lend::set_reserve(
+            &ctx.accounts.oracle_account,
+            mint_total_supply,
+            total_liquidity,
+        )?;
+        msg!(
+            "Kamino Lend reserve {} initialized with supply: {} liquidity: {}",
+            ctx.accounts.oracle_account.key(),
+            mint_total_supply,
+            total_liquidity
+        );
+        Ok(())
+    }
+
+    pub fn initialize_marginfi_bank(
+        ctx: Context<Initialize>,
+        total_asset_shares: u64,
+        total_liquidity: u64,
+    ) -> Result<()> {
+        marginfi::set_bank(
+            &ctx.accounts.oracle_account,
+            total_asset_shares,
+            total_liquidity,
+        )?;
+        msg!(
+            "Marginfi bank {} initialized with shares: {} liquidity: {}",
+            ctx.accounts.oracle_account.key(),
+            total_asset_shares,
+            total_liquidity
+        );
+        Ok(())
+    }
+
+    pub fn initialize_pyth_pull(
+        ctx: Context<Initialize>,
+        feed_id: [u8; 32],
+        price: i64,
+        expo: i32,
+        conf: u64,
+    ) -> Result<()> {
+        pyth_pull::initialize(&ctx.accounts.oracle_account, feed_id, price, expo, conf)?;
+        msg!(
+            "Pyth pull price {} initialized to {}, expo {}, conf {} at slot {}",
+            ctx.accounts.oracle_account.key(),
+            price,
+            expo,
+            conf,
+            ctx.accounts.clock.slot
+        );
+        Ok(())
+    }
+
     pub fn set_price_pyth(ctx: Context<SetPrice>, price: i64) -> Result<()> {
         let oracle = &ctx.accounts.oracle_account;
 
@@ -185,6 +284,17 @@ pub mod mock_oracles {
         Ok(())
     }
 
+    pub fn set_price_pyth_pull(ctx: Context<SetPrice>, price: i64) -> Result<()> {
+        pyth_pull::update(&ctx.accounts.oracle_account, price)?;
+        msg!(
+            "Pyth pull price {} updated to {} at slot {}",
+            ctx.accounts.oracle_account.key(),
+            price,
+            ctx.accounts.clock.slot
+        );
+        Ok(())
+    }
+
     pub fn set_price_switchboard_v1(
         ctx: Context<SetPrice>,
         mantissa: i128,
@@ -237,6 +347,28 @@ pub mod mock_oracles {
         Ok(())
     }
 
+    pub fn set_price_switchboard_on_demand(
+        ctx: Context<SetPrice>,
+        mantissa: i128,
+        scale: u32,
+    ) -> Result<()> {
+        let mut account_data = ctx.accounts.oracle_account.data.borrow_mut();
+        let pull_feed: &mut PullFeedAccountData = bytemuck::from_bytes_mut(&mut account_data[8..]);
+        pull_feed.result.value = switchboard_on_demand::to_precision(mantissa, scale);
+        pull_feed.result.num_samples = pull_feed.min_sample_size;
+        let slot = ctx.accounts.clock.slot;
+        pull_feed.result.slot = slot;
+        pull_feed.last_update_timestamp = ctx.accounts.clock.unix_timestamp;
+        let key = &ctx.accounts.oracle_account.key.to_string();
+        msg!(
+            "Switchboard On-Demand Price {} updated at slot {}",
+            key,
+            slot
+        );
+
+        Ok(())
+    }
+
     pub fn set_price_ctoken(
         ctx: Context<SetPrice>,
         mint_total_supply: u64,
@@ -275,6 +407,56 @@ pub mod mock_oracles {
         Ok(())
     }
 
+    pub fn set_price_msol_stake(ctx: Context<SetPrice>, msol_price: u64) -> Result<()> {
+        msol_stake::set_msol_price(&ctx.accounts.oracle_account, msol_price)?;
+        msg!(
+            "Marinade state {} msol_price updated to {} at slot {}",
+            ctx.accounts.oracle_account.key(),
+            msol_price,
+            ctx.accounts.clock.slot
+        );
+
+        Ok(())
+    }
+
+    pub fn set_price_kamino_lend_reserve(
+        ctx: Context<SetPrice>,
+        mint_total_supply: u64,
+        total_liquidity: u64,
+    ) -> Result<()> {
+        kamino_lend::set_reserve(
+            &ctx.accounts.oracle_account,
+            mint_total_supply,
+            total_liquidity,
+        )?;
+        msg!(
+            "Kamino Lend reserve {} updated at slot {}",
+            ctx.accounts.oracle_account.key(),
+            ctx.accounts.clock.slot
+        );
+
+        Ok(())
+    }
+
+    pub fn set_price_marginfi_bank(
+        ctx: Context<SetPrice>,
+        total_asset_shares: u64,
+        total_liquidity: u64,
+    ) -> Result<()> {
+        marginfi::set_bank(
+            &ctx.accounts.oracle_account,
+            total_asset_shares,
+            total_liquidity,
+        )?;
+        msg!(
+            "Marginfi bank {} updated at slot {}",
+            ctx.accounts.oracle_account.key(),
+            ctx.accounts.clock.slot
+        );
+
+        Ok(())
+    }
+
     pub fn set_trading_pyth(ctx: Context<SetPrice>, status: u8) -> Result<()> {
         let oracle = &ctx.accounts.oracle_account;
         let mut price_oracle = Price::load(oracle).unwrap();
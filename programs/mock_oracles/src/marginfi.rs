@@ -0,0 +1,23 @@
+//! Mock of the Marginfi `Bank` account, only the fields read by scope are set
+use anchor_lang::prelude::{AccountInfo, Clock, Result, SolanaSysvar};
+
+const DISCRIMINATOR: [u8; 8] = [142, 49, 166, 242, 50, 66, 97, 188];
+// Offsets including the discriminator
+const ASSET_SHARE_VALUE_OFFSET: usize = 80;
+const LAST_UPDATE_OFFSET: usize = 288;
+
+/// Set the value of an asset share (`I80F48`) from the bank total shares and liquidity
+pub fn set_bank(
+    bank_account: &AccountInfo,
+    total_asset_shares: u64,
+    total_liquidity: u64,
+) -> Result<()> {
+    let unix_timestamp = Clock::get()?.unix_timestamp;
+    let asset_share_value = (i128::from(total_liquidity) << 48) / i128::from(total_asset_shares);
+    let mut data = bank_account.data.borrow_mut();
+    data[..8].copy_from_slice(&DISCRIMINATOR);
+    data[ASSET_SHARE_VALUE_OFFSET..ASSET_SHARE_VALUE_OFFSET + 16]
+        .copy_from_slice(&asset_share_value.to_le_bytes());
+    data[LAST_UPDATE_OFFSET..LAST_UPDATE_OFFSET + 8].copy_from_slice(&unix_timestamp.to_le_bytes());
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! Mock of the marinade `State` account, only the fields read by scope are set
+use anchor_lang::prelude::{AccountInfo, Result};
+
+const DISCRIMINATOR: [u8; 8] = [216, 146, 107, 94, 104, 75, 182, 177];
+/// Offset of `State::msol_price` including the discriminator
+const MSOL_PRICE_OFFSET: usize = 512;
+
+/// Set the SOL per mSOL price, expressed with a `2^32` denominator
+pub fn set_msol_price(state_account: &AccountInfo, msol_price: u64) -> Result<()> {
+    let mut data = state_account.data.borrow_mut();
+    data[..8].copy_from_slice(&DISCRIMINATOR);
+    data[MSOL_PRICE_OFFSET..MSOL_PRICE_OFFSET + 8].copy_from_slice(&msol_price.to_le_bytes());
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Mock of the `PriceUpdateV2` accounts posted by the pyth receiver program
+use anchor_lang::{
+    prelude::{AccountInfo, Clock, Pubkey, Result, SolanaSysvar},
+    AnchorDeserialize, AnchorSerialize,
+};
+
+const DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+enum VerificationLevel {
+    #[allow(dead_code)]
+    Partial {
+        num_signatures: u8,
+    },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct PriceFeedMessage {
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+    prev_publish_time: i64,
+    ema_price: i64,
+    ema_conf: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct PriceUpdateV2 {
+    write_authority: Pubkey,
+    verification_level: VerificationLevel,
+    price_message: PriceFeedMessage,
+    posted_slot: u64,
+}
+
+fn write(price_update_account: &AccountInfo, price_update: &PriceUpdateV2) -> Result<()> {
+    let mut data = price_update_account.data.borrow_mut();
+    data[..8].copy_from_slice(&DISCRIMINATOR);
+    price_update.serialize(&mut &mut data[8..])?;
+    Ok(())
+}
+
+pub fn initialize(
+    price_update_account: &AccountInfo,
+    feed_id: [u8; 32],
+    price: i64,
+    exponent: i32,
+    conf: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let price_update = PriceUpdateV2 {
+        write_authority: Pubkey::default(),
+        verification_level: VerificationLevel::Full,
+        price_message: PriceFeedMessage {
+            feed_id,
+            price,
+            conf,
+            exponent,
+            publish_time: clock.unix_timestamp,
+            prev_publish_time: clock.unix_timestamp,
+            ema_price: price,
+            ema_conf: conf,
+        },
+        posted_slot: clock.slot,
+    };
+    write(price_update_account, &price_update)
+}
+
+pub fn update(price_update_account: &AccountInfo, price: i64) -> Result<()> {
+    let clock = Clock::get()?;
+    let mut price_update =
+        PriceUpdateV2::deserialize(&mut &price_update_account.data.borrow()[8..])?;
+    let message = &mut price_update.price_message;
+    message.prev_publish_time = message.publish_time;
+    message.publish_time = clock.unix_timestamp;
+    message.price = price;
+    price_update.posted_slot = clock.slot;
+    write(price_update_account, &price_update)
+}
@@ -2,7 +2,7 @@ use anchor_lang::{
     prelude::{AccountInfo, Clock, Result, SolanaSysvar},
     AnchorSerialize,
 };
-use spl_stake_pool::state::StakePool;
+use spl_stake_pool::state::{AccountType, StakePool};
 
 pub fn initialize(
     stake_pool_account: &AccountInfo,
@@ -10,6 +10,7 @@ pub fn initialize(
     total_liquidity: u64,
 ) -> Result<()> {
     let pool = StakePool {
+        account_type: AccountType::StakePool,
         last_update_epoch: Clock::get()?.epoch,
         total_lamports: total_liquidity,
         pool_token_supply: mint_total_supply,
@@ -26,6 +27,7 @@ pub fn update(
     total_liquidity: u64,
 ) -> Result<()> {
     let pool = StakePool {
+        account_type: AccountType::StakePool,
         last_update_epoch: Clock::get()?.epoch,
         total_lamports: total_liquidity,
         pool_token_supply: mint_total_supply,
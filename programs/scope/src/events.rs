@@ -0,0 +1,58 @@
+//! Events emitted by the program, allowing indexers to follow the prices and the mapping changes
+//! of a feed without parsing the program logs
+
+use anchor_lang::prelude::*;
+
+use crate::GENERIC_DATA_SIZE;
+
+/// A price entry has been refreshed
+#[event]
+pub struct PriceUpdated {
+    pub oracle_prices: Pubkey,
+    pub token: u16,
+    /// [`crate::oracles::OracleType`] of the entry
+    pub price_type: u8,
+    pub previous_value: u64,
+    pub previous_exp: u64,
+    pub previous_slot: u64,
+    pub value: u64,
+    pub exp: u64,
+    pub last_updated_slot: u64,
+    pub unix_timestamp: u64,
+}
+
+/// Reason why a price entry has not been refreshed by `refresh_price_list`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The price could not be computed or did not pass the oracle validations
+    ValidationFailed,
+    /// The entry is frozen
+    EntryFrozen,
+    /// The change from the previous price is above the entry limit
+    PriceChangeTooLarge,
+}
+
+/// A price entry has been skipped by `refresh_price_list`
+#[event]
+pub struct PriceSkipped {
+    pub oracle_prices: Pubkey,
+    pub token: u16,
+    /// [`crate::oracles::OracleType`] of the entry
+    pub price_type: u8,
+    pub reason: SkipReason,
+    /// Code of the error that caused the skip (0 if the skip is not caused by an error)
+    pub error_code: u32,
+}
+
+/// The admin has changed the oracle account, type or generic data of an entry
+#[event]
+pub struct MappingUpdated {
+    pub oracle_mappings: Pubkey,
+    pub token: u16,
+    pub old_price_info: Pubkey,
+    pub new_price_info: Pubkey,
+    pub old_price_type: u8,
+    pub new_price_type: u8,
+    pub old_generic_data: [u8; GENERIC_DATA_SIZE],
+    pub new_generic_data: [u8; GENERIC_DATA_SIZE],
+}
@@ -0,0 +1,451 @@
+//! Feed entries beyond [`MAX_ENTRIES`]
+//!
+//! [`OraclePrices`], [`OracleMappings`] and [`OracleTwaps`] store `MAX_ENTRIES` entries in a fixed
+//! layout that consumers read as is. When the admin resizes a feed, its accounts are reallocated
+//! and the additional entries are stored after the fixed layout, which is left untouched:
+//!
+//! ```text
+//! | discriminator | fixed layout (entries 0..MAX_ENTRIES) | ExtensionHeader | additional entries |
+//! ```
+//!
+//! Accounts without extension are version 1 and hold exactly `MAX_ENTRIES` entries.
+//!
+//! The layout is read with the functions of `scope_types::extension`, shared with the off-chain
+//! consumers of the accounts, this module adds the access to the entries of loaded accounts and
+//! the resize of the accounts.
+//!
+//! Oracle types computing their price from other entries of the feed (chain, median, TWAP...)
+//! read their sources through [`FeedEntries`], from all the entries of the feed.
+
+use std::{
+    cell::{Ref, RefMut},
+    mem::size_of,
+};
+
+use anchor_lang::{prelude::*, system_program, ZeroCopy};
+use scope_types::extension::{self as shared, DISCRIMINATOR_SIZE, HEADER_SIZE};
+pub use scope_types::extension::{
+    account_size, ExtensionHeader, FeedAccount as FixedLayout, MappingEntry, EXTENSION_VERSION,
+    MAX_FEED_ENTRIES,
+};
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+use crate::{
+    check_price_usable, DatedPrice, EntryTwap, OracleMappings, OraclePrices, OracleTwaps,
+    ScopeError, ScopeResult, MAX_ENTRIES,
+};
+
+/// Account of a feed holding one entry per token, owned by the program
+pub trait FeedAccount: FixedLayout + ZeroCopy + Owner {
+    /// Set the entry `index` of the fixed layout
+    fn set_fixed_entry(&mut self, index: usize, entry: Self::Entry);
+}
+
+impl FixedLayout for OraclePrices {
+    type Entry = DatedPrice;
+
+    fn fixed_entry(&self, index: usize) -> DatedPrice {
+        self.prices[index]
+    }
+}
+
+impl FeedAccount for OraclePrices {
+    fn set_fixed_entry(&mut self, index: usize, entry: DatedPrice) {
+        self.prices[index] = entry;
+    }
+}
+
+impl FixedLayout for OracleTwaps {
+    type Entry = EntryTwap;
+
+    fn fixed_entry(&self, index: usize) -> EntryTwap {
+        self.twaps[index]
+    }
+}
+
+impl FeedAccount for OracleTwaps {
+    fn set_fixed_entry(&mut self, index: usize, entry: EntryTwap) {
+        self.twaps[index] = entry;
+    }
+}
+
+impl FixedLayout for OracleMappings {
+    type Entry = MappingEntry;
+
+    fn fixed_entry(&self, index: usize) -> MappingEntry {
+        MappingEntry {
+            price_info_account: self.price_info_accounts[index],
+            generic_data: self.generic_data[index],
+            max_age_slots: self.max_age_slots[index],
+            max_change_bps_per_slot: self.max_change_bps_per_slot[index],
+            confidence_bps: self.confidence_bps[index],
+            price_type: self.price_types[index],
+            _padding: [0; 7],
+        }
+    }
+}
+
+impl FeedAccount for OracleMappings {
+    fn set_fixed_entry(&mut self, index: usize, entry: MappingEntry) {
+        self.price_info_accounts[index] = entry.price_info_account;
+        self.generic_data[index] = entry.generic_data;
+        self.max_age_slots[index] = entry.max_age_slots;
+        self.max_change_bps_per_slot[index] = entry.max_change_bps_per_slot;
+        self.confidence_bps[index] = entry.confidence_bps;
+        self.price_types[index] = entry.price_type;
+    }
+}
+
+/// Read access to all the entries of a feed account
+pub struct Entries<'a, T: FeedAccount> {
+    pub fixed: Ref<'a, T>,
+    additional: Ref<'a, [T::Entry]>,
+}
+
+impl<T: FeedAccount> Entries<'_, T> {
+    pub fn num_entries(&self) -> usize {
+        MAX_ENTRIES + self.additional.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<T::Entry> {
+        match index.checked_sub(MAX_ENTRIES) {
+            None => Some(self.fixed.fixed_entry(index)),
+            Some(additional_index) => self.additional.get(additional_index).copied(),
+        }
+    }
+}
+
+impl Entries<'_, OraclePrices> {
+    /// Get the price of an entry, checking it is not frozen nor older than its configured max age.
+    ///
+    /// Same as [`OraclePrices::get_checked`], for all the entries of a resized feed.
+    pub fn get_checked(
+        &self,
+        oracle_mappings: &Entries<'_, OracleMappings>,
+        index: usize,
+        clock: &Clock,
+    ) -> ScopeResult<DatedPrice> {
+        let price = self.get(index).ok_or(ScopeError::BadTokenNb)?;
+        let mapping = oracle_mappings.get(index).ok_or(ScopeError::BadTokenNb)?;
+        check_price_usable(index, &price, mapping.max_age_slots, clock)?;
+        Ok(price)
+    }
+}
+
+/// Entries of a feed read by index
+///
+/// Implemented by the fixed layout of the accounts (only the first `MAX_ENTRIES` entries) and by
+/// [`Entries`] (all the entries of a resized feed).
+pub trait FeedEntries {
+    type Entry;
+
+    fn entry(&self, index: usize) -> Option<Self::Entry>;
+}
+
+/// Prices of a feed, see [`FeedEntries`]
+pub trait FeedPrices: FeedEntries<Entry = DatedPrice> {}
+
+impl<T: FeedEntries<Entry = DatedPrice>> FeedPrices for T {}
+
+impl FeedEntries for OraclePrices {
+    type Entry = DatedPrice;
+
+    fn entry(&self, index: usize) -> Option<DatedPrice> {
+        self.prices.get(index).copied()
+    }
+}
+
+impl FeedEntries for OracleTwaps {
+    type Entry = EntryTwap;
+
+    fn entry(&self, index: usize) -> Option<EntryTwap> {
+        self.twaps.get(index).copied()
+    }
+}
+
+impl<T: FeedAccount> FeedEntries for Entries<'_, T> {
+    type Entry = T::Entry;
+
+    fn entry(&self, index: usize) -> Option<T::Entry> {
+        self.get(index)
+    }
+}
+
+/// Entries read off-chain with [`read_entries`]
+impl<E: Copy> FeedEntries for Vec<E> {
+    type Entry = E;
+
+    fn entry(&self, index: usize) -> Option<E> {
+        self.get(index).copied()
+    }
+}
+
+/// Write access to all the entries of a feed account
+pub struct EntriesMut<'a, T: FeedAccount> {
+    pub fixed: RefMut<'a, T>,
+    additional: RefMut<'a, [T::Entry]>,
+}
+
+impl<T: FeedAccount> EntriesMut<'_, T> {
+    pub fn num_entries(&self) -> usize {
+        MAX_ENTRIES + self.additional.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<T::Entry> {
+        match index.checked_sub(MAX_ENTRIES) {
+            None => Some(self.fixed.fixed_entry(index)),
+            Some(additional_index) => self.additional.get(additional_index).copied(),
+        }
+    }
+
+    pub fn set(&mut self, index: usize, entry: T::Entry) -> ScopeResult<()> {
+        match index.checked_sub(MAX_ENTRIES) {
+            None => self.fixed.set_fixed_entry(index, entry),
+            Some(additional_index) => {
+                *self
+                    .additional
+                    .get_mut(additional_index)
+                    .ok_or(ScopeError::BadTokenNb)? = entry
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply `f` to the entry `index`
+    pub fn update<R>(
+        &mut self,
+        index: usize,
+        f: impl FnOnce(&mut T::Entry) -> R,
+    ) -> ScopeResult<R> {
+        let mut entry = self.get(index).ok_or(ScopeError::BadTokenNb)?;
+        let res = f(&mut entry);
+        self.set(index, entry)?;
+        Ok(res)
+    }
+}
+
+/// Load all the entries of a feed account
+pub fn load<'a, T: FeedAccount>(loader: &'a AccountLoader<T>) -> Result<Entries<'a, T>> {
+    // Check the discriminator
+    drop(loader.load()?);
+    let data = loader.as_ref().try_borrow_data()?;
+    let (fixed, additional) = shared::layout::<T>(&data).map_err(ScopeError::from)?;
+    let (fixed, additional) = Ref::map_split(data, move |data| (&data[fixed], &data[additional]));
+    Ok(Entries {
+        fixed: Ref::map(fixed, bytemuck::from_bytes),
+        additional: Ref::map(additional, bytemuck::cast_slice),
+    })
+}
+
+/// Load all the entries of a feed account to update them
+pub fn load_mut<'a, T: FeedAccount>(loader: &'a AccountLoader<T>) -> Result<EntriesMut<'a, T>> {
+    // Check the discriminator and that the account is writable
+    drop(loader.load_mut()?);
+    let data = loader.as_ref().try_borrow_mut_data()?;
+    let (fixed, additional) = shared::layout::<T>(&data).map_err(ScopeError::from)?;
+    let (fixed, additional) = RefMut::map_split(data, move |data| {
+        let (head, tail) = data.split_at_mut(additional.start);
+        (&mut head[fixed], &mut tail[..additional.len()])
+    });
+    Ok(EntriesMut {
+        fixed: RefMut::map(fixed, bytemuck::from_bytes_mut),
+        additional: RefMut::map(additional, bytemuck::cast_slice_mut),
+    })
+}
+
+/// Number of entries of a feed account from its data (discriminator included)
+pub fn num_entries<T: FeedAccount>(data: &[u8]) -> ScopeResult<usize> {
+    Ok(shared::num_entries::<T>(data)?)
+}
+
+/// Read a copy of all the entries of a feed account from its data (discriminator included)
+///
+/// The data does not need to be aligned, meant to be used off-chain.
+pub fn read_entries<T: FeedAccount>(data: &[u8]) -> ScopeResult<Vec<T::Entry>> {
+    Ok(shared::read_entries::<T>(data)?)
+}
+
+/// Grow a feed account to hold `num_entries` entries, the payer funds the additional rent
+///
+/// Additional entries are zeroed.
+/// The runtime limits the growth of an account to [`MAX_PERMITTED_DATA_INCREASE`] bytes per
+/// instruction, large resizes have to be split in several steps.
+pub fn grow<'info, T: FeedAccount>(
+    loader: &AccountLoader<'info, T>,
+    num_entries: usize,
+    payer: &Signer<'info>,
+    system: &Program<'info, System>,
+) -> Result<()> {
+    let account = loader.as_ref();
+    let current_size = account.data_len();
+    let new_size = account_size::<T>(num_entries);
+    let increase = new_size.saturating_sub(current_size);
+    if num_entries > MAX_FEED_ENTRIES || increase == 0 || increase > MAX_PERMITTED_DATA_INCREASE {
+        msg!(
+            "Account {} cannot grow from {} to {} bytes in one instruction",
+            account.key,
+            current_size,
+            new_size
+        );
+        return err!(ScopeError::InvalidFeedSize);
+    }
+
+    realloc(account, new_size, payer, system)?;
+
+    let header = ExtensionHeader {
+        version: EXTENSION_VERSION,
+        num_entries: num_entries.try_into().map_err(ScopeError::from)?,
+    };
+    let header_start = DISCRIMINATOR_SIZE + size_of::<T>();
+    account.try_borrow_mut_data()?[header_start..header_start + HEADER_SIZE]
+        .copy_from_slice(bytemuck::bytes_of(&header));
+
+    Ok(())
+}
+
+/// Realloc an account to `new_size` bytes, the payer funds the missing rent
+///
+/// Additional bytes are zeroed.
+pub(crate) fn realloc<'info>(
+    account: &AccountInfo<'info>,
+    new_size: usize,
+    payer: &Signer<'info>,
+    system: &Program<'info, System>,
+) -> Result<()> {
+    let missing_rent = Rent::get()?
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+    if missing_rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            missing_rent,
+        )?;
+    }
+    account.realloc(new_size, true)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Discriminator;
+
+    use super::*;
+    use crate::{Price, GENERIC_DATA_SIZE};
+
+    fn prices_data(num_entries: usize) -> Vec<u8> {
+        let mut data = vec![0; account_size::<OraclePrices>(num_entries)];
+        data[..8].copy_from_slice(&OraclePrices::DISCRIMINATOR);
+        if num_entries > MAX_ENTRIES {
+            let header = ExtensionHeader {
+                version: EXTENSION_VERSION,
+                num_entries: num_entries as u32,
+            };
+            let header_start = 8 + size_of::<OraclePrices>();
+            data[header_start..header_start + HEADER_SIZE]
+                .copy_from_slice(bytemuck::bytes_of(&header));
+        }
+        data
+    }
+
+    fn set_price(data: &mut [u8], index: usize, value: u64) {
+        let offset = if index < MAX_ENTRIES {
+            8 + 32 + index * size_of::<DatedPrice>()
+        } else {
+            8 + size_of::<OraclePrices>()
+                + HEADER_SIZE
+                + (index - MAX_ENTRIES) * size_of::<DatedPrice>()
+        };
+        let price = DatedPrice {
+            price: Price { value, exp: 6 },
+            ..Default::default()
+        };
+        data[offset..offset + size_of::<DatedPrice>()].copy_from_slice(bytemuck::bytes_of(&price));
+    }
+
+    #[test]
+    fn fixed_layout_accounts() {
+        let data = prices_data(MAX_ENTRIES);
+        assert_eq!(data.len(), 8 + size_of::<OraclePrices>());
+        assert_eq!(num_entries::<OraclePrices>(&data), Ok(MAX_ENTRIES));
+        assert_eq!(
+            read_entries::<OraclePrices>(&data).unwrap().len(),
+            MAX_ENTRIES
+        );
+    }
+
+    #[test]
+    fn extended_accounts() {
+        let mut data = prices_data(MAX_ENTRIES + 3);
+        set_price(&mut data, 1, 42);
+        set_price(&mut data, MAX_ENTRIES + 2, 43);
+        assert_eq!(num_entries::<OraclePrices>(&data), Ok(MAX_ENTRIES + 3));
+
+        let prices = read_entries::<OraclePrices>(&data).unwrap();
+        assert_eq!(prices.len(), MAX_ENTRIES + 3);
+        assert_eq!(prices[1].price.value, 42);
+        assert_eq!(prices[MAX_ENTRIES].price.value, 0);
+        assert_eq!(prices[MAX_ENTRIES + 2].price.value, 43);
+    }
+
+    #[test]
+    fn invalid_extensions() {
+        let data = prices_data(MAX_ENTRIES + 3);
+        // Truncated additional entries
+        assert_eq!(
+            num_entries::<OraclePrices>(&data[..data.len() - 1]),
+            Err(ScopeError::UnableToDeserializeAccount)
+        );
+        // Truncated fixed layout
+        assert_eq!(
+            num_entries::<OraclePrices>(&data[..100]),
+            Err(ScopeError::UnableToDeserializeAccount)
+        );
+        // Unknown version
+        let mut data = data;
+        data[8 + size_of::<OraclePrices>()] = 3;
+        assert_eq!(
+            num_entries::<OraclePrices>(&data),
+            Err(ScopeError::UnableToDeserializeAccount)
+        );
+    }
+
+    #[test]
+    fn mapping_entries_round_trip() {
+        let mut mappings: Box<OracleMappings> = Box::new(bytemuck::Zeroable::zeroed());
+        let entry = MappingEntry {
+            price_info_account: Pubkey::new_unique(),
+            generic_data: [7; GENERIC_DATA_SIZE],
+            max_age_slots: 10,
+            max_change_bps_per_slot: 20,
+            confidence_bps: 30,
+            price_type: 4,
+            _padding: [0; 7],
+        };
+        mappings.set_fixed_entry(3, entry);
+        assert_eq!(mappings.fixed_entry(3), entry);
+        assert_eq!(mappings.price_types[3], 4);
+        assert_eq!(mappings.fixed_entry(2), MappingEntry::default());
+    }
+
+    #[test]
+    fn account_sizes() {
+        assert_eq!(
+            account_size::<OracleTwaps>(MAX_ENTRIES + 2),
+            8 + size_of::<OracleTwaps>() + HEADER_SIZE + 2 * size_of::<EntryTwap>()
+        );
+        assert_eq!(
+            account_size::<OracleMappings>(MAX_ENTRIES),
+            8 + size_of::<OracleMappings>()
+        );
+        // Additional entries are aligned
+        assert_eq!((8 + size_of::<OraclePrices>() + HEADER_SIZE) % 8, 0);
+        assert_eq!((8 + size_of::<OracleTwaps>() + HEADER_SIZE) % 8, 0);
+        assert_eq!((8 + size_of::<OracleMappings>() + HEADER_SIZE) % 8, 0);
+    }
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct ApproveAdminCached<'info> {
+    pub admin_cached: Signer<'info>,
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin_cached)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+}
+
+pub fn process(ctx: Context<ApproveAdminCached>, _: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+
+    msg!(
+        "Approve admin cached: previous admin {}, new admin {}",
+        configuration.admin,
+        configuration.admin_cached
+    );
+
+    configuration.admin = configuration.admin_cached;
+    configuration.admin_cached = Pubkey::default();
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use solana_program::program::set_return_data;
+
+use crate::{
+    extension, oracles::check_context, return_data::MAX_RETURNED_PRICES, DatedPrice, ScopeError,
+    ScopeResult,
+};
+
+#[derive(Accounts)]
+pub struct GetPrices<'info> {
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+}
+
+/// Return the requested entries as raw [`DatedPrice`]s in the instruction return data.
+///
+/// Prices are returned as stored, consumers are expected to check their age and status.
+pub fn process(ctx: Context<GetPrices>, tokens: &[u16]) -> Result<()> {
+    check_context(&ctx)?;
+
+    if tokens.is_empty() {
+        return err!(ScopeError::BadTokenNb);
+    }
+    if tokens.len() > MAX_RETURNED_PRICES {
+        msg!(
+            "Cannot return {} prices, max is {}",
+            tokens.len(),
+            MAX_RETURNED_PRICES
+        );
+        return err!(ScopeError::TooManyPricesRequested);
+    }
+
+    let oracle_prices = extension::load(&ctx.accounts.oracle_prices)?;
+    let prices = tokens
+        .iter()
+        .map(|&token| {
+            oracle_prices
+                .get(usize::from(token))
+                .ok_or(ScopeError::BadTokenNb)
+        })
+        .collect::<ScopeResult<Vec<DatedPrice>>>()?;
+
+    set_return_data(bytemuck::cast_slice(&prices));
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{extension, oracles::check_context, ScopeError, MAX_ENTRIES};
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct InitOracleTwaps<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"conf", feed_name.as_bytes()],
+        bump,
+        has_one = admin,
+        has_one = oracle_prices,
+        has_one = oracle_mappings,
+    )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+    pub oracle_mappings: AccountLoader<'info, crate::OracleMappings>,
+    // Account is pre-reserved/payed outside the program
+    #[account(zero)]
+    pub oracle_twaps: AccountLoader<'info, crate::OracleTwaps>,
+}
+
+/// Attach a TWAPs account to a feed, refreshes then record the observations of its prices.
+///
+/// Feeds record no TWAPs by default, only the ones using `ScopeTwap` entries need the account.
+pub fn process(ctx: Context<InitOracleTwaps>, _: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+    if configuration.oracle_twaps != Pubkey::default() {
+        msg!(
+            "Feed already has the TWAPs account {}",
+            configuration.oracle_twaps
+        );
+        return err!(ScopeError::OracleTwapsAlreadySet);
+    }
+    // The TWAPs account is grown with the feed once attached, it has to be attached first
+    if extension::load(&ctx.accounts.oracle_prices)?.num_entries() != MAX_ENTRIES {
+        return err!(ScopeError::InvalidFeedSize);
+    }
+
+    let mut oracle_twaps = ctx.accounts.oracle_twaps.load_init()?;
+    oracle_twaps.oracle_prices = ctx.accounts.oracle_prices.key();
+    oracle_twaps.oracle_mappings = ctx.accounts.oracle_mappings.key();
+
+    configuration.oracle_twaps = ctx.accounts.oracle_twaps.key();
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+use crate::{extension, oracles::check_context, OracleMappings, ScopeError, MAX_ENTRIES};
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct MigrateOracleMappings<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin, has_one = oracle_mappings)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    // Not loaded: the account is too small for the current layout until migrated
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, OracleMappings>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a mappings account created before the `generic_data` column to the current layout.
+///
+/// The new column is zeroed, which is the default of every entry. The account can only grow by
+/// `MAX_PERMITTED_DATA_INCREASE` bytes per instruction, the migration takes two instructions.
+/// The admin pays the additional rent.
+pub fn process(ctx: Context<MigrateOracleMappings>, _: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let oracle_mappings = ctx.accounts.oracle_mappings.as_ref();
+    let current_size = oracle_mappings.data_len();
+    let target_size = extension::account_size::<OracleMappings>(MAX_ENTRIES);
+    if current_size >= target_size {
+        msg!("Oracle mappings account is already migrated");
+        return err!(ScopeError::InvalidFeedSize);
+    }
+
+    let new_size = target_size.min(current_size + MAX_PERMITTED_DATA_INCREASE);
+    msg!(
+        "Migrate oracle mappings from {} to {} bytes",
+        current_size,
+        new_size
+    );
+
+    extension::realloc(
+        oracle_mappings,
+        new_size,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )
+}
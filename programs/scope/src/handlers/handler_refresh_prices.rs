@@ -10,11 +10,17 @@ use solana_program::{
 };
 
 use crate::{
-    oracles::{get_price, OracleType},
-    ScopeError,
+    events::{PriceSkipped, PriceUpdated, SkipReason},
+    extension,
+    oracles::{get_price, scope_twap::record_observation, OracleType},
+    utils::math::is_price_change_within,
+    DatedPrice, ScopeError,
 };
 
 const COMPUTE_BUDGET_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+/// Max number of slots since the previous update accounted for in the allowed price change
+/// (about 10 minutes), so the allowed change of a stale price does not grow without limit
+const MAX_PRICE_CHANGE_ELAPSED_SLOTS: u64 = 1_500;
 
 #[derive(Accounts)]
 pub struct RefreshOne<'info> {
@@ -28,6 +34,12 @@ pub struct RefreshOne<'info> {
     /// CHECK: Sysvar fixed address
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub instruction_sysvar_account_info: AccountInfo<'info>,
+    #[account(has_one = oracle_prices, has_one = oracle_mappings)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    /// CHECK: Checked against the configuration when the feed records TWAPs, the feed's
+    /// `oracle_prices` account otherwise
+    #[account(mut)]
+    pub oracle_twaps: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -41,55 +53,101 @@ pub struct RefreshList<'info> {
     /// CHECK: Sysvar fixed address
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub instruction_sysvar_account_info: AccountInfo<'info>,
+    #[account(has_one = oracle_prices, has_one = oracle_mappings)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    /// CHECK: Checked against the configuration when the feed records TWAPs, the feed's
+    /// `oracle_prices` account otherwise
+    #[account(mut)]
+    pub oracle_twaps: AccountInfo<'info>,
     // Note: use remaining accounts as price accounts
 }
 
 pub fn refresh_one_price(ctx: Context<RefreshOne>, token: usize) -> Result<()> {
-    check_execution_ctx(&ctx.accounts.instruction_sysvar_account_info)?;
+    check_execution_ctx(
+        &ctx.accounts.instruction_sysvar_account_info,
+        &ctx.accounts.configuration,
+    )?;
+    check_not_paused(&ctx.accounts.configuration)?;
 
-    let oracle_mappings = ctx.accounts.oracle_mappings.load()?;
+    let oracle_mappings = extension::load(&ctx.accounts.oracle_mappings)?;
+    let entry = oracle_mappings.get(token).ok_or(ScopeError::BadTokenNb)?;
     let price_info = &ctx.accounts.price_info;
 
     // Check that the provided account is the one referenced in oracleMapping
-    if oracle_mappings.price_info_accounts[token] != price_info.key() {
+    if entry.price_info_account != price_info.key() {
         return err!(ScopeError::UnexpectedAccount);
     }
 
-    let price_type: OracleType = oracle_mappings.price_types[token]
+    let price_type: OracleType = entry
+        .price_type
         .try_into()
         .map_err(|_| ScopeError::BadTokenType)?;
 
+    let previous = extension::load(&ctx.accounts.oracle_prices)?
+        .get(token)
+        .ok_or(ScopeError::BadTokenNb)?;
+    if previous.is_frozen() {
+        msg!("Price {} is frozen", token);
+        return err!(ScopeError::EntryFrozen);
+    }
+
+    let oracle_twaps = ctx
+        .accounts
+        .configuration
+        .load()?
+        .load_oracle_twaps(&ctx.accounts.oracle_twaps)?;
+
     let mut remaining_iter = ctx.remaining_accounts.iter();
     let clock = Clock::get()?;
-    let mut price = get_price(price_type, price_info, &mut remaining_iter, &clock)?;
+    let mut price = get_price(
+        price_type,
+        price_info,
+        &mut remaining_iter,
+        &clock,
+        &ctx.accounts.oracle_prices,
+        oracle_twaps.as_ref(),
+        token,
+        &entry,
+    )?;
     price.index = token.try_into().unwrap();
 
-    // Only load when needed, allows prices computation to use scope chain
-    let mut oracle = ctx.accounts.oracle_prices.load_mut()?;
+    check_price_change(&previous, &price, entry.max_change_bps_per_slot)?;
 
-    msg!(
-        "tk {}, {:?}: {:?} to {:?} | prev_slot: {:?}, new_slot: {:?}, crt_slot: {:?}",
-        token,
+    emit!(price_updated_event(
+        ctx.accounts.oracle_prices.key(),
+        price.index,
         price_type,
-        oracle.prices[token].price.value,
-        price.price.value,
-        oracle.prices[token].last_updated_slot,
-        price.last_updated_slot,
-        clock.slot,
-    );
+        &previous,
+        &price,
+    ));
+
+    // Only load when needed, allows prices computation to use scope chain
+    extension::load_mut(&ctx.accounts.oracle_prices)?.set(token, price)?;
 
-    oracle.prices[token] = price;
+    if let Some(oracle_twaps) = &oracle_twaps {
+        extension::load_mut(oracle_twaps)?
+            .update(token, |twap| record_observation(twap, &price))?;
+    }
 
     Ok(())
 }
 
 pub fn refresh_price_list(ctx: Context<RefreshList>, tokens: &[u16]) -> Result<()> {
-    check_execution_ctx(&ctx.accounts.instruction_sysvar_account_info)?;
+    check_execution_ctx(
+        &ctx.accounts.instruction_sysvar_account_info,
+        &ctx.accounts.configuration,
+    )?;
+    check_not_paused(&ctx.accounts.configuration)?;
 
-    let oracle_mappings = &ctx.accounts.oracle_mappings.load()?;
+    let oracle_mappings = extension::load(&ctx.accounts.oracle_mappings)?;
+    let oracle_twaps = ctx
+        .accounts
+        .configuration
+        .load()?
+        .load_oracle_twaps(&ctx.accounts.oracle_twaps)?;
 
     // Check that the received token list is not too long
-    if tokens.len() > crate::MAX_ENTRIES {
+    if tokens.len() > oracle_mappings.num_entries() {
         return Err(ProgramError::InvalidArgument.into());
     }
     // Check the received token list is at least as long as the number of provided accounts
@@ -103,61 +161,114 @@ pub fn refresh_price_list(ctx: Context<RefreshList>, tokens: &[u16]) -> Result<(
 
     for &token_nb in tokens.iter() {
         let token_idx: usize = token_nb.into();
-        let oracle_mapping = oracle_mappings
-            .price_info_accounts
+        let entry = oracle_mappings
             .get(token_idx)
             .ok_or(ScopeError::BadTokenNb)?;
-        let price_type: OracleType = oracle_mappings.price_types[token_idx]
+        let price_type: OracleType = entry
+            .price_type
             .try_into()
             .map_err(|_| ScopeError::BadTokenType)?;
         let received_account = accounts_iter
             .next()
             .ok_or(ScopeError::AccountsAndTokenMismatch)?;
         // Ignore unset mapping accounts
-        if zero_pk == *oracle_mapping {
+        if zero_pk == entry.price_info_account {
             continue;
         }
         // Check that the provided oracle accounts are the one referenced in oracleMapping
-        if oracle_mappings.price_info_accounts[token_idx] != received_account.key() {
+        if entry.price_info_account != received_account.key() {
             msg!(
                 "Invalid price account: {}, expected: {}",
                 received_account.key(),
-                oracle_mappings.price_info_accounts[token_idx]
+                entry.price_info_account
             );
             return err!(ScopeError::UnexpectedAccount);
         }
         let clock = Clock::get()?;
-        match get_price(price_type, received_account, &mut accounts_iter, &clock) {
+        match get_price(
+            price_type,
+            received_account,
+            &mut accounts_iter,
+            &clock,
+            &ctx.accounts.oracle_prices,
+            oracle_twaps.as_ref(),
+            token_idx,
+            &entry,
+        ) {
             Ok(price) => {
                 // Only temporary load as mut to allow prices to be computed based on a scope chain
                 // from the price feed that is currently updated
-                let mut oracle_prices = ctx.accounts.oracle_prices.load_mut()?;
-                let to_update = oracle_prices
-                    .prices
-                    .get_mut(token_idx)
-                    .ok_or(ScopeError::BadTokenNb)?;
-
-                msg!(
-                    "tk {}, {:?}: {:?} to {:?} | prev_slot: {:?}, new_slot: {:?}, crt_slot: {:?}",
-                    token_idx,
+                let mut oracle_prices = extension::load_mut(&ctx.accounts.oracle_prices)?;
+                let mut to_update = oracle_prices.get(token_idx).ok_or(ScopeError::BadTokenNb)?;
+
+                // Checked after the price computation so its extra accounts are consumed
+                if to_update.is_frozen() {
+                    emit!(PriceSkipped {
+                        oracle_prices: ctx.accounts.oracle_prices.key(),
+                        token: token_nb,
+                        price_type: price_type.into(),
+                        reason: SkipReason::EntryFrozen,
+                        error_code: 0,
+                    });
+                    continue;
+                }
+
+                if let Err(e) =
+                    check_price_change(&to_update, &price, entry.max_change_bps_per_slot)
+                {
+                    let error_code = error_code(e);
+                    to_update.last_error_code = error_code;
+                    to_update.last_error_slot = clock.slot;
+                    oracle_prices.set(token_idx, to_update)?;
+                    emit!(PriceSkipped {
+                        oracle_prices: ctx.accounts.oracle_prices.key(),
+                        token: token_nb,
+                        price_type: price_type.into(),
+                        reason: SkipReason::PriceChangeTooLarge,
+                        error_code,
+                    });
+                    continue;
+                }
+
+                emit!(price_updated_event(
+                    ctx.accounts.oracle_prices.key(),
+                    token_nb,
                     price_type,
-                    to_update.price.value,
-                    price.price.value,
-                    to_update.last_updated_slot,
-                    price.last_updated_slot,
-                    clock.slot,
-                );
-
-                *to_update = price;
-                to_update.index = token_nb;
+                    &to_update,
+                    &price,
+                ));
+
+                oracle_prices.set(
+                    token_idx,
+                    DatedPrice {
+                        index: token_nb,
+                        ..price
+                    },
+                )?;
+
+                if let Some(oracle_twaps) = &oracle_twaps {
+                    extension::load_mut(oracle_twaps)?
+                        .update(token_idx, |twap| record_observation(twap, &price))?;
+                }
             }
-            Err(_) => {
-                // Skip the error, details is already logged in get_price and formatting here cost a lot of CU
-                msg!(
-                    "Price skipped as validation failed (token {}, type {:?})",
+            Err(e) => {
+                // Skip the entry but keep track of the error so the crank and the consumers can
+                // tell why the price is not refreshed, details are already logged in get_price
+                let error_code = error_code(e);
+                extension::load_mut(&ctx.accounts.oracle_prices)?.update(
                     token_idx,
-                    price_type
-                );
+                    |to_update| {
+                        to_update.last_error_code = error_code;
+                        to_update.last_error_slot = clock.slot;
+                    },
+                )?;
+                emit!(PriceSkipped {
+                    oracle_prices: ctx.accounts.oracle_prices.key(),
+                    token: token_nb,
+                    price_type: price_type.into(),
+                    reason: SkipReason::ValidationFailed,
+                    error_code,
+                });
             }
         };
     }
@@ -165,11 +276,85 @@ pub fn refresh_price_list(ctx: Context<RefreshList>, tokens: &[u16]) -> Result<(
     Ok(())
 }
 
+fn price_updated_event(
+    oracle_prices: Pubkey,
+    token: u16,
+    price_type: OracleType,
+    previous: &DatedPrice,
+    new: &DatedPrice,
+) -> PriceUpdated {
+    PriceUpdated {
+        oracle_prices,
+        token,
+        price_type: price_type.into(),
+        previous_value: previous.price.value,
+        previous_exp: previous.price.exp,
+        previous_slot: previous.last_updated_slot,
+        value: new.price.value,
+        exp: new.price.exp,
+        last_updated_slot: new.last_updated_slot,
+        unix_timestamp: new.unix_timestamp,
+    }
+}
+
+/// Numeric code of an error, as reported in a failed transaction
+fn error_code(error: anchor_lang::error::Error) -> u32 {
+    match ProgramError::from(error) {
+        ProgramError::Custom(code) => code,
+        // Builtin errors are encoded in the upper 32 bits
+        builtin => (u64::from(builtin) >> 32) as u32,
+    }
+}
+
+fn check_not_paused(configuration: &AccountLoader<crate::Configuration>) -> Result<()> {
+    if configuration.load()?.paused != 0 {
+        msg!("Price feed is paused");
+        return err!(ScopeError::FeedPaused);
+    }
+    Ok(())
+}
+
+/// Reject a price whose relative change from the previous one is above the entry
+/// maximum change per slot elapsed since the previous update.
+///
+/// The elapsed slots are capped to [`MAX_PRICE_CHANGE_ELAPSED_SLOTS`]: an entry that stayed
+/// stale through a larger move is rejected until the admin relaxes its maximum change.
+/// Entries without a configured maximum change or without a previous price are not checked.
+fn check_price_change(
+    previous: &DatedPrice,
+    new: &DatedPrice,
+    max_change_bps_per_slot: u16,
+) -> Result<()> {
+    if max_change_bps_per_slot == 0 || previous.price.value == 0 {
+        return Ok(());
+    }
+    let elapsed_slots = new
+        .last_updated_slot
+        .saturating_sub(previous.last_updated_slot)
+        .clamp(1, MAX_PRICE_CHANGE_ELAPSED_SLOTS);
+    let max_change_bps = u64::from(max_change_bps_per_slot).saturating_mul(elapsed_slots);
+    if !is_price_change_within(&previous.price, &new.price, max_change_bps) {
+        msg!(
+            "Price change from {:?} to {:?} in {} slots is above the limit of {} bps per slot",
+            previous.price,
+            new.price,
+            elapsed_slots,
+            max_change_bps_per_slot
+        );
+        return err!(ScopeError::PriceChangeTooLarge);
+    }
+    Ok(())
+}
+
 /// Ensure that the refresh instruction is executed directly to avoid any manipulation:
 ///
 /// - Check that the current instruction is executed by our program id (not in CPI).
-/// - Check that instructions preceding the refresh are compute budget instructions.
-fn check_execution_ctx(instruction_sysvar_account_info: &AccountInfo) -> Result<()> {
+/// - Check that instructions preceding the refresh are compute budget instructions or instructions
+///   of a program allowed by the admin.
+fn check_execution_ctx(
+    instruction_sysvar_account_info: &AccountInfo,
+    configuration: &AccountLoader<crate::Configuration>,
+) -> Result<()> {
     let current_index: usize = load_current_index_checked(instruction_sysvar_account_info)?.into();
 
     // 1- Check that the current instruction is executed by our program id (not in CPI).
@@ -185,13 +370,65 @@ fn check_execution_ctx(instruction_sysvar_account_info: &AccountInfo) -> Result<
         return err!(ScopeError::RefreshInCPI);
     }
 
-    // 2- Check that instructions preceding the refresh are compute budget instructions.
+    // 2- Check that instructions preceding the refresh are compute budget instructions or
+    // instructions of an allowed program.
+    let configuration = configuration.load()?;
     for ixn in 0..current_index {
         let ix = load_instruction_at_checked(ixn, instruction_sysvar_account_info)?;
-        if ix.program_id != COMPUTE_BUDGET_ID {
+        if ix.program_id != COMPUTE_BUDGET_ID
+            && !configuration.is_allowed_preceding_program(&ix.program_id)
+        {
+            msg!(
+                "Instruction {} of program {} is not allowed before a refresh",
+                ixn,
+                ix.program_id
+            );
             return err!(ScopeError::RefreshWithUnexpectedIxs);
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Price;
+
+    fn dated_price(value: u64, last_updated_slot: u64) -> DatedPrice {
+        DatedPrice {
+            price: Price { value, exp: 6 },
+            last_updated_slot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn price_change_grows_with_elapsed_slots() {
+        let previous = dated_price(1_000_000, 100);
+        // 10 bps per slot, 1% after 10 slots
+        assert!(check_price_change(&previous, &dated_price(1_010_000, 110), 10).is_ok());
+        assert!(check_price_change(&previous, &dated_price(1_010_001, 110), 10).is_err());
+        assert!(check_price_change(&previous, &dated_price(990_000, 110), 10).is_ok());
+        // Same slot counts as one slot
+        assert!(check_price_change(&previous, &dated_price(1_001_000, 100), 10).is_ok());
+        assert!(check_price_change(&previous, &dated_price(1_002_000, 100), 10).is_err());
+    }
+
+    #[test]
+    fn price_change_of_stale_price_is_capped() {
+        let previous = dated_price(1_000_000, 100);
+        // 1 bps per slot, at most 15% whatever the time elapsed
+        let stale_slot = 100 + 10 * MAX_PRICE_CHANGE_ELAPSED_SLOTS;
+        assert!(check_price_change(&previous, &dated_price(1_150_000, stale_slot), 1).is_ok());
+        assert!(check_price_change(&previous, &dated_price(1_150_001, stale_slot), 1).is_err());
+        assert!(check_price_change(&previous, &dated_price(1_000, u64::MAX), 1).is_err());
+    }
+
+    #[test]
+    fn price_change_not_configured() {
+        let previous = dated_price(1_000_000, 100);
+        assert!(check_price_change(&previous, &dated_price(1, 101), 0).is_ok());
+        assert!(check_price_change(&DatedPrice::default(), &previous, 10).is_ok());
+    }
+}
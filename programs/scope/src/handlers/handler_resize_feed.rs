@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    extension::{self, MAX_FEED_ENTRIES},
+    oracles::check_context,
+    ScopeError,
+};
+
+#[derive(Accounts)]
+#[instruction(num_entries: u16, feed_name: String)]
+pub struct ResizeFeed<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"conf", feed_name.as_bytes()],
+        bump,
+        has_one = admin,
+        has_one = oracle_prices,
+        has_one = oracle_mappings,
+    )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    #[account(mut)]
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, crate::OracleMappings>,
+    /// CHECK: Checked against the configuration when the feed records TWAPs, the feed's
+    /// `oracle_prices` account otherwise
+    #[account(mut)]
+    pub oracle_twaps: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow the prices, mappings and TWAPs (if recorded) accounts of the feed to hold `num_entries`
+/// entries.
+///
+/// The admin pays the rent of the additional entries. An account can only grow by
+/// `MAX_PERMITTED_DATA_INCREASE` bytes per instruction, large resizes are done in several
+/// instructions (about 9 entries at a time, limited by the size of the TWAP entries).
+pub fn process(ctx: Context<ResizeFeed>, num_entries: u16, _: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let num_entries = usize::from(num_entries);
+    let current_num_entries = extension::load(&ctx.accounts.oracle_prices)?.num_entries();
+    if num_entries <= current_num_entries || num_entries > MAX_FEED_ENTRIES {
+        msg!(
+            "Cannot resize the feed from {} to {} entries, max is {}",
+            current_num_entries,
+            num_entries,
+            MAX_FEED_ENTRIES
+        );
+        return err!(ScopeError::InvalidFeedSize);
+    }
+
+    msg!(
+        "Resize feed from {} to {} entries",
+        current_num_entries,
+        num_entries
+    );
+
+    let accounts = &ctx.accounts;
+    extension::grow(
+        &accounts.oracle_prices,
+        num_entries,
+        &accounts.admin,
+        &accounts.system_program,
+    )?;
+    extension::grow(
+        &accounts.oracle_mappings,
+        num_entries,
+        &accounts.admin,
+        &accounts.system_program,
+    )?;
+    if let Some(oracle_twaps) = accounts
+        .configuration
+        .load()?
+        .load_oracle_twaps(&accounts.oracle_twaps)?
+    {
+        extension::grow(
+            &oracle_twaps,
+            num_entries,
+            &accounts.admin,
+            &accounts.system_program,
+        )?;
+    }
+
+    Ok(())
+}
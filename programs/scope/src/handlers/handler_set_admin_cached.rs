@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(new_admin: Pubkey, feed_name: String)]
+pub struct SetAdminCached<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+}
+
+pub fn process(ctx: Context<SetAdminCached>, new_admin: Pubkey, _: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+
+    msg!(
+        "Set admin cached: current admin {}, new admin cached {}",
+        configuration.admin,
+        new_admin
+    );
+
+    configuration.admin_cached = new_admin;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{oracles::check_context, ScopeError, MAX_ALLOWED_PRECEDING_PROGRAMS};
+
+#[derive(Accounts)]
+#[instruction(programs: Vec<Pubkey>, feed_name: String)]
+pub struct SetAllowedPrecedingPrograms<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+}
+
+/// Replace the list of programs whose instructions may precede a refresh.
+///
+/// Allowed programs must not be able to move the price of any source of the feed
+/// (e.g. posting a pyth pull update or updating a stake pool balance is fine, a swap is not).
+pub fn process(
+    ctx: Context<SetAllowedPrecedingPrograms>,
+    programs: Vec<Pubkey>,
+    _: String,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    if programs.len() > MAX_ALLOWED_PRECEDING_PROGRAMS {
+        msg!(
+            "Cannot allow {} programs, max is {}",
+            programs.len(),
+            MAX_ALLOWED_PRECEDING_PROGRAMS
+        );
+        return err!(ScopeError::InvalidAllowedPrograms);
+    }
+    for (i, program) in programs.iter().enumerate() {
+        // The default pubkey marks unused slots, scope itself must stay forbidden
+        if *program == Pubkey::default() || *program == crate::ID || programs[..i].contains(program)
+        {
+            msg!("Program {} cannot be allowed before a refresh", program);
+            return err!(ScopeError::InvalidAllowedPrograms);
+        }
+    }
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+
+    msg!(
+        "Set programs allowed before a refresh: previous {:?}, new {:?}",
+        configuration.allowed_preceding_programs,
+        programs
+    );
+
+    configuration.allowed_preceding_programs = [Pubkey::default(); MAX_ALLOWED_PRECEDING_PROGRAMS];
+    configuration.allowed_preceding_programs[..programs.len()].copy_from_slice(&programs);
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(new_guardian: Pubkey, feed_name: String)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+}
+
+pub fn process(ctx: Context<SetGuardian>, new_guardian: Pubkey, _: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+
+    msg!(
+        "Set guardian: previous guardian {}, new guardian {}",
+        configuration.guardian,
+        new_guardian
+    );
+
+    configuration.guardian = new_guardian;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    extension,
+    oracles::{check_context, FULL_BPS},
+    OracleMappings, ScopeError,
+};
+
+/// Setting of an entry, independent of its oracle type, to update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryConfigUpdate {
+    /// Maximum age (in slots) of the price before being considered too old by consumers (0 to disable)
+    MaxAgeSlots(u32),
+    /// Maximum relative change of the price (in bps) per slot elapsed since the previous update,
+    /// refreshes above it are rejected (0 to disable)
+    MaxChangeBpsPerSlot(u16),
+    /// Maximum confidence interval relative to the price (in bps), prices from oracles
+    /// providing a larger confidence interval are rejected (0 for the default)
+    ConfidenceBps(u16),
+}
+
+#[derive(Accounts)]
+#[instruction(token:usize, feed_name: String)]
+pub struct UpdateEntryConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin, has_one = oracle_mappings)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, OracleMappings>,
+}
+
+pub fn process(
+    ctx: Context<UpdateEntryConfig>,
+    token: usize,
+    _: String,
+    update: EntryConfigUpdate,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut oracle_mappings = extension::load_mut(&ctx.accounts.oracle_mappings)?;
+    let mut entry = oracle_mappings.get(token).ok_or(ScopeError::BadTokenNb)?;
+
+    msg!("Update entry {} config: {:?}", token, update);
+
+    match update {
+        EntryConfigUpdate::MaxAgeSlots(max_age_slots) => {
+            entry.max_age_slots = max_age_slots;
+        }
+        EntryConfigUpdate::MaxChangeBpsPerSlot(max_change_bps_per_slot) => {
+            entry.max_change_bps_per_slot = max_change_bps_per_slot;
+        }
+        EntryConfigUpdate::ConfidenceBps(confidence_bps) => {
+            if confidence_bps > FULL_BPS {
+                msg!("Confidence cannot be above 100%");
+                return err!(ScopeError::InvalidEntryConfig);
+            }
+            entry.confidence_bps = confidence_bps;
+        }
+    }
+    oracle_mappings.set(token, entry)?;
+
+    Ok(())
+}
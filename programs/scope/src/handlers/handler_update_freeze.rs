@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{extension, oracles::check_context, ScopeError};
+
+/// Part of the feed to freeze or unfreeze
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FreezeTarget {
+    /// A single entry, its price is not refreshed anymore while frozen
+    Entry(u16),
+    /// The whole feed, no price is refreshed while paused
+    Feed,
+}
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct UpdateFreeze<'info> {
+    /// Admin or guardian, only the admin can unfreeze
+    pub authority: Signer<'info>,
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump, has_one = oracle_prices)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    #[account(mut)]
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+}
+
+pub fn process(
+    ctx: Context<UpdateFreeze>,
+    _: String,
+    target: FreezeTarget,
+    frozen: bool,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+    let authority = ctx.accounts.authority.key();
+
+    let is_admin = authority == configuration.admin;
+    let is_guardian = authority == configuration.guardian;
+    if !(is_admin || (frozen && is_guardian)) {
+        msg!(
+            "Signer {} is not allowed to {} {:?}",
+            authority,
+            if frozen { "freeze" } else { "unfreeze" },
+            target
+        );
+        return err!(ScopeError::UnauthorizedSigner);
+    }
+
+    msg!("Set frozen to {} for {:?}", frozen, target);
+
+    match target {
+        FreezeTarget::Entry(token) => {
+            extension::load_mut(&ctx.accounts.oracle_prices)?
+                .update(usize::from(token), |price| price.set_frozen(frozen))?;
+        }
+        FreezeTarget::Feed => configuration.paused = frozen.into(),
+    }
+
+    Ok(())
+}
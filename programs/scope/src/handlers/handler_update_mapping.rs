@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    oracles::{check_context, validate_oracle_account, OracleType},
-    OracleMappings, ScopeError,
+    events::MappingUpdated,
+    extension,
+    oracles::{check_context, validate_generic_data, validate_oracle_account, OracleType},
+    OracleMappings, ScopeError, GENERIC_DATA_SIZE,
 };
 
 #[derive(Accounts)]
@@ -22,26 +24,44 @@ pub fn process(
     token: usize,
     price_type: u8,
     _: String,
+    generic_data: [u8; GENERIC_DATA_SIZE],
 ) -> Result<()> {
     check_context(&ctx)?;
 
     let new_price_pubkey = ctx.accounts.price_info.key();
-    let mut oracle_mappings = ctx.accounts.oracle_mappings.load_mut()?;
-    let ref_price_pubkey = oracle_mappings
-        .price_info_accounts
-        .get_mut(token)
-        .ok_or(ScopeError::BadTokenNb)?;
+    let mut oracle_mappings = extension::load_mut(&ctx.accounts.oracle_mappings)?;
+    let mut entry = oracle_mappings.get(token).ok_or(ScopeError::BadTokenNb)?;
     let price_type: OracleType = price_type
         .try_into()
         .map_err(|_| ScopeError::BadTokenType)?;
 
     let price_info = ctx.accounts.price_info.as_ref();
+    let oracle_prices_pk = ctx.accounts.configuration.load()?.oracle_prices;
 
-    validate_oracle_account(price_type, price_info)?;
+    validate_generic_data(
+        price_type,
+        token,
+        oracle_mappings.num_entries(),
+        &generic_data,
+    )?;
+    validate_oracle_account(price_type, price_info, &oracle_prices_pk, &generic_data)?;
 
     // Every check succeeded, replace current with new
-    *ref_price_pubkey = new_price_pubkey;
-    oracle_mappings.price_types[token] = price_type.into();
+    let old_price_info = std::mem::replace(&mut entry.price_info_account, new_price_pubkey);
+    let old_price_type = std::mem::replace(&mut entry.price_type, price_type.into());
+    let old_generic_data = std::mem::replace(&mut entry.generic_data, generic_data);
+    oracle_mappings.set(token, entry)?;
+
+    emit!(MappingUpdated {
+        oracle_mappings: ctx.accounts.oracle_mappings.key(),
+        token: token.try_into().unwrap(),
+        old_price_info,
+        new_price_info: new_price_pubkey,
+        old_price_type,
+        new_price_type: price_type.into(),
+        old_generic_data,
+        new_generic_data: generic_data,
+    });
 
     Ok(())
 }
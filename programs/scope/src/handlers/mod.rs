@@ -1,7 +1,27 @@
+pub mod handler_approve_admin_cached;
+pub mod handler_get_prices;
+pub mod handler_init_oracle_twaps;
 pub mod handler_initialize;
+pub mod handler_migrate_oracle_mappings;
 pub mod handler_refresh_prices;
+pub mod handler_resize_feed;
+pub mod handler_set_admin_cached;
+pub mod handler_set_allowed_preceding_programs;
+pub mod handler_set_guardian;
+pub mod handler_update_entry_config;
+pub mod handler_update_freeze;
 pub mod handler_update_mapping;
 
+pub use handler_approve_admin_cached::*;
+pub use handler_get_prices::*;
+pub use handler_init_oracle_twaps::*;
 pub use handler_initialize::*;
+pub use handler_migrate_oracle_mappings::*;
 pub use handler_refresh_prices::*;
+pub use handler_resize_feed::*;
+pub use handler_set_admin_cached::*;
+pub use handler_set_allowed_preceding_programs::*;
+pub use handler_set_guardian::*;
+pub use handler_update_entry_config::*;
+pub use handler_update_freeze::*;
 pub use handler_update_mapping::*;
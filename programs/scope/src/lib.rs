@@ -1,6 +1,9 @@
 #![allow(clippy::result_large_err)] //Needed because we can't change Anchor result type
+pub mod events;
+pub mod extension;
 pub mod oracles;
 pub mod program_id;
+pub mod return_data;
 pub mod utils;
 
 mod handlers;
@@ -27,6 +30,12 @@ declare_id!(PROGRAM_ID);
 pub const MAX_ENTRIES_U16: u16 = 512;
 // Note: Need to be directly integer value to not confuse the IDL generator
 pub const MAX_ENTRIES: usize = 512;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const GENERIC_DATA_SIZE: usize = 32;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const TWAP_NUM_OBSERVATIONS: usize = 64;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const MAX_ALLOWED_PRECEDING_PROGRAMS: usize = 8;
 
 #[program]
 pub mod scope {
@@ -49,6 +58,10 @@ pub mod scope {
         handler_refresh_prices::refresh_price_list(ctx, &tokens)
     }
 
+    pub fn get_prices(ctx: Context<GetPrices>, tokens: Vec<u16>) -> Result<()> {
+        handler_get_prices::process(ctx, &tokens)
+    }
+
     pub fn update_mapping(
         ctx: Context<UpdateOracleMapping>,
         token: u64,
@@ -58,7 +71,88 @@ pub mod scope {
         let token: usize = token
             .try_into()
             .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
-        handler_update_mapping::process(ctx, token, price_type, feed_name)
+        handler_update_mapping::process(ctx, token, price_type, feed_name, [0; GENERIC_DATA_SIZE])
+    }
+
+    pub fn update_mapping_and_generic_data(
+        ctx: Context<UpdateOracleMapping>,
+        token: u64,
+        price_type: u8,
+        feed_name: String,
+        generic_data: [u8; GENERIC_DATA_SIZE],
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_update_mapping::process(ctx, token, price_type, feed_name, generic_data)
+    }
+
+    pub fn init_oracle_twaps(ctx: Context<InitOracleTwaps>, feed_name: String) -> Result<()> {
+        handler_init_oracle_twaps::process(ctx, feed_name)
+    }
+
+    pub fn migrate_oracle_mappings(
+        ctx: Context<MigrateOracleMappings>,
+        feed_name: String,
+    ) -> Result<()> {
+        handler_migrate_oracle_mappings::process(ctx, feed_name)
+    }
+
+    pub fn update_entry_config(
+        ctx: Context<UpdateEntryConfig>,
+        token: u64,
+        feed_name: String,
+        update: EntryConfigUpdate,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_update_entry_config::process(ctx, token, feed_name, update)
+    }
+
+    pub fn set_admin_cached(
+        ctx: Context<SetAdminCached>,
+        new_admin: Pubkey,
+        feed_name: String,
+    ) -> Result<()> {
+        handler_set_admin_cached::process(ctx, new_admin, feed_name)
+    }
+
+    pub fn approve_admin_cached(ctx: Context<ApproveAdminCached>, feed_name: String) -> Result<()> {
+        handler_approve_admin_cached::process(ctx, feed_name)
+    }
+
+    pub fn set_guardian(
+        ctx: Context<SetGuardian>,
+        new_guardian: Pubkey,
+        feed_name: String,
+    ) -> Result<()> {
+        handler_set_guardian::process(ctx, new_guardian, feed_name)
+    }
+
+    pub fn set_allowed_preceding_programs(
+        ctx: Context<SetAllowedPrecedingPrograms>,
+        programs: Vec<Pubkey>,
+        feed_name: String,
+    ) -> Result<()> {
+        handler_set_allowed_preceding_programs::process(ctx, programs, feed_name)
+    }
+
+    pub fn resize_feed(
+        ctx: Context<ResizeFeed>,
+        num_entries: u16,
+        feed_name: String,
+    ) -> Result<()> {
+        handler_resize_feed::process(ctx, num_entries, feed_name)
+    }
+
+    pub fn update_freeze(
+        ctx: Context<UpdateFreeze>,
+        feed_name: String,
+        target: FreezeTarget,
+        frozen: bool,
+    ) -> Result<()> {
+        handler_update_freeze::process(ctx, feed_name, target, frozen)
     }
 }
 
@@ -85,9 +179,16 @@ pub struct DatedPrice {
     pub price: Price,
     pub last_updated_slot: u64,
     pub unix_timestamp: u64,
-    pub _reserved: [u64; 2],
-    pub _reserved2: [u16; 3],
-    // Current index of the dated price.
+    // Confidence interval of the price, with the price exponent (0 if not provided by the oracle)
+    pub confidence: u64,
+    // Slot of the last failed refresh of the entry by `refresh_price_list`, cleared by a successful refresh
+    pub last_error_slot: u64,
+    // Error code of the last failed refresh, meaningful only if `last_error_slot` is set
+    pub last_error_code: u32,
+    // Status flags of the entry, see `DatedPrice::FLAG_*`
+    pub flags: u16,
+    // Current index of the dated price, `u16::MAX` until the entry is refreshed for the first time
+    // (all the indexes up to `MAX_FEED_ENTRIES` are valid).
     pub index: u16,
 }
 
@@ -97,9 +198,28 @@ impl Default for DatedPrice {
             price: Default::default(),
             last_updated_slot: Default::default(),
             unix_timestamp: Default::default(),
-            _reserved: Default::default(),
-            _reserved2: Default::default(),
-            index: MAX_ENTRIES_U16,
+            confidence: 0,
+            last_error_slot: 0,
+            last_error_code: 0,
+            flags: 0,
+            index: u16::MAX,
+        }
+    }
+}
+
+impl DatedPrice {
+    /// The entry has been frozen by the admin or the guardian and is not refreshed anymore
+    pub const FLAG_FROZEN: u16 = 1 << 0;
+
+    pub fn is_frozen(&self) -> bool {
+        self.flags & Self::FLAG_FROZEN != 0
+    }
+
+    pub fn set_frozen(&mut self, frozen: bool) {
+        if frozen {
+            self.flags |= Self::FLAG_FROZEN;
+        } else {
+            self.flags &= !Self::FLAG_FROZEN;
         }
     }
 }
@@ -111,12 +231,90 @@ pub struct OraclePrices {
     pub prices: [DatedPrice; MAX_ENTRIES],
 }
 
+impl OraclePrices {
+    /// Get the price of an entry, checking it is not frozen nor older than its configured max age.
+    ///
+    /// `oracle_mappings` must be the account referenced in `self.oracle_mappings`.
+    /// Entries without a configured max age are returned without age check.
+    pub fn get_checked(
+        &self,
+        oracle_mappings: &OracleMappings,
+        index: usize,
+        clock: &Clock,
+    ) -> ScopeResult<DatedPrice> {
+        let price = self.prices.get(index).ok_or(ScopeError::BadTokenNb)?;
+        check_price_usable(index, price, oracle_mappings.max_age_slots[index], clock)?;
+        Ok(*price)
+    }
+}
+
+/// Check that the price of entry `index` is not frozen nor older than `max_age_slots` (if set).
+pub(crate) fn check_price_usable(
+    index: usize,
+    price: &DatedPrice,
+    max_age_slots: u32,
+    clock: &Clock,
+) -> ScopeResult<()> {
+    if price.is_frozen() {
+        msg!("Price {} is frozen", index);
+        return Err(ScopeError::EntryFrozen);
+    }
+    let age = clock.slot.saturating_sub(price.last_updated_slot);
+    if max_age_slots != 0 && age > u64::from(max_age_slots) {
+        msg!(
+            "Price {} is too old: {} slots, max age: {} slots",
+            index,
+            age,
+            max_age_slots
+        );
+        return Err(ScopeError::PriceTooOld);
+    }
+    Ok(())
+}
+
+#[zero_copy]
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct TwapObservation {
+    // Price value, expressed with the exponent of the entry `EntryTwap`
+    pub value: u64,
+    pub unix_timestamp: u64,
+}
+
+// Ring buffer of the latest price observations of an entry
+#[zero_copy]
+#[derive(Debug, Eq, PartialEq)]
+pub struct EntryTwap {
+    // Exponent shared by all the observations
+    pub exp: u64,
+    // Index of the most recent observation
+    pub head: u16,
+    // Number of valid observations in the buffer
+    pub len: u16,
+    pub _reserved: u32,
+    pub observations: [TwapObservation; TWAP_NUM_OBSERVATIONS],
+}
+
+// Account to store the price observations used to compute TWAPs
+#[account(zero_copy)]
+pub struct OracleTwaps {
+    pub oracle_prices: Pubkey,
+    pub oracle_mappings: Pubkey,
+    pub twaps: [EntryTwap; MAX_ENTRIES],
+}
+
 // Accounts holding source of prices
 #[account(zero_copy)]
 pub struct OracleMappings {
     pub price_info_accounts: [Pubkey; MAX_ENTRIES],
     pub price_types: [u8; MAX_ENTRIES],
-    pub _reserved2: [u64; MAX_ENTRIES],
+    // Max age of the price in slots before being considered too old by consumers (0 if not set)
+    pub max_age_slots: [u32; MAX_ENTRIES],
+    // Max relative change of the price per slot elapsed since previous update, in bps (0 if not set)
+    pub max_change_bps_per_slot: [u16; MAX_ENTRIES],
+    // Max confidence interval relative to the price, in bps (0 for the default of 2%)
+    pub confidence_bps: [u16; MAX_ENTRIES],
+    // Oracle type specific configuration of each entry
+    pub generic_data: [[u8; GENERIC_DATA_SIZE]; MAX_ENTRIES],
 }
 
 // Configuration account of the program
@@ -125,7 +323,47 @@ pub struct Configuration {
     pub admin: Pubkey,
     pub oracle_mappings: Pubkey,
     pub oracle_prices: Pubkey,
-    _padding: [u64; 1267],
+    // Only feeds recording TWAPs have one, the default pubkey otherwise
+    pub oracle_twaps: Pubkey,
+    // Admin proposed by the current admin, has to approve the handover to become admin
+    pub admin_cached: Pubkey,
+    // Can only freeze entries or pause the whole feed, unfreezing requires the admin
+    pub guardian: Pubkey,
+    // Non zero if the whole feed is paused
+    pub paused: u8,
+    _padding0: [u8; 7],
+    // Programs whose instructions may precede a refresh in the transaction, besides compute budget
+    // (unused slots are set to the default pubkey)
+    pub allowed_preceding_programs: [Pubkey; MAX_ALLOWED_PRECEDING_PROGRAMS],
+    _padding: [u64; 1222],
+}
+
+impl Configuration {
+    /// Check if instructions of `program_id` may precede a refresh
+    pub fn is_allowed_preceding_program(&self, program_id: &Pubkey) -> bool {
+        *program_id != Pubkey::default() && self.allowed_preceding_programs.contains(program_id)
+    }
+
+    /// Load the TWAPs account of the feed, `None` if the feed does not record TWAPs
+    ///
+    /// `account` is ignored if the feed does not record TWAPs, callers pass `oracle_prices` instead.
+    pub fn load_oracle_twaps<'info>(
+        &self,
+        account: &AccountInfo<'info>,
+    ) -> Result<Option<AccountLoader<'info, OracleTwaps>>> {
+        if self.oracle_twaps == Pubkey::default() {
+            return Ok(None);
+        }
+        if *account.key != self.oracle_twaps {
+            msg!(
+                "Invalid TWAPs account: {}, expected: {}",
+                account.key,
+                self.oracle_twaps
+            );
+            return err!(ScopeError::UnexpectedAccount);
+        }
+        AccountLoader::try_from(account).map(Some)
+    }
 }
 
 #[error_code]
@@ -175,6 +413,48 @@ pub enum ScopeError {
 
     #[msg("Refresh price instruction preceded by unexpected ixs")]
     RefreshWithUnexpectedIxs,
+
+    #[msg("Invalid generic data for the oracle type")]
+    InvalidGenericData,
+
+    #[msg("Price is older than the configured max age")]
+    PriceTooOld,
+
+    #[msg("Price change since the last update is above the configured limit")]
+    PriceChangeTooLarge,
+
+    #[msg("Signer is not allowed to perform this operation")]
+    UnauthorizedSigner,
+
+    #[msg("Price entry is frozen")]
+    EntryFrozen,
+
+    #[msg("Price feed is paused")]
+    FeedPaused,
+
+    #[msg("Invalid entry configuration")]
+    InvalidEntryConfig,
+
+    #[msg("There was an error with the Switchboard On-Demand retrieval")]
+    SwitchboardOnDemandError,
+
+    #[msg("Oracle account is not owned by the expected program")]
+    WrongAccountOwner,
+
+    #[msg("Too many prices requested to fit in the return data")]
+    TooManyPricesRequested,
+
+    #[msg("Invalid list of programs allowed to precede a refresh")]
+    InvalidAllowedPrograms,
+
+    #[msg("Invalid number of entries for the feed")]
+    InvalidFeedSize,
+
+    #[msg("The feed already has a TWAPs account")]
+    OracleTwapsAlreadySet,
+
+    #[msg("The feed does not record TWAPs")]
+    OracleTwapsNotSet,
 }
 
 impl<T> From<TryFromPrimitiveError<T>> for ScopeError
@@ -192,6 +472,13 @@ impl From<TryFromIntError> for ScopeError {
     }
 }
 
+impl From<scope_types::ScopeError> for ScopeError {
+    fn from(err: scope_types::ScopeError) -> Self {
+        // Both crates declare the same errors in the same order
+        num_traits::FromPrimitive::from_u32(err as u32).unwrap_or(ScopeError::ConversionFailure)
+    }
+}
+
 pub type ScopeResult<T = ()> = std::result::Result<T, ScopeError>;
 
 impl From<DecimalError> for ScopeError {
@@ -201,3 +488,77 @@ impl From<DecimalError> for ScopeError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prices_and_mappings() -> (Box<OraclePrices>, Box<OracleMappings>) {
+        let mut prices: Box<OraclePrices> = Box::new(bytemuck::Zeroable::zeroed());
+        prices.prices[1] = DatedPrice {
+            price: Price { value: 42, exp: 0 },
+            last_updated_slot: 100,
+            ..Default::default()
+        };
+        let mut mappings: Box<OracleMappings> = Box::new(bytemuck::Zeroable::zeroed());
+        mappings.max_age_slots[1] = 10;
+        (prices, mappings)
+    }
+
+    fn clock_at(slot: u64) -> Clock {
+        Clock {
+            slot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn get_checked_fresh_price() {
+        let (prices, mappings) = prices_and_mappings();
+        let price = prices.get_checked(&mappings, 1, &clock_at(110)).unwrap();
+        assert_eq!(price.price.value, 42);
+    }
+
+    #[test]
+    fn get_checked_too_old_price() {
+        let (prices, mappings) = prices_and_mappings();
+        assert_eq!(
+            prices.get_checked(&mappings, 1, &clock_at(111)),
+            Err(ScopeError::PriceTooOld)
+        );
+    }
+
+    #[test]
+    fn get_checked_frozen_price() {
+        let (mut prices, mappings) = prices_and_mappings();
+        prices.prices[1].set_frozen(true);
+        assert_eq!(
+            prices.get_checked(&mappings, 1, &clock_at(110)),
+            Err(ScopeError::EntryFrozen)
+        );
+        prices.prices[1].set_frozen(false);
+        assert!(prices.get_checked(&mappings, 1, &clock_at(110)).is_ok());
+    }
+
+    #[test]
+    fn get_checked_without_max_age() {
+        let (prices, mappings) = prices_and_mappings();
+        assert!(prices.get_checked(&mappings, 0, &clock_at(1_000)).is_ok());
+        assert_eq!(
+            prices.get_checked(&mappings, MAX_ENTRIES, &clock_at(0)),
+            Err(ScopeError::BadTokenNb)
+        );
+    }
+
+    #[test]
+    fn errors_of_scope_types() {
+        assert_eq!(
+            ScopeError::from(scope_types::ScopeError::UnableToDeserializeAccount),
+            ScopeError::UnableToDeserializeAccount
+        );
+        assert_eq!(
+            ScopeError::from(scope_types::ScopeError::OracleTwapsNotSet),
+            ScopeError::OracleTwapsNotSet
+        );
+    }
+}
@@ -0,0 +1,642 @@
+//! Spot or time-weighted price of the tokens of concentrated liquidity pools (Orca Whirlpool,
+//! Raydium CLMM)
+//!
+//! The price of token A in token B (or of token B in token A if the entry is inverted) is derived
+//! from the current sqrt price of the pool, adjusted with the decimals of both tokens.
+//! A spot price can be moved by swapping against the pool, so the pool must have some in-range
+//! liquidity and the entry can require a minimum depth, see [`ClmmConfig::min_quote_depth`].
+//!
+//! Raydium pools record their sqrt price in an observation account, an entry can average these
+//! observations over a configured duration instead, see [`ClmmConfig::twap_duration_s`].
+//! Whirlpools do not keep a history of their prices on-chain, a time-weighted average is
+//! obtained by mapping a `ScopeTwap` entry on top of the spot price entry.
+//!
+//! The mapping points to the pool account. The mints of token A and B are provided as extra
+//! accounts to read their decimals, followed by the observation account of the pool for a
+//! time-weighted price.
+//!
+//! Reading the pool accounts requires the `yvaults` feature.
+use anchor_lang::prelude::*;
+use decimal_wad::rate::U128;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey;
+#[cfg(feature = "yvaults")]
+pub use yvaults_pools::*;
+
+use super::OracleGenericData;
+use crate::{utils::math::price_from_u128, Price, ScopeError, ScopeResult};
+
+/// Orca Whirlpool program
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey =
+    pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+/// Raydium concentrated liquidity (AMM v3) program
+pub const RAYDIUM_CLMM_PROGRAM_ID: Pubkey = pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+
+/// Number of extra accounts needed to refresh the spot price: the mints of token A and B
+pub const NB_EXTRA_ACCOUNTS: usize = 2;
+
+/// Longest duration of a time-weighted price, bounding the observations read in a refresh
+pub const MAX_TWAP_DURATION_S: u32 = 3_600;
+
+/// Supported concentrated liquidity pools
+#[derive(
+    Serialize, Deserialize, IntoPrimitive, TryFromPrimitive, Clone, Copy, PartialEq, Eq, Debug,
+)]
+#[repr(u16)]
+pub enum ClmmPool {
+    /// Orca `Whirlpool`
+    OrcaWhirlpool = 0,
+    /// Raydium CLMM `PoolState`, token 0 and 1 being token A and B
+    RaydiumClmm = 1,
+}
+
+impl ClmmPool {
+    /// Program allowed to own the pool account
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            ClmmPool::OrcaWhirlpool => ORCA_WHIRLPOOL_PROGRAM_ID,
+            ClmmPool::RaydiumClmm => RAYDIUM_CLMM_PROGRAM_ID,
+        }
+    }
+}
+
+/// Configuration of a `ClmmSpot` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ClmmConfig {
+    /// Minimum virtual reserve of the quote token backing the in-range liquidity of the pool,
+    /// in the smallest unit of the quote token, 0 only requires some in-range liquidity
+    ///
+    /// Moving the price by a factor `f` requires swapping about `(sqrt(f) - 1)` times this
+    /// reserve while the price stays in range.
+    pub min_quote_depth: u64,
+    /// Kind of the mapped pool, see [`ClmmPool`]
+    pub pool: u16,
+    /// 1 to price token B in token A, 0 to price token A in token B
+    pub invert: u16,
+    /// Duration in seconds of the time-weighted average of the observations of a Raydium pool,
+    /// 0 for the spot price
+    pub twap_duration_s: u32,
+}
+
+impl OracleGenericData for ClmmConfig {}
+
+impl ClmmConfig {
+    pub fn pool(&self) -> ScopeResult<ClmmPool> {
+        ClmmPool::try_from(self.pool).map_err(|_| {
+            msg!("Unknown concentrated liquidity pool kind {}", self.pool);
+            ScopeError::InvalidGenericData
+        })
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.invert != 0
+    }
+
+    pub fn is_twap(&self) -> bool {
+        self.twap_duration_s != 0
+    }
+
+    /// Number of extra accounts needed to refresh the price of the entry
+    pub fn nb_extra_accounts(&self) -> usize {
+        NB_EXTRA_ACCOUNTS + usize::from(self.is_twap())
+    }
+}
+
+pub fn validate_config(config: &ClmmConfig) -> ScopeResult<()> {
+    let pool = config.pool()?;
+    if config.invert > 1 {
+        msg!("CLMM invert flag must be 0 or 1, got {}", config.invert);
+        return Err(ScopeError::InvalidGenericData);
+    }
+    if config.is_twap() {
+        if pool != ClmmPool::RaydiumClmm {
+            msg!("Only Raydium CLMM pools record observations for a time-weighted price");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        if config.twap_duration_s > MAX_TWAP_DURATION_S {
+            msg!(
+                "CLMM TWAP duration of {}s is above the maximum of {}s",
+                config.twap_duration_s,
+                MAX_TWAP_DURATION_S
+            );
+            return Err(ScopeError::InvalidGenericData);
+        }
+    }
+    Ok(())
+}
+
+/// Fields of a pool needed to compute its spot price
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    /// Square root of the price of token A in token B (in their smallest units), as a Q64.64
+    pub sqrt_price_x64: u128,
+    /// Liquidity of the positions in range of the current price
+    pub liquidity: u128,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+}
+
+/// Spot price of the pool in the configured direction, after the liquidity checks
+pub fn spot_price(
+    pool: &PoolState,
+    config: &ClmmConfig,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> ScopeResult<Price> {
+    pool_price(pool, pool.sqrt_price_x64, config, decimals_a, decimals_b)
+}
+
+/// Price in the configured direction at the given sqrt price of the pool (spot or average),
+/// after the liquidity checks on the current state of the pool
+pub fn pool_price(
+    pool: &PoolState,
+    sqrt_price_x64: u128,
+    config: &ClmmConfig,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> ScopeResult<Price> {
+    if pool.sqrt_price_x64 == 0 || pool.liquidity == 0 || sqrt_price_x64 == 0 {
+        msg!("CLMM pool has no liquidity in range of its current price");
+        return Err(ScopeError::PriceNotValid);
+    }
+    // sqrt(1 / p) in Q64.64 is 2^128 / sqrt(p) in Q64.64
+    let invert = |sqrt_price_x64: u128| u128::MAX / sqrt_price_x64;
+    let (current_sqrt_price_x64, sqrt_price_x64, decimals_base, decimals_quote) =
+        if config.is_inverted() {
+            (
+                invert(pool.sqrt_price_x64),
+                invert(sqrt_price_x64),
+                decimals_b,
+                decimals_a,
+            )
+        } else {
+            (pool.sqrt_price_x64, sqrt_price_x64, decimals_a, decimals_b)
+        };
+    let depth = quote_depth(pool.liquidity, current_sqrt_price_x64);
+    if depth < config.min_quote_depth {
+        msg!(
+            "CLMM pool depth of {} is below the configured minimum of {}",
+            depth,
+            config.min_quote_depth
+        );
+        return Err(ScopeError::PriceNotValid);
+    }
+    price_from_sqrt_price(sqrt_price_x64, decimals_base, decimals_quote)
+}
+
+/// Time-weighted average of the sqrt prices recorded by the observations of a pool over the
+/// last `duration_s` seconds
+///
+/// `observations` are `(timestamp, sqrt_price_x64)` pairs from the newest to the oldest, each
+/// sqrt price being held until the next observation and the newest one until `now`.
+/// An uninitialized observation (timestamp 0) or a newer one (the buffer wrapped around) ends
+/// the history, which must cover the whole duration.
+pub fn twap_sqrt_price(
+    observations: impl Iterator<Item = (u64, u128)>,
+    now: u64,
+    duration_s: u64,
+) -> ScopeResult<u128> {
+    let window_start = now.saturating_sub(duration_s);
+    let mut end = now;
+    let mut weighted_sum: u128 = 0;
+    for (timestamp, sqrt_price_x64) in observations {
+        if timestamp == 0 || timestamp > end {
+            break;
+        }
+        let held = u128::from(end - timestamp.max(window_start));
+        weighted_sum = sqrt_price_x64
+            .checked_mul(held)
+            .and_then(|weighted| weighted_sum.checked_add(weighted))
+            .ok_or(ScopeError::IntegerOverflow)?;
+        if timestamp <= window_start {
+            return Ok(weighted_sum / u128::from(duration_s.max(1)));
+        }
+        end = timestamp;
+    }
+    msg!(
+        "CLMM pool observations do not cover the last {} seconds",
+        duration_s
+    );
+    Err(ScopeError::PriceNotValid)
+}
+
+/// Virtual reserve of the quote token of the in-range liquidity: `liquidity * sqrt_price`
+///
+/// Saturates to `u64::MAX` as any larger depth passes all checks.
+pub fn quote_depth(liquidity: u128, sqrt_price_x64: u128) -> u64 {
+    liquidity
+        .checked_mul(sqrt_price_x64)
+        .map_or(u64::MAX, |depth| {
+            u64::try_from(depth >> 64).unwrap_or(u64::MAX)
+        })
+}
+
+/// Price of 1 base token in quote tokens from the Q64.64 sqrt price of the pool
+///
+/// The square of the sqrt price is computed on its 64 most significant bits, then scaled by
+/// powers of 10 as much as possible while it is shifted back to keep the best precision.
+pub fn price_from_sqrt_price(
+    sqrt_price_x64: u128,
+    decimals_base: u8,
+    decimals_quote: u8,
+) -> ScopeResult<Price> {
+    let ten = U128::from(10u64);
+    let significant_bits = u128::BITS - sqrt_price_x64.leading_zeros();
+    let dropped_bits = significant_bits.saturating_sub(64);
+    let root = sqrt_price_x64 >> dropped_bits;
+    // `root` fits in 64 bits so its square cannot overflow
+    let mut value = U128::from(root * root);
+    // Price in smallest units is `value / 2^shift`
+    let mut shift = 128 - 2 * dropped_bits;
+    // Price in whole tokens is `value / 2^shift / 10^exp`
+    let mut exp = i64::from(decimals_quote) - i64::from(decimals_base);
+    while shift > 0 {
+        match value.checked_mul(ten) {
+            Some(scaled) => {
+                value = scaled;
+                exp += 1;
+            }
+            None => {
+                value >>= 1;
+                shift -= 1;
+            }
+        }
+    }
+    if exp < 0 {
+        let factor = ten
+            .checked_pow(U128::from(exp.unsigned_abs()))
+            .ok_or(ScopeError::IntegerOverflow)?;
+        let value = value
+            .checked_mul(factor)
+            .ok_or(ScopeError::IntegerOverflow)?;
+        price_from_u128(value, 0)
+    } else {
+        price_from_u128(value, exp.unsigned_abs())
+    }
+}
+
+#[cfg(feature = "yvaults")]
+mod yvaults_pools {
+    use std::cell::Ref;
+
+    use anchor_lang::prelude::*;
+    use kamino::{
+        raydium_amm_v3::states::{
+            ObservationState as RaydiumObservations, PoolState as RaydiumPool,
+        },
+        utils::types::DEX,
+        whirlpool::state::Whirlpool as OrcaWhirlpool,
+    };
+    use yvaults as kamino;
+
+    use super::{pool_price, twap_sqrt_price, ClmmConfig, ClmmPool, PoolState};
+    use crate::{
+        oracles::{
+            check_account_owner, constant_product_lp::mint_supply_and_decimals, OracleGenericData,
+        },
+        utils::{account_deserialize, zero_copy_deserialize},
+        DatedPrice, ScopeError, ScopeResult, GENERIC_DATA_SIZE,
+    };
+
+    impl From<DEX> for ClmmPool {
+        fn from(dex: DEX) -> Self {
+            match dex {
+                DEX::Orca => ClmmPool::OrcaWhirlpool,
+                DEX::Raydium => ClmmPool::RaydiumClmm,
+            }
+        }
+    }
+
+    /// Deserialized pool account, also used to value the positions of kTokens
+    pub enum PoolAccount<'a> {
+        Orca(OrcaWhirlpool),
+        Raydium(Ref<'a, RaydiumPool>),
+    }
+
+    impl<'a> PoolAccount<'a> {
+        /// Read the pool account, checking its discriminator
+        pub fn load(pool: ClmmPool, pool_account: &'a AccountInfo) -> ScopeResult<Self> {
+            Ok(match pool {
+                ClmmPool::OrcaWhirlpool => PoolAccount::Orca(account_deserialize(pool_account)?),
+                ClmmPool::RaydiumClmm => PoolAccount::Raydium(zero_copy_deserialize(pool_account)?),
+            })
+        }
+
+        /// Fields of the pool needed to compute its price
+        pub fn state(&self) -> PoolState {
+            match self {
+                PoolAccount::Orca(whirlpool) => PoolState {
+                    sqrt_price_x64: whirlpool.sqrt_price,
+                    liquidity: whirlpool.liquidity,
+                    mint_a: whirlpool.token_mint_a,
+                    mint_b: whirlpool.token_mint_b,
+                },
+                PoolAccount::Raydium(pool) => PoolState {
+                    sqrt_price_x64: pool.sqrt_price_x64,
+                    liquidity: pool.liquidity,
+                    mint_a: pool.token_mint_0,
+                    mint_b: pool.token_mint_1,
+                },
+            }
+        }
+    }
+
+    /// Gives the spot price of the pool, or the average of its observations, as of the current
+    /// slot
+    pub fn get_price<'a, 'b>(
+        pool_account: &AccountInfo,
+        config: &ClmmConfig,
+        clock: &Clock,
+        extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    ) -> Result<DatedPrice>
+    where
+        'a: 'b,
+    {
+        // Consume all the extra accounts before any failure
+        let mut next_account = || {
+            extra_accounts
+                .next()
+                .ok_or(ScopeError::AccountsAndTokenMismatch)
+        };
+        let mint_a = next_account()?;
+        let mint_b = next_account()?;
+        let observation_account = if config.is_twap() {
+            Some(next_account()?)
+        } else {
+            None
+        };
+
+        let pool_data = PoolAccount::load(config.pool()?, pool_account)?;
+        let pool = pool_data.state();
+
+        let account_check = |account: &AccountInfo, expected, name| {
+            if *account.key != expected {
+                msg!(
+                    "CLMM received account {} for {} is not the one expected ({})",
+                    account.key,
+                    name,
+                    expected
+                );
+                err!(ScopeError::UnexpectedAccount)
+            } else {
+                Ok(())
+            }
+        };
+        account_check(mint_a, pool.mint_a, "mint_a")?;
+        account_check(mint_b, pool.mint_b, "mint_b")?;
+
+        let decimals_a = mint_supply_and_decimals(mint_a)?.decimals;
+        let decimals_b = mint_supply_and_decimals(mint_b)?.decimals;
+
+        let sqrt_price_x64 = match (&pool_data, observation_account) {
+            (_, None) => pool.sqrt_price_x64,
+            (PoolAccount::Raydium(raydium_pool), Some(observation_account)) => {
+                account_check(
+                    observation_account,
+                    raydium_pool.observation_key,
+                    "observation",
+                )?;
+                raydium_twap_sqrt_price(raydium_pool, observation_account, config, clock)?
+            }
+            (PoolAccount::Orca(_), Some(_)) => {
+                msg!("Orca whirlpools do not record observations");
+                return err!(ScopeError::InvalidGenericData);
+            }
+        };
+
+        let price = pool_price(&pool, sqrt_price_x64, config, decimals_a, decimals_b)?;
+
+        Ok(DatedPrice {
+            price,
+            last_updated_slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp.try_into().unwrap_or(0),
+            ..Default::default()
+        })
+    }
+
+    /// Average sqrt price of the Raydium pool over the configured duration, from the ring buffer
+    /// of observations whose newest entry is at the observation index of the pool
+    fn raydium_twap_sqrt_price(
+        pool: &RaydiumPool,
+        observation_account: &AccountInfo,
+        config: &ClmmConfig,
+        clock: &Clock,
+    ) -> Result<u128> {
+        check_account_owner(observation_account, &[ClmmPool::RaydiumClmm.program_id()])?;
+        let observation_state = zero_copy_deserialize::<RaydiumObservations>(observation_account)?;
+        let nb_observations = observation_state.observations.len();
+        let newest = usize::from(pool.observation_index) % nb_observations;
+        let newest_first = (0..nb_observations).map(|age| {
+            let observation =
+                observation_state.observations[(newest + nb_observations - age) % nb_observations];
+            (
+                u64::from(observation.block_timestamp),
+                observation.sqrt_price_x64,
+            )
+        });
+        let now = u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::ConversionFailure)?;
+        Ok(twap_sqrt_price(
+            newest_first,
+            now,
+            config.twap_duration_s.into(),
+        )?)
+    }
+
+    /// Check that the account is a pool of the configured kind
+    pub fn validate_pool_info(
+        pool_account: &AccountInfo,
+        generic_data: &[u8; GENERIC_DATA_SIZE],
+    ) -> Result<()> {
+        if cfg!(feature = "skip_price_validation") {
+            return Ok(());
+        }
+        let pool = ClmmConfig::from_generic_data(generic_data).pool()?;
+        check_account_owner(pool_account, &[pool.program_id()])?;
+        PoolAccount::load(pool, pool_account)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::math::price_value_with_exp;
+
+    const Q64: u128 = 1 << 64;
+
+    fn value_at_exp(price: &Price, exp: u64) -> u64 {
+        price_value_with_exp(price, exp).unwrap().as_u64()
+    }
+
+    fn pool(sqrt_price_x64: u128, liquidity: u128) -> PoolState {
+        PoolState {
+            sqrt_price_x64,
+            liquidity,
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+        }
+    }
+
+    fn config(invert: bool, min_quote_depth: u64) -> ClmmConfig {
+        ClmmConfig {
+            min_quote_depth,
+            pool: ClmmPool::OrcaWhirlpool.into(),
+            invert: invert.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn price_with_decimals() {
+        // 0.25 USDC lamport (6 decimals) per SOL lamport (9 decimals): 250 USDC per SOL
+        let price = price_from_sqrt_price(Q64 / 2, 9, 6).unwrap();
+        assert_eq!(value_at_exp(&price, 6), 250_000_000);
+
+        // 4 SOL lamports per USDC lamport: 0.004 SOL per USDC
+        let price = price_from_sqrt_price(2 * Q64, 6, 9).unwrap();
+        assert_eq!(value_at_exp(&price, 9), 4_000_000);
+    }
+
+    #[test]
+    fn inverted_price() {
+        let pool = pool(Q64 / 2, Q64);
+        let price = spot_price(&pool, &config(false, 0), 9, 6).unwrap();
+        assert_eq!(value_at_exp(&price, 6), 250_000_000);
+        let price = spot_price(&pool, &config(true, 0), 9, 6).unwrap();
+        // 1 / 250, the inversion rounds down
+        assert!(value_at_exp(&price, 12).abs_diff(4_000_000_000) <= 1);
+    }
+
+    #[test]
+    fn extreme_sqrt_prices() {
+        // Bounds of the sqrt prices of the whirlpool program
+        let min = price_from_sqrt_price(4_295_048_016, 0, 0).unwrap();
+        // ~5.42e-20
+        assert_eq!(value_at_exp(&min, 22), 542);
+        // ~3.4e38 does not fit in a price
+        assert_eq!(
+            price_from_sqrt_price(79_226_673_515_401_279_992_447_579_055, 0, 0),
+            Err(ScopeError::IntegerOverflow)
+        );
+    }
+
+    #[test]
+    fn liquidity_checks() {
+        assert_eq!(
+            spot_price(&pool(Q64, 0), &config(false, 0), 6, 6),
+            Err(ScopeError::PriceNotValid)
+        );
+        // Price of 4 B per A with a liquidity of 1000: 2000 B of virtual reserve
+        let pool = pool(2 * Q64, 1_000);
+        assert_eq!(quote_depth(pool.liquidity, pool.sqrt_price_x64), 2_000);
+        assert!(spot_price(&pool, &config(false, 2_000), 6, 6).is_ok());
+        assert_eq!(
+            spot_price(&pool, &config(false, 2_001), 6, 6),
+            Err(ScopeError::PriceNotValid)
+        );
+        // 500 A of virtual reserve when pricing B in A, the inversion rounds down
+        assert!(spot_price(&pool, &config(true, 499), 6, 6).is_ok());
+        assert_eq!(
+            spot_price(&pool, &config(true, 500), 6, 6),
+            Err(ScopeError::PriceNotValid)
+        );
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(validate_config(&config(true, 0)).is_ok());
+        assert_eq!(
+            validate_config(&ClmmConfig {
+                invert: 2,
+                ..Default::default()
+            }),
+            Err(ScopeError::InvalidGenericData)
+        );
+        assert_eq!(
+            validate_config(&ClmmConfig {
+                pool: 2,
+                ..Default::default()
+            }),
+            Err(ScopeError::InvalidGenericData)
+        );
+        let twap = ClmmConfig {
+            pool: ClmmPool::RaydiumClmm.into(),
+            twap_duration_s: MAX_TWAP_DURATION_S,
+            ..Default::default()
+        };
+        assert!(validate_config(&twap).is_ok());
+        assert_eq!(twap.nb_extra_accounts(), 3);
+        assert_eq!(
+            validate_config(&ClmmConfig {
+                twap_duration_s: MAX_TWAP_DURATION_S + 1,
+                ..twap
+            }),
+            Err(ScopeError::InvalidGenericData)
+        );
+        // Whirlpools have no observations
+        assert_eq!(
+            validate_config(&ClmmConfig {
+                pool: ClmmPool::OrcaWhirlpool.into(),
+                ..twap
+            }),
+            Err(ScopeError::InvalidGenericData)
+        );
+    }
+
+    #[test]
+    fn twap_of_observations() {
+        // Newest first: 2 * Q64 since 1060, Q64 from 1000 to 1060, 4 * Q64 before
+        let observations = [(1_060, 2 * Q64), (1_000, Q64), (900, 4 * Q64)];
+
+        // Only the newest observation
+        assert_eq!(
+            twap_sqrt_price(observations.into_iter(), 1_100, 40),
+            Ok(2 * Q64)
+        );
+        // 40s at 2 * Q64 and 60s at Q64
+        assert_eq!(
+            twap_sqrt_price(observations.into_iter(), 1_100, 100),
+            Ok(Q64 * 140 / 100)
+        );
+        // 40s at 2 * Q64, 60s at Q64 and 20s at 4 * Q64
+        assert_eq!(
+            twap_sqrt_price(observations.into_iter(), 1_100, 120),
+            Ok(Q64 * 220 / 120)
+        );
+    }
+
+    #[test]
+    fn twap_not_covered_by_observations() {
+        // Uninitialized observations end the history
+        let observations = [(1_060, 2 * Q64), (1_000, Q64), (0, 0)];
+        assert_eq!(
+            twap_sqrt_price(observations.into_iter(), 1_100, 100),
+            Ok(Q64 * 140 / 100)
+        );
+        assert_eq!(
+            twap_sqrt_price(observations.into_iter(), 1_100, 101),
+            Err(ScopeError::PriceNotValid)
+        );
+        // A newer observation is the start of the ring buffer
+        let observations = [(1_060, 2 * Q64), (1_000, Q64), (1_080, 4 * Q64)];
+        assert_eq!(
+            twap_sqrt_price(observations.into_iter(), 1_100, 101),
+            Err(ScopeError::PriceNotValid)
+        );
+    }
+
+    #[test]
+    fn twap_price_with_current_liquidity() {
+        let pool = pool(Q64, 1_000);
+        // Priced at the average but the depth is the one of the current price
+        let price = pool_price(&pool, 2 * Q64, &config(false, 1_000), 6, 6).unwrap();
+        assert_eq!(value_at_exp(&price, 6), 4_000_000);
+        assert_eq!(
+            pool_price(&pool, 2 * Q64, &config(false, 1_001), 6, 6),
+            Err(ScopeError::PriceNotValid)
+        );
+    }
+}
@@ -0,0 +1,523 @@
+//! Price of the LP tokens of constant product AMM pools (Orca legacy token swap, Raydium AMM v4)
+//!
+//! LP tokens are valued with the "fair reserves" of the pool rather than its raw reserves: the
+//! reserves are derived from the pool invariant `k = reserve_a * reserve_b` and the scope prices
+//! of both tokens, giving a pool value of `2 * sqrt(k * price_a * price_b)`.
+//! Swapping in the pool moves its reserves but cannot decrease `k`, so the LP price cannot be
+//! pushed around by trading against the pool (see Alpha Homora fair LP pricing).
+//!
+//! The mapping points to the pool account and the entries of the feed pricing both tokens are
+//! defined in the entry generic data (see [`ConstantProductLpConfig`]).
+//! The vaults and mints of the pool are provided as extra accounts, see [`PoolAccounts`].
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use decimal_wad::rate::U128;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey;
+
+use super::{check_account_owner, get_source_price, validate_source, OracleGenericData};
+use crate::{
+    extension::FeedPrices, utils::math::price_from_u128, DatedPrice, Price, ScopeError,
+    ScopeResult, GENERIC_DATA_SIZE,
+};
+
+/// Orca legacy token swap programs (v2 and v1), forks of the SPL token swap
+pub const ORCA_TOKEN_SWAP_PROGRAM_IDS: [Pubkey; 2] = [
+    pubkey!("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP"),
+    pubkey!("DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1"),
+];
+
+/// Raydium AMM v4 program
+pub const RAYDIUM_AMM_V4_PROGRAM_ID: Pubkey =
+    pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+
+/// Number of extra accounts needed to refresh the price, see [`PoolAccounts`]
+pub const NB_EXTRA_ACCOUNTS: usize = 5;
+
+/// Supported constant product pools
+#[derive(
+    Serialize, Deserialize, IntoPrimitive, TryFromPrimitive, Clone, Copy, PartialEq, Eq, Debug,
+)]
+#[repr(u16)]
+pub enum ConstantProductPool {
+    /// Orca legacy pool (`SwapV1` of the SPL token swap) with a constant product curve
+    OrcaTokenSwap = 0,
+    /// Raydium AMM v4 pool (`AmmInfo`)
+    RaydiumAmmV4 = 1,
+}
+
+impl ConstantProductPool {
+    /// Programs allowed to own the pool account
+    pub fn program_ids(&self) -> &'static [Pubkey] {
+        match self {
+            ConstantProductPool::OrcaTokenSwap => &ORCA_TOKEN_SWAP_PROGRAM_IDS,
+            ConstantProductPool::RaydiumAmmV4 => &[RAYDIUM_AMM_V4_PROGRAM_ID],
+        }
+    }
+}
+
+/// Configuration of a `ConstantProductLp` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ConstantProductLpConfig {
+    /// Kind of the mapped pool, see [`ConstantProductPool`]
+    pub pool: u16,
+    /// Index of the entry pricing token A of the pool
+    pub price_a: u16,
+    /// Index of the entry pricing token B of the pool
+    pub price_b: u16,
+}
+
+impl OracleGenericData for ConstantProductLpConfig {}
+
+impl ConstantProductLpConfig {
+    pub fn pool(&self) -> ScopeResult<ConstantProductPool> {
+        ConstantProductPool::try_from(self.pool).map_err(|_| {
+            msg!("Unknown constant product pool kind {}", self.pool);
+            ScopeError::InvalidGenericData
+        })
+    }
+}
+
+pub fn validate_config(
+    entry_id: usize,
+    num_entries: usize,
+    config: &ConstantProductLpConfig,
+) -> ScopeResult<()> {
+    config.pool()?;
+    validate_source(entry_id, config.price_a, num_entries)?;
+    validate_source(entry_id, config.price_b, num_entries)
+}
+
+/// Accounts of a pool needed to compute its LP price, provided in this order as extra accounts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolAccounts {
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub lp_mint: Pubkey,
+}
+
+impl PoolAccounts {
+    pub fn to_vec(&self) -> Vec<Pubkey> {
+        vec![
+            self.vault_a,
+            self.vault_b,
+            self.mint_a,
+            self.mint_b,
+            self.lp_mint,
+        ]
+    }
+}
+
+/// Fields of a pool account needed to compute its LP price
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    pub accounts: PoolAccounts,
+    /// Amounts held in the vaults that are not owned by the LP holders
+    pub excluded_a: u64,
+    pub excluded_b: u64,
+}
+
+pub mod orca {
+    /// Size of a `SwapVersion::SwapV1` account
+    pub const SWAP_SIZE: usize = 324;
+    pub const SWAP_V1_VERSION: u8 = 1;
+    pub const IS_INITIALIZED_OFFSET: usize = 1;
+    pub const VAULT_A_OFFSET: usize = 35;
+    pub const VAULT_B_OFFSET: usize = 67;
+    pub const LP_MINT_OFFSET: usize = 99;
+    pub const MINT_A_OFFSET: usize = 131;
+    pub const MINT_B_OFFSET: usize = 163;
+    pub const CURVE_TYPE_OFFSET: usize = 291;
+    pub const CONSTANT_PRODUCT_CURVE: u8 = 0;
+}
+
+pub mod raydium {
+    /// Size of an `AmmInfo` account
+    pub const AMM_INFO_SIZE: usize = 752;
+    pub const STATUS_OFFSET: usize = 0;
+    pub const NEED_TAKE_PNL_COIN_OFFSET: usize = 192;
+    pub const NEED_TAKE_PNL_PC_OFFSET: usize = 200;
+    pub const COIN_VAULT_OFFSET: usize = 336;
+    pub const PC_VAULT_OFFSET: usize = 368;
+    pub const COIN_MINT_OFFSET: usize = 400;
+    pub const PC_MINT_OFFSET: usize = 432;
+    pub const LP_MINT_OFFSET: usize = 464;
+    pub const UNINITIALIZED_STATUS: u64 = 0;
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(*array_ref![data, offset, 32])
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(*array_ref![data, offset, 8])
+}
+
+impl PoolState {
+    /// Read the pool account data, checking it is an initialized constant product pool
+    pub fn from_data(pool: ConstantProductPool, data: &[u8]) -> ScopeResult<Self> {
+        match pool {
+            ConstantProductPool::OrcaTokenSwap => {
+                use orca::*;
+                if data.len() < SWAP_SIZE || data[0] != SWAP_V1_VERSION {
+                    msg!("Account is not an orca token swap pool");
+                    return Err(ScopeError::UnableToDeserializeAccount);
+                }
+                if data[IS_INITIALIZED_OFFSET] == 0 {
+                    msg!("Orca pool is not initialized");
+                    return Err(ScopeError::UnexpectedAccount);
+                }
+                if data[CURVE_TYPE_OFFSET] != CONSTANT_PRODUCT_CURVE {
+                    msg!("Orca pool curve is not a constant product");
+                    return Err(ScopeError::UnexpectedAccount);
+                }
+                Ok(PoolState {
+                    accounts: PoolAccounts {
+                        vault_a: read_pubkey(data, VAULT_A_OFFSET),
+                        vault_b: read_pubkey(data, VAULT_B_OFFSET),
+                        mint_a: read_pubkey(data, MINT_A_OFFSET),
+                        mint_b: read_pubkey(data, MINT_B_OFFSET),
+                        lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+                    },
+                    excluded_a: 0,
+                    excluded_b: 0,
+                })
+            }
+            ConstantProductPool::RaydiumAmmV4 => {
+                use raydium::*;
+                if data.len() < AMM_INFO_SIZE {
+                    msg!("Account is not a raydium amm pool");
+                    return Err(ScopeError::UnableToDeserializeAccount);
+                }
+                if read_u64(data, STATUS_OFFSET) == UNINITIALIZED_STATUS {
+                    msg!("Raydium pool is not initialized");
+                    return Err(ScopeError::UnexpectedAccount);
+                }
+                // Funds placed on the order book by the pool are not accounted for, this can only
+                // under-value the LP token
+                Ok(PoolState {
+                    accounts: PoolAccounts {
+                        vault_a: read_pubkey(data, COIN_VAULT_OFFSET),
+                        vault_b: read_pubkey(data, PC_VAULT_OFFSET),
+                        mint_a: read_pubkey(data, COIN_MINT_OFFSET),
+                        mint_b: read_pubkey(data, PC_MINT_OFFSET),
+                        lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+                    },
+                    // Pnl owed to the pool owner
+                    excluded_a: read_u64(data, NEED_TAKE_PNL_COIN_OFFSET),
+                    excluded_b: read_u64(data, NEED_TAKE_PNL_PC_OFFSET),
+                })
+            }
+        }
+    }
+}
+
+/// Amount of a token with its decimals
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenAmount {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Offset of the amount in an SPL token account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+/// Offsets of the supply and decimals in an SPL token mint
+const MINT_SUPPLY_OFFSET: usize = 36;
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+fn token_account_amount(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    if data.len() < TOKEN_ACCOUNT_AMOUNT_OFFSET + 8 {
+        msg!("Account {} is not a token account", account.key);
+        return err!(ScopeError::UnableToDeserializeAccount);
+    }
+    Ok(read_u64(&data, TOKEN_ACCOUNT_AMOUNT_OFFSET))
+}
+
+pub(super) fn mint_supply_and_decimals(account: &AccountInfo) -> Result<TokenAmount> {
+    let data = account.try_borrow_data()?;
+    if data.len() <= MINT_DECIMALS_OFFSET {
+        msg!("Account {} is not a mint", account.key);
+        return err!(ScopeError::UnableToDeserializeAccount);
+    }
+    Ok(TokenAmount {
+        amount: read_u64(&data, MINT_SUPPLY_OFFSET),
+        decimals: data[MINT_DECIMALS_OFFSET],
+    })
+}
+
+/// Gives the price of 1 LP token, in the quotation of the prices of the pool tokens
+///
+/// The price is as old as the oldest of the two token prices.
+pub fn get_price<'a, 'b>(
+    pool_account: &AccountInfo,
+    config: &ConstantProductLpConfig,
+    oracle_prices: &impl FeedPrices,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    // Consume all the extra accounts before any failure
+    let mut next_account = || {
+        extra_accounts
+            .next()
+            .ok_or(ScopeError::AccountsAndTokenMismatch)
+    };
+    let vault_a = next_account()?;
+    let vault_b = next_account()?;
+    let mint_a = next_account()?;
+    let mint_b = next_account()?;
+    let lp_mint = next_account()?;
+
+    let pool = PoolState::from_data(config.pool()?, &pool_account.try_borrow_data()?)?;
+
+    let account_check = |account: &AccountInfo, expected, name| {
+        if *account.key != expected {
+            msg!(
+                "LP received account {} for {} is not the one expected ({})",
+                account.key,
+                name,
+                expected
+            );
+            err!(ScopeError::UnexpectedAccount)
+        } else {
+            Ok(())
+        }
+    };
+    account_check(vault_a, pool.accounts.vault_a, "vault_a")?;
+    account_check(vault_b, pool.accounts.vault_b, "vault_b")?;
+    account_check(mint_a, pool.accounts.mint_a, "mint_a")?;
+    account_check(mint_b, pool.accounts.mint_b, "mint_b")?;
+    account_check(lp_mint, pool.accounts.lp_mint, "lp_mint")?;
+
+    let reserve_a = TokenAmount {
+        amount: token_account_amount(vault_a)?
+            .checked_sub(pool.excluded_a)
+            .ok_or(ScopeError::MathOverflow)?,
+        decimals: mint_supply_and_decimals(mint_a)?.decimals,
+    };
+    let reserve_b = TokenAmount {
+        amount: token_account_amount(vault_b)?
+            .checked_sub(pool.excluded_b)
+            .ok_or(ScopeError::MathOverflow)?,
+        decimals: mint_supply_and_decimals(mint_b)?.decimals,
+    };
+    let lp_supply = mint_supply_and_decimals(lp_mint)?;
+
+    let price_a = get_source_price(oracle_prices, config.price_a)?;
+    let price_b = get_source_price(oracle_prices, config.price_b)?;
+
+    Ok(DatedPrice {
+        price: fair_lp_price(
+            &reserve_a,
+            &reserve_b,
+            &price_a.price,
+            &price_b.price,
+            &lp_supply,
+        )?,
+        last_updated_slot: price_a.last_updated_slot.min(price_b.last_updated_slot),
+        unix_timestamp: price_a.unix_timestamp.min(price_b.unix_timestamp),
+        ..Default::default()
+    })
+}
+
+/// Check that the account is an initialized constant product pool of the configured kind
+pub fn validate_pool_info(
+    pool_account: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_SIZE],
+) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    let pool = ConstantProductLpConfig::from_generic_data(generic_data).pool()?;
+    check_account_owner(pool_account, pool.program_ids())?;
+    PoolState::from_data(pool, &pool_account.try_borrow_data()?)?;
+    Ok(())
+}
+
+/// Square root of the value of a reserve (`amount * price`), with its exponent
+///
+/// The value is scaled up as much as possible before taking the root to keep its precision,
+/// the root always fits in 64 bits.
+fn reserve_value_sqrt(reserve: &TokenAmount, price: &Price) -> ScopeResult<(U128, i64)> {
+    let ten = U128::from(10u64);
+    let mut value = U128::from(reserve.amount) * U128::from(price.value);
+    let mut exp = i64::from(reserve.decimals)
+        + i64::try_from(price.exp).map_err(|_| ScopeError::IntegerOverflow)?;
+    if value.is_zero() {
+        msg!("Constant product pool has an empty reserve");
+        return Err(ScopeError::PriceNotValid);
+    }
+    // The exponent must be even to take the root
+    if exp.rem_euclid(2) != 0 {
+        match value.checked_mul(ten) {
+            Some(scaled) => {
+                value = scaled;
+                exp += 1;
+            }
+            None => {
+                value /= ten;
+                exp -= 1;
+            }
+        }
+    }
+    while let Some(scaled) = value.checked_mul(ten * ten) {
+        value = scaled;
+        exp += 2;
+    }
+    Ok((value.integer_sqrt(), exp / 2))
+}
+
+/// Fair price of 1 LP token: `2 * sqrt(reserve_a * price_a * reserve_b * price_b) / lp_supply`
+pub fn fair_lp_price(
+    reserve_a: &TokenAmount,
+    reserve_b: &TokenAmount,
+    price_a: &Price,
+    price_b: &Price,
+    lp_supply: &TokenAmount,
+) -> ScopeResult<Price> {
+    if lp_supply.amount == 0 {
+        msg!("LP token has no supply");
+        return Err(ScopeError::PriceNotValid);
+    }
+    let (root_a, exp_a) = reserve_value_sqrt(reserve_a, price_a)?;
+    let (root_b, exp_b) = reserve_value_sqrt(reserve_b, price_b)?;
+    // Both roots fit in 64 bits so the product cannot overflow
+    let sqrt_k_value = root_a * root_b;
+    let per_lp = sqrt_k_value / U128::from(lp_supply.amount);
+    let exp = exp_a + exp_b - i64::from(lp_supply.decimals);
+    let (value, exp) = match per_lp.checked_mul(U128::from(2u64)) {
+        Some(value) => (value, exp),
+        None => (per_lp / U128::from(5u64), exp - 1),
+    };
+    if exp < 0 {
+        let factor = U128::from(10u64)
+            .checked_pow(U128::from(exp.unsigned_abs()))
+            .ok_or(ScopeError::IntegerOverflow)?;
+        let value = value
+            .checked_mul(factor)
+            .ok_or(ScopeError::IntegerOverflow)?;
+        price_from_u128(value, 0)
+    } else {
+        price_from_u128(value, exp.unsigned_abs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::math::price_value_with_exp, MAX_ENTRIES};
+
+    const USDC: u8 = 6;
+
+    fn amount(amount: u64, decimals: u8) -> TokenAmount {
+        TokenAmount {
+            amount: amount * 10u64.pow(decimals.into()),
+            decimals,
+        }
+    }
+
+    fn usd(value: u64) -> Price {
+        Price {
+            value: value * 100_000_000,
+            exp: 8,
+        }
+    }
+
+    fn assert_price_close(price: &Price, expected: u64, exp: u64) {
+        let value = price_value_with_exp(price, exp).unwrap().as_u64();
+        assert!(
+            value.abs_diff(expected) <= 1,
+            "price {price:?} is not close to {expected}e-{exp}"
+        );
+    }
+
+    #[test]
+    fn balanced_pool() {
+        // 1000 A at $2 and 2000 B at $1, $4000 for 100 LP tokens
+        let price = fair_lp_price(
+            &amount(1_000, 9),
+            &amount(2_000, USDC),
+            &usd(2),
+            &usd(1),
+            &amount(100, 6),
+        )
+        .unwrap();
+        assert_price_close(&price, 40_000_000_000, 9);
+    }
+
+    #[test]
+    fn swaps_do_not_move_the_price() {
+        // Same `k` as the balanced pool but the reserves do not match the prices
+        let price = fair_lp_price(
+            &amount(4_000, 9),
+            &amount(500, USDC),
+            &usd(2),
+            &usd(1),
+            &amount(100, 6),
+        )
+        .unwrap();
+        assert_price_close(&price, 40_000_000_000, 9);
+    }
+
+    #[test]
+    fn large_pool() {
+        // 10^12 whole tokens on each side at $10^6 with a 9 decimals LP token
+        let price = fair_lp_price(
+            &amount(1_000_000_000_000, 6),
+            &amount(1_000_000_000_000, 6),
+            &usd(1_000_000),
+            &usd(1_000_000),
+            &amount(1, 9),
+        )
+        .unwrap();
+        assert_price_close(&price, 2_000_000_000_000_000_000, 0);
+    }
+
+    #[test]
+    fn empty_pool() {
+        let empty = TokenAmount {
+            amount: 0,
+            decimals: 6,
+        };
+        assert!(fair_lp_price(&empty, &amount(1, 6), &usd(1), &usd(1), &amount(1, 6)).is_err());
+        assert!(fair_lp_price(&amount(1, 6), &amount(1, 6), &usd(1), &usd(1), &empty).is_err());
+    }
+
+    #[test]
+    fn config_validation() {
+        let config = ConstantProductLpConfig {
+            pool: ConstantProductPool::RaydiumAmmV4.into(),
+            price_a: 1,
+            price_b: 2,
+        };
+        assert!(validate_config(0, MAX_ENTRIES, &config).is_ok());
+        assert!(validate_config(1, MAX_ENTRIES, &config).is_err());
+        assert!(validate_config(
+            0,
+            MAX_ENTRIES,
+            &ConstantProductLpConfig { pool: 2, ..config }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn orca_pool_layout() {
+        let mut data = vec![0; orca::SWAP_SIZE];
+        data[0] = orca::SWAP_V1_VERSION;
+        data[orca::IS_INITIALIZED_OFFSET] = 1;
+        let lp_mint = Pubkey::new_unique();
+        data[orca::LP_MINT_OFFSET..orca::LP_MINT_OFFSET + 32].copy_from_slice(lp_mint.as_ref());
+        let pool = PoolState::from_data(ConstantProductPool::OrcaTokenSwap, &data).unwrap();
+        assert_eq!(pool.accounts.lp_mint, lp_mint);
+
+        // Stable curve
+        data[orca::CURVE_TYPE_OFFSET] = 2;
+        assert!(PoolState::from_data(ConstantProductPool::OrcaTokenSwap, &data).is_err());
+    }
+}
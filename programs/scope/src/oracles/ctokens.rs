@@ -2,9 +2,14 @@ use anchor_lang::{
     prelude::*,
     solana_program::{clock, program_pack::Pack},
 };
+use solana_program::pubkey;
 
 use self::solend::Reserve;
-use crate::{DatedPrice, Price, Result, ScopeResult};
+use super::check_account_owner;
+use crate::{DatedPrice, Price, Result, ScopeError, ScopeResult};
+
+/// Solend lending program, owner of the reserves
+pub const SOLEND_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
 
 const DECIMALS: u32 = 15u32;
 
@@ -46,6 +51,40 @@ pub fn get_price(solend_reserve_account: &AccountInfo, clock: &Clock) -> Result<
     Ok(dated_price)
 }
 
+/// Check that the account is an initialized solend reserve with a usable exchange rate
+pub fn validate_reserve_info(solend_reserve_account: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(solend_reserve_account, &[SOLEND_PROGRAM_ID])?;
+
+    // Fails on uninitialized reserves
+    let reserve = Reserve::unpack(&solend_reserve_account.data.borrow()).map_err(|_| {
+        msg!(
+            "Account {} is not an initialized solend reserve",
+            solend_reserve_account.key
+        );
+        ScopeError::UnexpectedAccount
+    })?;
+    validate_reserve(&reserve)
+}
+
+fn validate_reserve(reserve: &Reserve) -> Result<()> {
+    if reserve.lending_market == Pubkey::default()
+        || reserve.liquidity.mint_pubkey == Pubkey::default()
+    {
+        msg!("Solend reserve is not attached to a lending market or liquidity mint");
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    match scaled_rate(reserve) {
+        Ok(value) if value > 0 => Ok(()),
+        _ => {
+            msg!("Solend reserve has an invalid collateral exchange rate");
+            err!(ScopeError::PriceNotValid)
+        }
+    }
+}
+
 fn scaled_rate(reserve: &Reserve) -> ScopeResult<u64> {
     const FACTOR: u64 = 10u64.pow(DECIMALS);
     let rate = reserve.collateral_exchange_rate()?;
@@ -120,6 +159,41 @@ mod test {
         // Expect ctoken price to be 2 tokens
         assert_eq!(scaled_rate(&reserve).unwrap(), 2 * 10u64.pow(DECIMALS));
     }
+
+    #[test]
+    pub fn reserve_validation() {
+        let reserve = Reserve {
+            version: 1,
+            lending_market: Pubkey::new_unique(),
+            liquidity: ReserveLiquidity {
+                mint_pubkey: Pubkey::new_unique(),
+                available_amount: 10u64.pow(5),
+                ..Default::default()
+            },
+            collateral: ReserveCollateral {
+                mint_total_supply: 10u64.pow(5),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(validate_reserve(&reserve).is_ok());
+
+        // Reserve without lending market
+        let mut invalid = reserve.clone();
+        invalid.lending_market = Pubkey::default();
+        assert!(validate_reserve(&invalid).is_err());
+
+        // Reserve without liquidity mint
+        let mut invalid = reserve.clone();
+        invalid.liquidity.mint_pubkey = Pubkey::default();
+        assert!(validate_reserve(&invalid).is_err());
+
+        // Collateral worth nothing
+        let mut invalid = reserve;
+        invalid.liquidity.available_amount = 1;
+        invalid.collateral.mint_total_supply = u64::MAX;
+        assert!(validate_reserve(&invalid).is_err());
+    }
 }
 
 pub mod solend {
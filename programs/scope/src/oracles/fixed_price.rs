@@ -0,0 +1,98 @@
+//! Constant price set by the admin, for pegged tokens or placeholders during token onboarding
+//!
+//! The price is stored in the entry generic data, see [`FixedPriceConfig`].
+use anchor_lang::prelude::*;
+
+use super::OracleGenericData;
+use crate::{DatedPrice, Price, ScopeError, ScopeResult};
+
+/// Max exponent of a fixed price, `10^exp` has to fit in a `u64` for the consumers of the price
+pub const MAX_EXP: u64 = 18;
+
+/// Configuration of a `FixedPrice` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct FixedPriceConfig {
+    pub value: u64,
+    pub exp: u64,
+}
+
+impl OracleGenericData for FixedPriceConfig {}
+
+impl From<Price> for FixedPriceConfig {
+    fn from(Price { value, exp }: Price) -> Self {
+        Self { value, exp }
+    }
+}
+
+impl From<FixedPriceConfig> for Price {
+    fn from(FixedPriceConfig { value, exp }: FixedPriceConfig) -> Self {
+        Self { value, exp }
+    }
+}
+
+pub fn validate_config(config: &FixedPriceConfig) -> ScopeResult<()> {
+    if config.value == 0 {
+        msg!("Fixed price cannot be zero");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    if config.exp > MAX_EXP {
+        msg!("Fixed price exponent {} is above {}", config.exp, MAX_EXP);
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
+/// The price is always fresh: it is stamped with the current slot
+pub fn get_price(config: &FixedPriceConfig, clock: &Clock) -> Result<DatedPrice> {
+    Ok(DatedPrice {
+        price: (*config).into(),
+        last_updated_slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp.try_into().unwrap_or(0),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_price_is_stamped_with_current_slot() {
+        let config = FixedPriceConfig {
+            value: 10_005,
+            exp: 4,
+        };
+        let clock = Clock {
+            slot: 42,
+            unix_timestamp: 1_000,
+            ..Default::default()
+        };
+        let price = get_price(&config, &clock).unwrap();
+        assert_eq!(
+            price.price,
+            Price {
+                value: 10_005,
+                exp: 4
+            }
+        );
+        assert_eq!(price.last_updated_slot, 42);
+        assert_eq!(price.unix_timestamp, 1_000);
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(validate_config(&FixedPriceConfig { value: 1, exp: 0 }).is_ok());
+        assert!(validate_config(&FixedPriceConfig {
+            value: 1,
+            exp: MAX_EXP
+        })
+        .is_ok());
+        assert!(validate_config(&FixedPriceConfig {
+            value: 1,
+            exp: MAX_EXP + 1
+        })
+        .is_err());
+        assert!(validate_config(&FixedPriceConfig::from_generic_data(&[0; 32])).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Toolings to retrieve the price of Kamino Lend collateral tokens (kTokens of a reserve) in their
+//! liquidity token
+//!
+//! The exchange rate is the total liquidity of the reserve (available and borrowed, minus the
+//! fees owed to the protocol and referrers) divided by the collateral mint supply.
+//! Borrowed amounts only include the interests accrued until the last refresh of the reserve.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use solana_program::pubkey;
+
+use super::lending::{self, CollateralReserve, DECIMALS};
+use crate::{Result, ScopeError};
+
+/// Kamino Lend program, owner of the reserves
+pub const KAMINO_LEND_PROGRAM_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+/// Anchor discriminator of the `Reserve` account
+pub const RESERVE_DISCRIMINATOR: [u8; 8] = [43, 242, 204, 202, 26, 247, 59, 127];
+
+// Offsets of the fields read in the `Reserve` account (including the discriminator)
+pub const LAST_UPDATE_SLOT_OFFSET: usize = 16;
+pub const AVAILABLE_AMOUNT_OFFSET: usize = 224;
+pub const BORROWED_AMOUNT_SF_OFFSET: usize = 232;
+pub const ACCUMULATED_PROTOCOL_FEES_SF_OFFSET: usize = 344;
+pub const ACCUMULATED_REFERRER_FEES_SF_OFFSET: usize = 360;
+pub const PENDING_REFERRER_FEES_SF_OFFSET: usize = 376;
+pub const COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET: usize = 2592;
+
+/// Fractional bits of the `_sf` (scaled fraction) amounts
+pub const SF_FRACTIONAL_BITS: u32 = 60;
+
+/// Fields of a Kamino Lend `Reserve` needed to compute its collateral exchange rate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reserve {
+    pub last_update_slot: u64,
+    pub available_amount: u64,
+    pub borrowed_amount_sf: u128,
+    pub accumulated_protocol_fees_sf: u128,
+    pub accumulated_referrer_fees_sf: u128,
+    pub pending_referrer_fees_sf: u128,
+    pub collateral_mint_total_supply: u64,
+}
+
+impl Reserve {
+    /// Total liquidity owned by the collateral holders, as a scaled fraction
+    pub fn total_liquidity_sf(&self) -> Result<u128> {
+        (u128::from(self.available_amount) << SF_FRACTIONAL_BITS)
+            .checked_add(self.borrowed_amount_sf)
+            .and_then(|total| total.checked_sub(self.accumulated_protocol_fees_sf))
+            .and_then(|total| total.checked_sub(self.accumulated_referrer_fees_sf))
+            .and_then(|total| total.checked_sub(self.pending_referrer_fees_sf))
+            .ok_or_else(|| error!(ScopeError::MathOverflow))
+    }
+}
+
+impl CollateralReserve for Reserve {
+    const PROGRAM_IDS: &'static [Pubkey] = &[KAMINO_LEND_PROGRAM_ID];
+
+    fn load(reserve_account_info: &AccountInfo) -> Result<Self> {
+        let data = lending::load_data(
+            reserve_account_info,
+            RESERVE_DISCRIMINATOR,
+            COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET + 8,
+        )?;
+        let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![data, offset, 8]);
+        let read_u128 = |offset: usize| u128::from_le_bytes(*array_ref![data, offset, 16]);
+
+        Ok(Reserve {
+            last_update_slot: read_u64(LAST_UPDATE_SLOT_OFFSET),
+            available_amount: read_u64(AVAILABLE_AMOUNT_OFFSET),
+            borrowed_amount_sf: read_u128(BORROWED_AMOUNT_SF_OFFSET),
+            accumulated_protocol_fees_sf: read_u128(ACCUMULATED_PROTOCOL_FEES_SF_OFFSET),
+            accumulated_referrer_fees_sf: read_u128(ACCUMULATED_REFERRER_FEES_SF_OFFSET),
+            pending_referrer_fees_sf: read_u128(PENDING_REFERRER_FEES_SF_OFFSET),
+            collateral_mint_total_supply: read_u64(COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET),
+        })
+    }
+
+    fn scaled_rate(&self) -> Result<u64> {
+        const FACTOR: u128 = 10u128.pow(DECIMALS);
+        let total_liquidity_sf = self.total_liquidity_sf()?;
+        // Kamino Lend initial collateral rate is 1
+        if self.collateral_mint_total_supply == 0 || total_liquidity_sf == 0 {
+            return Ok(FACTOR as u64);
+        }
+        // total * FACTOR / supply without overflowing on large reserves
+        let supply = u128::from(self.collateral_mint_total_supply);
+        let value = (total_liquidity_sf / supply)
+            .checked_mul(FACTOR)
+            .and_then(|value| value.checked_add(total_liquidity_sf % supply * FACTOR / supply))
+            .ok_or_else(|| error!(ScopeError::MathOverflow))?
+            >> SF_FRACTIONAL_BITS;
+        u64::try_from(value).map_err(|_| error!(ScopeError::IntegerOverflow))
+    }
+
+    fn last_update(&self, clock: &Clock) -> (u64, u64) {
+        (
+            self.last_update_slot,
+            lending::timestamp_at_slot(clock, self.last_update_slot),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1 << SF_FRACTIONAL_BITS;
+
+    #[test]
+    fn collateral_rate() {
+        let reserve = Reserve {
+            available_amount: 600,
+            borrowed_amount_sf: 650 * ONE + ONE / 2,
+            accumulated_protocol_fees_sf: 20 * ONE,
+            accumulated_referrer_fees_sf: 5 * ONE,
+            pending_referrer_fees_sf: ONE / 2,
+            collateral_mint_total_supply: 1_000,
+            ..Default::default()
+        };
+        // (600 + 650.5 - 20 - 5 - 0.5) / 1000
+        assert_eq!(
+            reserve.scaled_rate().unwrap(),
+            1_225 * 10u64.pow(DECIMALS - 3)
+        );
+    }
+
+    #[test]
+    fn empty_reserve_rate() {
+        let reserve = Reserve::default();
+        assert_eq!(reserve.scaled_rate().unwrap(), 10u64.pow(DECIMALS));
+    }
+
+    #[test]
+    fn fees_above_liquidity() {
+        let reserve = Reserve {
+            available_amount: 1,
+            accumulated_protocol_fees_sf: 2 * ONE,
+            collateral_mint_total_supply: 1,
+            ..Default::default()
+        };
+        assert!(reserve.scaled_rate().is_err());
+    }
+}
@@ -3,11 +3,11 @@ use std::ops::Deref;
 use anchor_lang::{prelude::*, Result};
 use kamino::{
     clmm::{orca_clmm::OrcaClmm, Clmm},
-    raydium_amm_v3::states::{PersonalPositionState as RaydiumPosition, PoolState as RaydiumPool},
+    raydium_amm_v3::states::PersonalPositionState as RaydiumPosition,
     raydium_clmm::RaydiumClmm,
     state::{CollateralInfos, GlobalConfig, WhirlpoolStrategy},
     utils::types::DEX,
-    whirlpool::state::{Position as OrcaPosition, Whirlpool as OrcaWhirlpool},
+    whirlpool::state::Position as OrcaPosition,
 };
 use yvaults as kamino;
 use yvaults::{
@@ -21,6 +21,7 @@ use yvaults::{
     },
 };
 
+use super::{check_account_owner, clmm::PoolAccount};
 use crate::{
     utils::{account_deserialize, zero_copy_deserialize},
     DatedPrice, Price, ScopeError,
@@ -163,9 +164,8 @@ fn get_clmm<'a, 'info>(
     strategy: &WhirlpoolStrategy,
 ) -> Result<Box<dyn Clmm + 'a>> {
     let dex = DEX::try_from(strategy.strategy_dex).unwrap();
-    let clmm: Box<dyn Clmm> = match dex {
-        DEX::Orca => {
-            let pool = account_deserialize::<OrcaWhirlpool>(pool)?;
+    let clmm: Box<dyn Clmm> = match PoolAccount::load(dex.into(), pool)? {
+        PoolAccount::Orca(pool) => {
             let position = if strategy.position != Pubkey::default() {
                 let position = account_deserialize::<OrcaPosition>(position)?;
                 Some(position)
@@ -179,8 +179,7 @@ fn get_clmm<'a, 'info>(
                 upper_tick_array: None,
             })
         }
-        DEX::Raydium => {
-            let pool = zero_copy_deserialize::<RaydiumPool>(pool)?;
+        PoolAccount::Raydium(pool) => {
             let position = if strategy.position != Pubkey::default() {
                 let position = account_deserialize::<RaydiumPosition>(position)?;
                 Some(position)
@@ -242,6 +241,16 @@ fn get_component_px_last_update(
     Ok((last_updated_slot, unix_timestamp))
 }
 
+/// Check that the account is a strategy of the kamino program
+pub fn validate_strategy_info(k_account: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(k_account, &[kamino::ID])?;
+    zero_copy_deserialize::<WhirlpoolStrategy>(k_account)?;
+    Ok(())
+}
+
 /// Returns the holdings of the strategy
 /// Use a sqrt price derived from price_a and price_b, not from the pool as it cannot be considered reliable
 /// Exclude rewards from the holdings calculation, as they are generally low value/mcap and can be manipulated
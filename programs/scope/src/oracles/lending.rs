@@ -0,0 +1,140 @@
+//! Toolings to retrieve the price of lending deposits (collateral tokens or shares) in their
+//! liquidity token.
+//!
+//! Lending protocols store a collateral-to-liquidity exchange rate, or the amounts it is computed
+//! from, in their reserve (or bank) accounts. Each supported protocol implements
+//! [`CollateralReserve`] by reading only the few fields needed at their offset in the account,
+//! the price is then computed and validated the same way for all of them.
+//!
+//! Solend reserves, which need a full interest accrual, are handled in [`super::ctokens`].
+
+use anchor_lang::{prelude::*, solana_program::clock::DEFAULT_MS_PER_SLOT};
+
+use super::check_account_owner;
+use crate::{DatedPrice, Price, Result, ScopeError};
+
+/// Decimals of the collateral-to-liquidity rates, same as [`super::ctokens`]
+pub const DECIMALS: u32 = 15u32;
+
+/// Reserve account of a lending protocol holding a collateral-to-liquidity exchange rate
+pub trait CollateralReserve: Sized {
+    /// Programs allowed to own the reserve account
+    const PROGRAM_IDS: &'static [Pubkey];
+
+    /// Read the needed fields of the reserve account, checking its discriminator and size
+    fn load(reserve_account_info: &AccountInfo) -> Result<Self>;
+
+    /// Amount of liquidity for 1 collateral unit with [`DECIMALS`] decimals
+    fn scaled_rate(&self) -> Result<u64>;
+
+    /// Slot and unix timestamp of the last update of the rate
+    fn last_update(&self, clock: &Clock) -> (u64, u64);
+}
+
+// Gives the price of 1 collateral unit in the liquidity token
+pub fn get_price<T: CollateralReserve>(
+    reserve_account_info: &AccountInfo,
+    clock: &Clock,
+) -> Result<DatedPrice> {
+    let reserve = T::load(reserve_account_info)?;
+    let value = reserve.scaled_rate()?;
+    if value == 0 {
+        msg!("Lending reserve has an invalid collateral exchange rate");
+        return err!(ScopeError::PriceNotValid);
+    }
+    let (last_updated_slot, unix_timestamp) = reserve.last_update(clock);
+
+    Ok(DatedPrice {
+        price: Price {
+            value,
+            exp: DECIMALS.into(),
+        },
+        last_updated_slot,
+        unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// Check that the account is a reserve of the lending program with a usable exchange rate
+pub fn validate_reserve_info<T: CollateralReserve>(
+    reserve_account_info: &AccountInfo,
+) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(reserve_account_info, T::PROGRAM_IDS)?;
+
+    let reserve = T::load(reserve_account_info)?;
+    match reserve.scaled_rate() {
+        Ok(value) if value > 0 => Ok(()),
+        _ => {
+            msg!("Lending reserve has an invalid collateral exchange rate");
+            err!(ScopeError::PriceNotValid)
+        }
+    }
+}
+
+/// Check the discriminator and size of a reserve account and return its data
+pub(super) fn load_data<'a>(
+    reserve_account_info: &'a AccountInfo,
+    discriminator: [u8; 8],
+    min_size: usize,
+) -> Result<std::cell::Ref<'a, &'a mut [u8]>> {
+    let data = reserve_account_info.try_borrow_data()?;
+    if data.get(..8) != Some(&discriminator[..]) {
+        msg!(
+            "Account {} is not a lending reserve of the expected type",
+            reserve_account_info.key
+        );
+        return err!(ScopeError::InvalidAccountDiscriminator);
+    }
+    if data.len() < min_size {
+        msg!(
+            "Lending reserve {} is too small: {} bytes",
+            reserve_account_info.key,
+            data.len()
+        );
+        return err!(ScopeError::UnableToDeserializeAccount);
+    }
+    Ok(data)
+}
+
+/// Estimate the unix timestamp of a past slot
+pub(super) fn timestamp_at_slot(clock: &Clock, slot: u64) -> u64 {
+    let elapsed_s = clock
+        .slot
+        .saturating_sub(slot)
+        .saturating_mul(DEFAULT_MS_PER_SLOT)
+        / 1000;
+    u64::try_from(clock.unix_timestamp)
+        .unwrap_or(0)
+        .saturating_sub(elapsed_s)
+}
+
+/// Estimate the slot of a past unix timestamp
+pub(super) fn slot_at_timestamp(clock: &Clock, unix_timestamp: u64) -> u64 {
+    let now = u64::try_from(clock.unix_timestamp).unwrap_or(0);
+    let elapsed_slots =
+        now.saturating_sub(unix_timestamp).saturating_mul(1000) / DEFAULT_MS_PER_SLOT;
+    clock.slot.saturating_sub(elapsed_slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_timestamp_estimations() {
+        let clock = Clock {
+            slot: 10_000,
+            unix_timestamp: 1_000_000,
+            ..Default::default()
+        };
+        // 400ms slots
+        assert_eq!(timestamp_at_slot(&clock, 9_000), 1_000_000 - 400);
+        assert_eq!(slot_at_timestamp(&clock, 1_000_000 - 400), 9_000);
+        // Updates in the future are considered current
+        assert_eq!(timestamp_at_slot(&clock, 10_001), 1_000_000);
+        assert_eq!(slot_at_timestamp(&clock, 1_000_001), 10_000);
+    }
+}
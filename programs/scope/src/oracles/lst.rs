@@ -0,0 +1,69 @@
+//! Toolings to retrieve the price of liquid staking tokens (LST) whose exchange rate is stored in
+//! the state account of their own staking program (as opposed to SPL stake pools, see
+//! [`super::spl_stake`]).
+//!
+//! Each supported program implements [`LstState`] for a mirror of its state account, the price
+//! is then computed and validated the same way for all of them.
+
+use anchor_lang::prelude::*;
+
+use super::check_account_owner;
+use crate::{DatedPrice, Price, Result, ScopeError};
+
+/// Decimals of the SOL per LST rates, same as [`super::spl_stake`]
+pub const DECIMALS: u32 = 15u32;
+
+/// State account of a liquid staking program holding the SOL per LST exchange rate
+pub trait LstState: Sized {
+    /// Programs allowed to own the state account
+    const PROGRAM_IDS: &'static [Pubkey];
+
+    /// Deserialize the state account, checking its discriminator
+    fn load(state_account_info: &AccountInfo) -> Result<Self>;
+
+    /// Amount of SOL for 1 LST with [`DECIMALS`] decimals
+    fn scaled_rate(&self) -> Result<u64>;
+}
+
+// Gives the price of 1 LST in SOL
+pub fn get_price<T: LstState>(
+    state_account_info: &AccountInfo,
+    current_clock: &Clock,
+) -> Result<DatedPrice> {
+    let state = T::load(state_account_info)?;
+    let value = state.scaled_rate()?;
+    if value == 0 {
+        msg!("LST state account has an invalid exchange rate");
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    let price = Price {
+        value,
+        exp: DECIMALS.into(),
+    };
+    let dated_price = DatedPrice {
+        price,
+        last_updated_slot: current_clock.slot,
+        unix_timestamp: u64::try_from(current_clock.unix_timestamp).unwrap(),
+        ..Default::default()
+    };
+
+    Ok(dated_price)
+}
+
+/// Check that the account is a state account of the LST program with a usable exchange rate
+pub fn validate_state_info<T: LstState>(state_account_info: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(state_account_info, T::PROGRAM_IDS)?;
+
+    let state = T::load(state_account_info)?;
+    match state.scaled_rate() {
+        Ok(value) if value > 0 => Ok(()),
+        _ => {
+            msg!("LST state account has an invalid exchange rate");
+            err!(ScopeError::PriceNotValid)
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Toolings to retrieve the price of Marginfi bank deposits (asset shares) in their liquidity token
+//!
+//! Each bank stores the value of one asset share in its token as an `I80F48` fixed point number,
+//! it includes the interests accrued until the last update of the bank.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use solana_program::pubkey;
+
+use super::lending::{self, CollateralReserve, DECIMALS};
+use crate::{Result, ScopeError};
+
+/// Marginfi v2 program, owner of the banks
+pub const MARGINFI_PROGRAM_ID: Pubkey = pubkey!("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FMnHmfX6HY");
+
+/// Anchor discriminator of the `Bank` account
+pub const BANK_DISCRIMINATOR: [u8; 8] = [142, 49, 166, 242, 50, 66, 97, 188];
+
+// Offsets of the fields read in the `Bank` account (including the discriminator)
+pub const ASSET_SHARE_VALUE_OFFSET: usize = 80;
+pub const LAST_UPDATE_OFFSET: usize = 288;
+
+/// Fractional bits of the `I80F48` values
+pub const I80F48_FRACTIONAL_BITS: u32 = 48;
+
+/// Fields of a Marginfi `Bank` needed to compute its asset share value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bank {
+    /// Raw `I80F48` value of one asset share
+    pub asset_share_value: i128,
+    pub last_update: i64,
+}
+
+impl CollateralReserve for Bank {
+    const PROGRAM_IDS: &'static [Pubkey] = &[MARGINFI_PROGRAM_ID];
+
+    fn load(bank_account_info: &AccountInfo) -> Result<Self> {
+        let data = lending::load_data(
+            bank_account_info,
+            BANK_DISCRIMINATOR,
+            LAST_UPDATE_OFFSET + 8,
+        )?;
+
+        Ok(Bank {
+            asset_share_value: i128::from_le_bytes(*array_ref![data, ASSET_SHARE_VALUE_OFFSET, 16]),
+            last_update: i64::from_le_bytes(*array_ref![data, LAST_UPDATE_OFFSET, 8]),
+        })
+    }
+
+    fn scaled_rate(&self) -> Result<u64> {
+        const FACTOR: u128 = 10u128.pow(DECIMALS);
+        let asset_share_value = u128::try_from(self.asset_share_value).map_err(|_| {
+            msg!("Marginfi bank has a negative asset share value");
+            error!(ScopeError::PriceNotValid)
+        })?;
+        let value = asset_share_value
+            .checked_mul(FACTOR)
+            .ok_or_else(|| error!(ScopeError::MathOverflow))?
+            >> I80F48_FRACTIONAL_BITS;
+        u64::try_from(value).map_err(|_| error!(ScopeError::IntegerOverflow))
+    }
+
+    fn last_update(&self, clock: &Clock) -> (u64, u64) {
+        let unix_timestamp = u64::try_from(self.last_update).unwrap_or(0);
+        (
+            lending::slot_at_timestamp(clock, unix_timestamp),
+            unix_timestamp,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: i128 = 1 << I80F48_FRACTIONAL_BITS;
+
+    #[test]
+    fn asset_share_rate() {
+        let bank = Bank {
+            // 1.0625
+            asset_share_value: ONE + ONE / 16,
+            ..Default::default()
+        };
+        assert_eq!(
+            bank.scaled_rate().unwrap(),
+            10_625 * 10u64.pow(DECIMALS - 4)
+        );
+    }
+
+    #[test]
+    fn negative_asset_share_value() {
+        let bank = Bank {
+            asset_share_value: -ONE,
+            ..Default::default()
+        };
+        assert!(bank.scaled_rate().is_err());
+    }
+}
@@ -1,17 +1,127 @@
+//! Oracle types of the entries of a feed and computation of their price
+//!
+//! Some oracle types derive their price from other entries of the same feed (`ScopeMedian`,
+//! `ScopeTwap`, `ScopeChain`, `ScopeReciprocal`, `ScopeRatio`...) or hold it themselves
+//! (`FixedPrice`). They do not read any external account: their mapping points to the feed's own
+//! `OraclePrices` account and their configuration is stored in the entry generic data. Their
+//! sources are checked with [`validate_source`] and read with [`get_source_price`].
+//!
+//! Any entry of the feed can be a source, including the entries added by resizing the feed, see
+//! [`crate::extension`].
+pub mod clmm;
+pub mod constant_product_lp;
 pub mod ctokens;
+pub mod fixed_price;
+pub mod kamino_lend;
 #[cfg(feature = "yvaults")]
 pub mod ktokens;
+pub mod lending;
+pub mod lst;
+pub mod marginfi;
+pub mod msol_stake;
 pub mod pyth;
 pub mod pyth_ema;
+pub mod pyth_pull;
+pub mod scope_chain;
+pub mod scope_median;
+pub mod scope_ratio;
+pub mod scope_twap;
 pub mod spl_stake;
+pub mod switchboard_on_demand;
 pub mod switchboard_v1;
 pub mod switchboard_v2;
 
-use anchor_lang::prelude::{err, AccountInfo, Clock, Context, Result};
+use anchor_lang::prelude::{err, msg, AccountInfo, AccountLoader, Clock, Context, Pubkey, Result};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 
-use crate::{DatedPrice, ScopeError};
+use crate::{
+    extension::{self, FeedPrices, MappingEntry},
+    DatedPrice, OraclePrices, OracleTwaps, ScopeError, ScopeResult, GENERIC_DATA_SIZE,
+};
+
+/// 100% in bps
+pub const FULL_BPS: u16 = 10_000;
+
+/// Max confidence interval relative to the price of entries without a configured one (2%)
+pub const DEFAULT_CONFIDENCE_BPS: u16 = 200;
+
+/// Get the max confidence interval of an entry relative to its price, in bps
+pub fn entry_confidence_bps(entry: &MappingEntry) -> u16 {
+    match entry.confidence_bps {
+        0 => DEFAULT_CONFIDENCE_BPS,
+        confidence_bps => confidence_bps,
+    }
+}
+
+/// Get the `FULL_BPS / confidence_bps` factor as a reduced `(numerator, denominator)` fraction
+///
+/// A price is valid if `confidence * numerator < price * denominator`.
+/// The fraction is reduced so the default 2% gives the historical `confidence * 50 < price` check.
+pub fn confidence_factor(confidence_bps: u16) -> (u64, u64) {
+    let confidence_bps = confidence_bps.max(1);
+    let divisor = num::integer::gcd(FULL_BPS, confidence_bps);
+    (
+        (FULL_BPS / divisor).into(),
+        (confidence_bps / divisor).into(),
+    )
+}
+
+/// Check that an oracle account is owned by one of the programs expected for its oracle type
+///
+/// Skipped on localnet where oracle accounts are created by the mock oracles program.
+pub fn check_account_owner(account: &AccountInfo, expected_owners: &[Pubkey]) -> Result<()> {
+    if !cfg!(feature = "localnet") && !expected_owners.contains(account.owner) {
+        msg!(
+            "Oracle account {} is owned by {}, expected one of {:?}",
+            account.key,
+            account.owner,
+            expected_owners
+        );
+        return err!(ScopeError::WrongAccountOwner);
+    }
+    Ok(())
+}
+
+/// Check that `source` is a valid entry to compute the price of `entry_id` from, in a feed of
+/// `num_entries` entries
+pub fn validate_source(entry_id: usize, source: u16, num_entries: usize) -> ScopeResult<()> {
+    let source_id = usize::from(source);
+    if source_id >= num_entries {
+        msg!(
+            "Invalid source {}, the feed has {} entries",
+            source,
+            num_entries
+        );
+        return Err(ScopeError::InvalidGenericData);
+    }
+    if source_id == entry_id {
+        msg!(
+            "Invalid source {}, an entry cannot be its own source",
+            source
+        );
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
+/// Get the price of an entry of the feed used to compute the price of another entry
+///
+/// Frozen sources are rejected: their price is not maintained and must not leak into other entries.
+pub fn get_source_price(prices: &impl FeedPrices, source: u16) -> Result<DatedPrice> {
+    let price = prices
+        .entry(usize::from(source))
+        .ok_or(ScopeError::BadTokenNb)?;
+    if price.is_frozen() {
+        msg!("Source {} is frozen", source);
+        return err!(ScopeError::EntryFrozen);
+    }
+    if price.price.value == 0 {
+        msg!("Source {} has no price", source);
+        return err!(ScopeError::PriceNotValid);
+    }
+    Ok(price)
+}
 
 pub fn check_context<T>(ctx: &Context<T>) -> Result<()> {
     //make sure there are no extra accounts
@@ -41,6 +151,33 @@ pub enum OracleType {
     KToken = 6,
     /// Pyth Exponentially-Weighted Moving Average
     PythEMA = 7,
+    /// Median of other scope entries with outlier rejection
+    ScopeMedian = 8,
+    /// Time-weighted average price of another scope entry
+    ScopeTwap = 9,
+    /// Constant price set by the admin
+    FixedPrice = 10,
+    /// Product of a chain of other scope entries
+    ScopeChain = 11,
+    /// Reciprocal of another scope entry
+    ScopeReciprocal = 12,
+    /// Ratio of two other scope entries
+    ScopeRatio = 13,
+    /// Pyth price posted by the pyth receiver program (pull oracle)
+    PythPull = 14,
+    /// Switchboard on-demand pull feed
+    SwitchboardOnDemand = 15,
+    /// Marinade staked SOL (mSOL), rate read from the marinade state account
+    MsolStake = 16,
+    /// Kamino Lend collateral token, rate read from the reserve
+    KaminoLendCollateral = 17,
+    /// Marginfi bank deposit (asset share), rate read from the bank
+    MarginfiCollateral = 18,
+    /// LP token of a constant product AMM pool, valued with its fair reserves
+    ConstantProductLp = 19,
+    /// Spot price of a concentrated liquidity pool (Orca Whirlpool, Raydium CLMM), or the
+    /// time-weighted average of the observations of a Raydium pool
+    ClmmSpot = 20,
 }
 
 impl OracleType {
@@ -54,6 +191,19 @@ impl OracleType {
             OracleType::SplStake => 20000,
             OracleType::KToken => 100000,
             OracleType::PythEMA => 15000,
+            OracleType::ScopeMedian => 20000,
+            OracleType::ScopeTwap => 30000,
+            OracleType::FixedPrice => 10000,
+            OracleType::ScopeChain => 20000,
+            OracleType::ScopeReciprocal => 15000,
+            OracleType::ScopeRatio => 20000,
+            OracleType::PythPull => 20000,
+            OracleType::SwitchboardOnDemand => 30000,
+            OracleType::MsolStake => 20000,
+            OracleType::KaminoLendCollateral => 20000,
+            OracleType::MarginfiCollateral => 20000,
+            OracleType::ConstantProductLp => 40000,
+            OracleType::ClmmSpot => 50000,
             OracleType::DeprecatedPlaceholder => {
                 panic!("DeprecatedPlaceholder is not a valid oracle type")
             }
@@ -61,33 +211,128 @@ impl OracleType {
     }
 }
 
+/// Per entry configuration of an oracle type, stored in [`OracleMappings::generic_data`]
+///
+/// The configuration is stored unaligned at the beginning of the entry generic data.
+pub trait OracleGenericData: bytemuck::Pod {
+    fn from_generic_data(data: &[u8; GENERIC_DATA_SIZE]) -> Self {
+        bytemuck::pod_read_unaligned(&data[..std::mem::size_of::<Self>()])
+    }
+
+    fn to_generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        let mut data = [0; GENERIC_DATA_SIZE];
+        data[..std::mem::size_of::<Self>()].copy_from_slice(bytemuck::bytes_of(self));
+        data
+    }
+}
+
 /// Get the price for a given oracle type
 ///
 /// The `base_account` should have been checked against the oracle mapping
 /// If needed the `extra_accounts` will be extracted from the provided iterator and checked
 /// with the data contained in the `base_account`
+/// Oracles providing a confidence interval check it against the max confidence of the `entry`.
+/// Oracle types computing a price from other scope prices read them from all the entries of
+/// `oracle_prices` and their configuration from the generic data of the `entry`.
+/// TWAPs are computed from the observations stored in `oracle_twaps`, unavailable if the feed
+/// does not record TWAPs.
+/// Oracles whose stale updates can be re-posted compare them with the current price of `token`.
 pub fn get_price<'a, 'b>(
     price_type: OracleType,
     base_account: &AccountInfo,
-    _extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
     clock: &Clock,
+    oracle_prices: &AccountLoader<OraclePrices>,
+    oracle_twaps: Option<&AccountLoader<OracleTwaps>>,
+    token: usize,
+    entry: &MappingEntry,
 ) -> crate::Result<DatedPrice>
 where
     'a: 'b,
 {
     match price_type {
-        OracleType::Pyth => pyth::get_price(base_account),
+        OracleType::Pyth => pyth::get_price(base_account, entry_confidence_bps(entry)),
         OracleType::SwitchboardV1 => switchboard_v1::get_price(base_account),
-        OracleType::SwitchboardV2 => switchboard_v2::get_price(base_account),
+        OracleType::SwitchboardV2 => {
+            switchboard_v2::get_price(base_account, entry_confidence_bps(entry))
+        }
         OracleType::CToken => ctokens::get_price(base_account, clock),
-        OracleType::SplStake => spl_stake::get_price(base_account, clock),
+        OracleType::SplStake => spl_stake::get_price(
+            base_account,
+            &spl_stake::SplStakeConfig::from_generic_data(&entry.generic_data),
+            clock,
+        ),
         #[cfg(not(feature = "yvaults"))]
         OracleType::KToken => {
             panic!("yvaults feature is not enabled, KToken oracle type is not available")
         }
         #[cfg(feature = "yvaults")]
-        OracleType::KToken => ktokens::get_price(base_account, clock, _extra_accounts),
-        OracleType::PythEMA => pyth_ema::get_price(base_account),
+        OracleType::KToken => ktokens::get_price(base_account, clock, extra_accounts),
+        OracleType::PythEMA => pyth_ema::get_price(base_account, entry_confidence_bps(entry)),
+        OracleType::ScopeMedian => scope_median::get_price(
+            &extension::load(oracle_prices)?,
+            &scope_median::ScopeMedianConfig::from_generic_data(&entry.generic_data),
+            clock,
+        ),
+        OracleType::ScopeTwap => scope_twap::get_price(
+            &extension::load(oracle_twaps.ok_or(ScopeError::OracleTwapsNotSet)?)?,
+            &extension::load(oracle_prices)?,
+            &scope_twap::ScopeTwapConfig::from_generic_data(&entry.generic_data),
+            clock,
+        ),
+        OracleType::FixedPrice => fixed_price::get_price(
+            &fixed_price::FixedPriceConfig::from_generic_data(&entry.generic_data),
+            clock,
+        ),
+        OracleType::ScopeChain => scope_chain::get_price(
+            &extension::load(oracle_prices)?,
+            &scope_chain::ScopeChainConfig::from_generic_data(&entry.generic_data),
+        ),
+        OracleType::ScopeReciprocal => scope_ratio::get_reciprocal_price(
+            &extension::load(oracle_prices)?,
+            &scope_ratio::ScopeReciprocalConfig::from_generic_data(&entry.generic_data),
+        ),
+        OracleType::ScopeRatio => scope_ratio::get_ratio_price(
+            &extension::load(oracle_prices)?,
+            &scope_ratio::ScopeRatioConfig::from_generic_data(&entry.generic_data),
+        ),
+        OracleType::PythPull => pyth_pull::get_price(
+            base_account,
+            &pyth_pull::PythPullConfig::from_generic_data(&entry.generic_data),
+            entry_confidence_bps(entry),
+            clock,
+            &extension::load(oracle_prices)?
+                .get(token)
+                .ok_or(ScopeError::BadTokenNb)?,
+        ),
+        OracleType::SwitchboardOnDemand => switchboard_on_demand::get_price(
+            base_account,
+            clock,
+            entry_confidence_bps(entry),
+            entry.max_age_slots,
+        ),
+        OracleType::MsolStake => lst::get_price::<msol_stake::State>(base_account, clock),
+        OracleType::KaminoLendCollateral => {
+            lending::get_price::<kamino_lend::Reserve>(base_account, clock)
+        }
+        OracleType::MarginfiCollateral => lending::get_price::<marginfi::Bank>(base_account, clock),
+        OracleType::ConstantProductLp => constant_product_lp::get_price(
+            base_account,
+            &constant_product_lp::ConstantProductLpConfig::from_generic_data(&entry.generic_data),
+            &extension::load(oracle_prices)?,
+            extra_accounts,
+        ),
+        #[cfg(not(feature = "yvaults"))]
+        OracleType::ClmmSpot => {
+            panic!("yvaults feature is not enabled, ClmmSpot oracle type is not available")
+        }
+        #[cfg(feature = "yvaults")]
+        OracleType::ClmmSpot => clmm::get_price(
+            base_account,
+            &clmm::ClmmConfig::from_generic_data(&entry.generic_data),
+            clock,
+            extra_accounts,
+        ),
         OracleType::DeprecatedPlaceholder => {
             panic!("DeprecatedPlaceholder is not a valid oracle type")
         }
@@ -97,21 +342,136 @@ where
 /// Validate the given account as being an appropriate price account for the
 /// given oracle type.
 ///
+/// Oracle types computing their price from other scope prices, or from their configuration
+/// only, must be mapped to the feed's own `oracle_prices` account.
+///
+/// Oracle types reading an account able to hold several feeds check it against the feed
+/// registered in the `generic_data` of the entry.
+///
 /// This function shall be called before update of oracle mappings
 pub fn validate_oracle_account(
     price_type: OracleType,
     price_account: &AccountInfo,
+    oracle_prices_pk: &Pubkey,
+    generic_data: &[u8; GENERIC_DATA_SIZE],
 ) -> crate::Result<()> {
     match price_type {
         OracleType::Pyth => pyth::validate_pyth_price_info(price_account),
-        OracleType::SwitchboardV1 => Ok(()), // TODO at least check account ownership?
-        OracleType::SwitchboardV2 => Ok(()), // TODO at least check account ownership?
-        OracleType::CToken => Ok(()),        // TODO how shall we validate ctoken account?
-        OracleType::SplStake => Ok(()),
-        OracleType::KToken => Ok(()),
+        OracleType::SwitchboardV1 => switchboard_v1::validate_aggregator_info(price_account),
+        OracleType::SwitchboardV2 => switchboard_v2::validate_aggregator_info(price_account),
+        OracleType::CToken => ctokens::validate_reserve_info(price_account),
+        OracleType::SplStake => spl_stake::validate_stake_pool_info(price_account),
+        #[cfg(not(feature = "yvaults"))]
+        OracleType::KToken => {
+            msg!("yvaults feature is not enabled, KToken oracle type is not available");
+            err!(ScopeError::BadTokenType)
+        }
+        #[cfg(feature = "yvaults")]
+        OracleType::KToken => ktokens::validate_strategy_info(price_account),
         OracleType::PythEMA => pyth::validate_pyth_price_info(price_account),
+        OracleType::ScopeMedian
+        | OracleType::ScopeTwap
+        | OracleType::FixedPrice
+        | OracleType::ScopeChain
+        | OracleType::ScopeReciprocal
+        | OracleType::ScopeRatio => validate_scope_prices_account(price_account, oracle_prices_pk),
+        OracleType::PythPull => {
+            pyth_pull::validate_price_update_account(price_account, generic_data)
+        }
+        OracleType::SwitchboardOnDemand => {
+            switchboard_on_demand::validate_pull_feed_info(price_account)
+        }
+        OracleType::MsolStake => lst::validate_state_info::<msol_stake::State>(price_account),
+        OracleType::KaminoLendCollateral => {
+            lending::validate_reserve_info::<kamino_lend::Reserve>(price_account)
+        }
+        OracleType::MarginfiCollateral => {
+            lending::validate_reserve_info::<marginfi::Bank>(price_account)
+        }
+        OracleType::ConstantProductLp => {
+            constant_product_lp::validate_pool_info(price_account, generic_data)
+        }
+        #[cfg(not(feature = "yvaults"))]
+        OracleType::ClmmSpot => {
+            msg!("yvaults feature is not enabled, ClmmSpot oracle type is not available");
+            err!(ScopeError::BadTokenType)
+        }
+        #[cfg(feature = "yvaults")]
+        OracleType::ClmmSpot => clmm::validate_pool_info(price_account, generic_data),
         OracleType::DeprecatedPlaceholder => {
             panic!("DeprecatedPlaceholder is not a valid oracle type")
         }
     }
 }
+
+/// Validate the generic data of an entry for the given oracle type, in a feed of `num_entries`
+/// entries.
+///
+/// This function shall be called before update of oracle mappings
+pub fn validate_generic_data(
+    price_type: OracleType,
+    entry_id: usize,
+    num_entries: usize,
+    generic_data: &[u8; GENERIC_DATA_SIZE],
+) -> ScopeResult<()> {
+    match price_type {
+        OracleType::ScopeMedian => scope_median::validate_config(
+            entry_id,
+            num_entries,
+            &scope_median::ScopeMedianConfig::from_generic_data(generic_data),
+        ),
+        OracleType::ScopeTwap => scope_twap::validate_config(
+            entry_id,
+            num_entries,
+            &scope_twap::ScopeTwapConfig::from_generic_data(generic_data),
+        ),
+        OracleType::FixedPrice => fixed_price::validate_config(
+            &fixed_price::FixedPriceConfig::from_generic_data(generic_data),
+        ),
+        OracleType::ScopeChain => scope_chain::validate_config(
+            entry_id,
+            num_entries,
+            &scope_chain::ScopeChainConfig::from_generic_data(generic_data),
+        ),
+        OracleType::ScopeReciprocal => scope_ratio::validate_reciprocal_config(
+            entry_id,
+            num_entries,
+            &scope_ratio::ScopeReciprocalConfig::from_generic_data(generic_data),
+        ),
+        OracleType::ScopeRatio => scope_ratio::validate_ratio_config(
+            entry_id,
+            num_entries,
+            &scope_ratio::ScopeRatioConfig::from_generic_data(generic_data),
+        ),
+        OracleType::PythPull => {
+            pyth_pull::validate_config(&pyth_pull::PythPullConfig::from_generic_data(generic_data))
+        }
+        OracleType::SplStake => {
+            spl_stake::validate_config(&spl_stake::SplStakeConfig::from_generic_data(generic_data))
+        }
+        OracleType::ConstantProductLp => constant_product_lp::validate_config(
+            entry_id,
+            num_entries,
+            &constant_product_lp::ConstantProductLpConfig::from_generic_data(generic_data),
+        ),
+        OracleType::ClmmSpot => {
+            clmm::validate_config(&clmm::ClmmConfig::from_generic_data(generic_data))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_scope_prices_account(
+    price_account: &AccountInfo,
+    oracle_prices_pk: &Pubkey,
+) -> crate::Result<()> {
+    if price_account.key != oracle_prices_pk {
+        msg!(
+            "Expected the feed oracle prices account {}, received {}",
+            oracle_prices_pk,
+            price_account.key
+        );
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//! Toolings to retrieve the price of mSOL from the Marinade state account
+//!
+//! Marinade stores the SOL per mSOL exchange rate in its `State` account as a fixed point
+//! number with a `2^32` denominator, it is updated by the marinade cranks every epoch.
+
+use anchor_lang::prelude::*;
+use solana_program::pubkey;
+
+pub use self::marinade::*;
+use super::lst::{LstState, DECIMALS};
+use crate::{Result, ScopeError};
+
+/// Marinade liquid staking program, owner of the `State` account
+pub const MARINADE_PROGRAM_ID: Pubkey = pubkey!("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD");
+
+/// Denominator of `State::msol_price`
+pub const MSOL_PRICE_DENOMINATOR: u64 = 1 << 32;
+
+impl LstState for State {
+    const PROGRAM_IDS: &'static [Pubkey] = &[MARINADE_PROGRAM_ID];
+
+    fn load(state_account_info: &AccountInfo) -> Result<Self> {
+        let data = state_account_info.try_borrow_data()?;
+        if data.get(..8) != Some(&State::discriminator()[..]) {
+            msg!(
+                "Account {} is not a marinade state account",
+                state_account_info.key
+            );
+            return err!(ScopeError::InvalidAccountDiscriminator);
+        }
+        // Only the beginning of the account is mirrored
+        AnchorDeserialize::deserialize(&mut &data[8..]).map_err(|_| {
+            msg!(
+                "Marinade state account {} deserialization failed",
+                state_account_info.key
+            );
+            error!(ScopeError::UnableToDeserializeAccount)
+        })
+    }
+
+    fn scaled_rate(&self) -> Result<u64> {
+        const FACTOR: u128 = 10u128.pow(DECIMALS);
+        let value = u128::from(self.msol_price) * FACTOR / u128::from(MSOL_PRICE_DENOMINATOR);
+        u64::try_from(value).map_err(|_| error!(ScopeError::IntegerOverflow))
+    }
+}
+
+mod marinade {
+    use super::*;
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Fee {
+        pub basis_points: u32,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct List {
+        pub account: Pubkey,
+        pub item_size: u32,
+        pub count: u32,
+        pub new_account: Pubkey,
+        pub copy_data: u32,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct StakeSystem {
+        pub stake_list: List,
+        pub delayed_unstake_cooling_down: u64,
+        pub stake_deposit_bump_seed: u8,
+        pub stake_withdraw_bump_seed: u8,
+        pub slots_for_stake_delta: u64,
+        pub last_stake_delta_epoch: u64,
+        pub min_stake: u64,
+        pub extra_stake_delta_runs: u32,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ValidatorSystem {
+        pub validator_list: List,
+        pub manager_authority: Pubkey,
+        pub total_validator_score: u32,
+        pub total_active_balance: u64,
+        pub auto_add_validator_enabled: u8,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct LiqPool {
+        pub lp_mint: Pubkey,
+        pub lp_mint_authority_bump_seed: u8,
+        pub sol_leg_bump_seed: u8,
+        pub msol_leg_authority_bump_seed: u8,
+        pub msol_leg: Pubkey,
+        pub lp_liquidity_target: u64,
+        pub lp_max_fee: Fee,
+        pub lp_min_fee: Fee,
+        pub treasury_cut: Fee,
+        pub lp_supply: u64,
+        pub lent_from_sol_leg: u64,
+        pub liquidity_sol_cap: u64,
+    }
+
+    /// Mirror of the beginning of the marinade `State` account, up to the mSOL price
+    ///
+    /// The marinade crate depends on a different anchor version than this program.
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct State {
+        pub msol_mint: Pubkey,
+        pub admin_authority: Pubkey,
+        pub operational_sol_account: Pubkey,
+        pub treasury_msol_account: Pubkey,
+        pub reserve_bump_seed: u8,
+        pub msol_mint_authority_bump_seed: u8,
+        pub rent_exempt_for_token_acc: u64,
+        pub reward_fee: Fee,
+        pub stake_system: StakeSystem,
+        pub validator_system: ValidatorSystem,
+        pub liq_pool: LiqPool,
+        pub available_reserve_balance: u64,
+        pub msol_supply: u64,
+        // SOL per mSOL, times `MSOL_PRICE_DENOMINATOR`
+        pub msol_price: u64,
+    }
+
+    impl State {
+        pub fn discriminator() -> [u8; 8] {
+            [216, 146, 107, 94, 104, 75, 182, 177]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_layout() {
+        let state = State {
+            msol_price: 0x1_2000_0000,
+            ..Default::default()
+        };
+        let data = state.try_to_vec().unwrap();
+        // The mSOL price is read at offset 512 (including the discriminator) by other integrations
+        assert_eq!(data.len(), 512);
+        assert_eq!(data[504..], 0x1_2000_0000_u64.to_le_bytes());
+    }
+
+    #[test]
+    fn msol_rate() {
+        let state = State {
+            // 1.125 SOL per mSOL
+            msol_price: MSOL_PRICE_DENOMINATOR + MSOL_PRICE_DENOMINATOR / 8,
+            ..Default::default()
+        };
+        assert_eq!(
+            state.scaled_rate().unwrap(),
+            1_125 * 10u64.pow(DECIMALS - 3)
+        );
+    }
+}
@@ -7,7 +7,8 @@
 //!    expected to be checked by the admin to ensure the product has the expected quality prior the mapping
 //!    update.
 //! 2. Upon usage the current price state is checked in [`validate_valid_price`]
-//! 3. The confidence interval is also checked in this same function with [`ORACLE_CONFIDENCE_FACTOR`]
+//! 3. The confidence interval is also checked in this same function against the entry max confidence
+//!    and stored along the price for consumers.
 
 use std::convert::{TryFrom, TryInto};
 
@@ -15,12 +16,9 @@ use anchor_lang::prelude::*;
 use pyth_client::PriceType;
 use pyth_sdk_solana::state as pyth_client;
 
-use crate::{DatedPrice, Price, Result, ScopeError};
+use crate::{oracles::confidence_factor, DatedPrice, Price, Result, ScopeError};
 
-/// validate price confidence - confidence/price ratio should be less than 2%
-const ORACLE_CONFIDENCE_FACTOR: u64 = 50; // 100% / 2%
-
-pub fn get_price(price_info: &AccountInfo) -> Result<DatedPrice> {
+pub fn get_price(price_info: &AccountInfo, confidence_bps: u16) -> Result<DatedPrice> {
     let data = price_info.try_borrow_data()?;
     let price_account = pyth_client::load_price_account(data.as_ref())
         .map_err(|_| error!(ScopeError::PriceNotValid))?;
@@ -38,7 +36,7 @@ pub fn get_price(price_info: &AccountInfo) -> Result<DatedPrice> {
         return err!(ScopeError::PriceNotValid);
     };
 
-    let price = validate_valid_price(&pyth_price, ORACLE_CONFIDENCE_FACTOR).map_err(|e| {
+    let price = validate_valid_price(&pyth_price, confidence_bps).map_err(|e| {
         msg!(
             "Confidence interval check failed on pyth account {}",
             price_info.key
@@ -53,14 +51,13 @@ pub fn get_price(price_info: &AccountInfo) -> Result<DatedPrice> {
         },
         last_updated_slot: price_account.valid_slot,
         unix_timestamp: u64::try_from(price_account.timestamp).unwrap(),
+        confidence: pyth_price.conf,
         ..Default::default()
     })
 }
 
-pub fn validate_valid_price(
-    pyth_price: &pyth_client::Price,
-    oracle_confidence_factor: u64,
-) -> Result<u64> {
+/// Validate the price state and that its confidence/price ratio is at most `confidence_bps`
+pub fn validate_valid_price(pyth_price: &pyth_client::Price, confidence_bps: u16) -> Result<u64> {
     if cfg!(feature = "skip_price_validation") {
         return Ok(u64::try_from(pyth_price.price).unwrap());
     }
@@ -69,9 +66,9 @@ pub fn validate_valid_price(
     if price == 0 {
         return err!(ScopeError::PriceNotValid);
     }
-    let conf: u64 = pyth_price.conf;
-    let conf_50x: u64 = conf.checked_mul(oracle_confidence_factor).unwrap();
-    if conf_50x > price {
+    let (factor_num, factor_den) = confidence_factor(confidence_bps);
+    let conf_x_factor = u128::from(pyth_price.conf) * u128::from(factor_num);
+    if conf_x_factor > u128::from(price) * u128::from(factor_den) {
         return err!(ScopeError::PriceNotValid);
     };
     Ok(price)
@@ -7,7 +7,8 @@
 //!    expected to be checked by the admin to ensure the product has the expected quality prior the mapping
 //!    update.
 //! 2. Upon usage the current ema price state is checked in [`validate_valid_price`]
-//! 3. The confidence interval is also checked in this same function with [`ORACLE_CONFIDENCE_FACTOR`]
+//! 3. The confidence interval is also checked in this same function against the entry max confidence
+//!    and stored along the price for consumers.
 
 use std::convert::{TryFrom, TryInto};
 
@@ -16,10 +17,7 @@ use pyth_sdk_solana::state as pyth_client;
 
 use crate::{DatedPrice, Price, Result, ScopeError};
 
-/// validate price confidence - confidence/price ratio should be less than 2%
-const ORACLE_CONFIDENCE_FACTOR: u64 = 50; // 100% / 2%
-
-pub fn get_price(price_info: &AccountInfo) -> Result<DatedPrice> {
+pub fn get_price(price_info: &AccountInfo, confidence_bps: u16) -> Result<DatedPrice> {
     let data = price_info.try_borrow_data()?;
     let price_account = pyth_client::load_price_account(data.as_ref()).map_err(|e| {
         msg!("Invalid pyth price account: {}", e);
@@ -39,12 +37,11 @@ pub fn get_price(price_info: &AccountInfo) -> Result<DatedPrice> {
         return err!(ScopeError::PriceNotValid);
     };
 
-    let ema_price =
-        crate::oracles::pyth::validate_valid_price(&pyth_ema_price, ORACLE_CONFIDENCE_FACTOR)
-            .map_err(|e| {
-                msg!("Invalid EMA price on pyth account {}", price_info.key);
-                e
-            })?;
+    let ema_price = crate::oracles::pyth::validate_valid_price(&pyth_ema_price, confidence_bps)
+        .map_err(|e| {
+            msg!("Invalid EMA price on pyth account {}", price_info.key);
+            e
+        })?;
 
     Ok(DatedPrice {
         price: Price {
@@ -53,6 +50,7 @@ pub fn get_price(price_info: &AccountInfo) -> Result<DatedPrice> {
         },
         last_updated_slot: price_account.valid_slot,
         unix_timestamp: u64::try_from(price_account.timestamp).unwrap(),
+        confidence: pyth_ema_price.conf,
         ..Default::default()
     })
 }
@@ -0,0 +1,262 @@
+//! Toolings to retrieve pyth prices posted by the pyth receiver program (pull oracle)
+//!
+//! Pull prices are stored in `PriceUpdateV2` accounts owned by the pyth receiver program.
+//! Any feed can be posted in such an account, so the pyth feed id expected for an entry is
+//! registered in the entry generic data (see [`PythPullConfig`]).
+//!
+//! 1. Upon registration in the oracle mapping the account owner, layout and feed id are checked
+//!    in [`validate_price_update_account`].
+//! 2. Upon usage the feed id and the verification level of the update are checked again, then the
+//!    price is validated in [`validate_valid_price`] with the same confidence interval rules as
+//!    legacy pyth prices.
+//!
+//! Anyone can post any verified update, including old ones, so the age of the price is derived
+//! from the publish time of the update and not from the slot it was posted at, and updates older
+//! than the current price of the entry are rejected.
+
+use anchor_lang::{prelude::*, Discriminator};
+use solana_program::pubkey;
+
+use super::{
+    check_account_owner, confidence_factor, lending::slot_at_timestamp, OracleGenericData,
+};
+use crate::{utils::account_deserialize, DatedPrice, Price, ScopeError, ScopeResult};
+
+/// Pyth receiver program, owner of the `PriceUpdateV2` accounts
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Configuration of a `PythPull` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct PythPullConfig {
+    /// Pyth feed id of the price posted in the mapped account
+    pub feed_id: [u8; 32],
+}
+
+impl OracleGenericData for PythPullConfig {}
+
+/// Verification of the wormhole signatures of a price update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    /// Only `num_signatures` guardian signatures were checked
+    Partial { num_signatures: u8 },
+    /// The update was verified with a quorum of guardian signatures
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// Mirror of the `PriceUpdateV2` account of the pyth receiver program
+///
+/// The pyth receiver sdk depends on a more recent anchor version than this program.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl Discriminator for PriceUpdateV2 {
+    fn discriminator() -> [u8; 8] {
+        [34, 241, 35, 99, 157, 126, 244, 205]
+    }
+}
+
+impl AccountDeserialize for PriceUpdateV2 {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        if buf.get(..8) != Some(&Self::discriminator()[..]) {
+            return err!(ScopeError::InvalidAccountDiscriminator);
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data: &[u8] = buf
+            .get(8..)
+            .ok_or_else(|| error!(ScopeError::UnableToDeserializeAccount))?;
+        AnchorDeserialize::deserialize(&mut data)
+            .map_err(|_| error!(ScopeError::UnableToDeserializeAccount))
+    }
+}
+
+pub fn validate_config(config: &PythPullConfig) -> ScopeResult<()> {
+    if config.feed_id == [0; 32] {
+        msg!("Pyth pull entries require a feed id");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
+fn load_price_update(price_info: &AccountInfo, config: &PythPullConfig) -> Result<PriceUpdateV2> {
+    check_account_owner(price_info, &[PYTH_RECEIVER_PROGRAM_ID])?;
+    let price_update: PriceUpdateV2 = account_deserialize(price_info)?;
+    if price_update.price_message.feed_id != config.feed_id {
+        msg!(
+            "Pyth price update account {} does not hold the expected feed",
+            price_info.key
+        );
+        return err!(ScopeError::PriceNotValid);
+    }
+    Ok(price_update)
+}
+
+pub fn get_price(
+    price_info: &AccountInfo,
+    config: &PythPullConfig,
+    confidence_bps: u16,
+    clock: &Clock,
+    current_price: &DatedPrice,
+) -> Result<DatedPrice> {
+    let price_update = load_price_update(price_info, config)?;
+
+    if price_update.verification_level != VerificationLevel::Full {
+        msg!(
+            "Pyth price update account {} is not fully verified",
+            price_info.key
+        );
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    let message = &price_update.price_message;
+    let publish_time = u64::try_from(message.publish_time).unwrap_or(0);
+    if publish_time < current_price.unix_timestamp {
+        msg!(
+            "Pyth price update account {} was published at {}, before the current price ({})",
+            price_info.key,
+            publish_time,
+            current_price.unix_timestamp
+        );
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    let price = validate_valid_price(message, confidence_bps).map_err(|e| {
+        msg!(
+            "Price validation failed on pyth price update account {}",
+            price_info.key
+        );
+        e
+    })?;
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: slot_at_timestamp(clock, publish_time),
+        unix_timestamp: publish_time,
+        confidence: message.conf,
+        ..Default::default()
+    })
+}
+
+/// Validate the price sign and exponent and that its confidence/price ratio is at most `confidence_bps`
+pub fn validate_valid_price(message: &PriceFeedMessage, confidence_bps: u16) -> Result<Price> {
+    let value = u64::try_from(message.price).map_err(|_| error!(ScopeError::PriceNotValid))?;
+    let exp = u64::try_from(-i64::from(message.exponent))
+        .map_err(|_| error!(ScopeError::PriceNotValid))?;
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(Price { value, exp });
+    }
+
+    if value == 0 {
+        return err!(ScopeError::PriceNotValid);
+    }
+    let (factor_num, factor_den) = confidence_factor(confidence_bps);
+    let conf_x_factor = u128::from(message.conf) * u128::from(factor_num);
+    if conf_x_factor > u128::from(value) * u128::from(factor_den) {
+        return err!(ScopeError::PriceNotValid);
+    };
+    Ok(Price { value, exp })
+}
+
+pub fn validate_price_update_account(
+    price_info: &AccountInfo,
+    generic_data: &[u8; crate::GENERIC_DATA_SIZE],
+) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    load_price_update(price_info, &PythPullConfig::from_generic_data(generic_data))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracles::DEFAULT_CONFIDENCE_BPS;
+
+    fn message(price: i64, conf: u64, exponent: i32) -> PriceFeedMessage {
+        PriceFeedMessage {
+            feed_id: [1; 32],
+            price,
+            conf,
+            exponent,
+            publish_time: 1_000,
+            prev_publish_time: 990,
+            ema_price: price,
+            ema_conf: conf,
+        }
+    }
+
+    #[test]
+    fn price_update_layout() {
+        let price_update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: message(2_000_000_000, 1_000_000, -8),
+            posted_slot: 42,
+        };
+        let mut data = PriceUpdateV2::discriminator().to_vec();
+        price_update.serialize(&mut data).unwrap();
+        // discriminator + write authority + verification level + message + posted slot
+        assert_eq!(data.len(), 8 + 32 + 1 + 84 + 8);
+        let decoded = PriceUpdateV2::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded, price_update);
+        data[0] = 0;
+        assert!(PriceUpdateV2::try_deserialize(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn valid_price() {
+        let price = validate_valid_price(
+            &message(2_000_000_000, 1_000_000, -8),
+            DEFAULT_CONFIDENCE_BPS,
+        )
+        .unwrap();
+        assert_eq!(
+            price,
+            Price {
+                value: 2_000_000_000,
+                exp: 8
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_prices() {
+        // Negative price
+        assert!(validate_valid_price(&message(-1, 0, -8), DEFAULT_CONFIDENCE_BPS).is_err());
+        // Positive exponent
+        assert!(validate_valid_price(&message(1, 0, 2), DEFAULT_CONFIDENCE_BPS).is_err());
+        // Zero price
+        assert!(validate_valid_price(&message(0, 0, -8), DEFAULT_CONFIDENCE_BPS).is_err());
+        // Confidence interval above 2%
+        assert!(validate_valid_price(&message(100, 3, -8), DEFAULT_CONFIDENCE_BPS).is_err());
+        // Unless a larger one is configured
+        assert!(validate_valid_price(&message(100, 3, -8), 500).is_ok());
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(validate_config(&PythPullConfig { feed_id: [1; 32] }).is_ok());
+        assert!(validate_config(&PythPullConfig::from_generic_data(&[0; 32])).is_err());
+    }
+}
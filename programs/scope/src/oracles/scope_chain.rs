@@ -0,0 +1,157 @@
+//! Price derived from a chain of other scope entries, see [`ScopeChainConfig`]
+//!
+//! The prices of the chain are multiplied in order, e.g. `mSOL/USD` is computed from
+//! `[mSOL/SOL, SOL/USD]`, see [`get_price_from_chain`] for details.
+use anchor_lang::prelude::*;
+
+use super::{get_source_price, validate_source, OracleGenericData};
+use crate::{
+    extension::FeedPrices,
+    utils::scope_chain::{get_price_from_chain, MAX_CHAIN_LENGTH, UNUSED_LINK},
+    DatedPrice, ScopeError, ScopeResult,
+};
+
+/// Configuration of a `ScopeChain` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ScopeChainConfig {
+    /// Index of the entries of the chain, unused trailing slots are set to `u16::MAX`
+    pub chain: [u16; MAX_CHAIN_LENGTH],
+}
+
+impl OracleGenericData for ScopeChainConfig {}
+
+impl ScopeChainConfig {
+    pub fn new(chain: &[u16]) -> ScopeResult<Self> {
+        if chain.len() > MAX_CHAIN_LENGTH {
+            return Err(ScopeError::InvalidGenericData);
+        }
+        let mut config = Self {
+            chain: [UNUSED_LINK; MAX_CHAIN_LENGTH],
+        };
+        config.chain[..chain.len()].copy_from_slice(chain);
+        Ok(config)
+    }
+
+    pub fn used_links(&self) -> impl Iterator<Item = u16> + '_ {
+        self.chain
+            .iter()
+            .copied()
+            .take_while(|&link| link != UNUSED_LINK)
+    }
+}
+
+pub fn validate_config(
+    entry_id: usize,
+    num_entries: usize,
+    config: &ScopeChainConfig,
+) -> ScopeResult<()> {
+    let nb_links = config.used_links().count();
+    if nb_links == 0 {
+        msg!("Scope chain cannot be empty");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    if config.chain[nb_links..]
+        .iter()
+        .any(|&link| link != UNUSED_LINK)
+    {
+        msg!("Unused links of the scope chain must be at the end");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    for link in config.used_links() {
+        validate_source(entry_id, link, num_entries)?;
+    }
+    Ok(())
+}
+
+pub fn get_price(prices: &impl FeedPrices, config: &ScopeChainConfig) -> Result<DatedPrice> {
+    for link in config.used_links() {
+        get_source_price(prices, link)?;
+    }
+    Ok(get_price_from_chain(prices, &config.chain).map_err(ScopeError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OraclePrices, Price, MAX_ENTRIES};
+
+    fn prices() -> Box<OraclePrices> {
+        let mut prices: Box<OraclePrices> = Box::new(bytemuck::Zeroable::zeroed());
+        // mSOL/SOL
+        prices.prices[1] = DatedPrice {
+            price: Price {
+                value: 1_100_000,
+                exp: 6,
+            },
+            last_updated_slot: 100,
+            unix_timestamp: 1_000,
+            ..Default::default()
+        };
+        // SOL/USD
+        prices.prices[2] = DatedPrice {
+            price: Price {
+                value: 2_000_000_000,
+                exp: 8,
+            },
+            last_updated_slot: 90,
+            unix_timestamp: 1_010,
+            ..Default::default()
+        };
+        prices
+    }
+
+    #[test]
+    fn chain_price() {
+        let config = ScopeChainConfig::new(&[1, 2]).unwrap();
+        let price = get_price(&*prices(), &config).unwrap();
+        assert_eq!(
+            price.price,
+            Price {
+                value: 2_200_000_000,
+                exp: 8
+            }
+        );
+        // The chain is as old as its oldest link
+        assert_eq!(price.last_updated_slot, 90);
+        assert_eq!(price.unix_timestamp, 1_000);
+    }
+
+    #[test]
+    fn chain_with_unset_link() {
+        let config = ScopeChainConfig::new(&[1, 3]).unwrap();
+        assert!(get_price(&*prices(), &config).is_err());
+    }
+
+    #[test]
+    fn chain_with_frozen_link() {
+        let mut prices = prices();
+        prices.prices[2].set_frozen(true);
+        let config = ScopeChainConfig::new(&[1, 2]).unwrap();
+        assert!(get_price(&*prices, &config).is_err());
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(validate_config(10, MAX_ENTRIES, &ScopeChainConfig::new(&[1, 2]).unwrap()).is_ok());
+        // Too long
+        assert!(ScopeChainConfig::new(&[1; MAX_CHAIN_LENGTH + 1]).is_err());
+        // Empty
+        assert!(validate_config(10, MAX_ENTRIES, &ScopeChainConfig::new(&[]).unwrap()).is_err());
+        // Self reference
+        assert!(validate_config(2, MAX_ENTRIES, &ScopeChainConfig::new(&[1, 2]).unwrap()).is_err());
+        // Out of range
+        let config = ScopeChainConfig::new(&[1, MAX_ENTRIES as u16]).unwrap();
+        assert!(validate_config(10, MAX_ENTRIES, &config).is_err());
+        // Link added by a resize of the feed
+        assert!(validate_config(10, MAX_ENTRIES + 1, &config).is_ok());
+        // Hole in the chain
+        let config = ScopeChainConfig {
+            chain: [1, UNUSED_LINK, 2, UNUSED_LINK],
+        };
+        assert!(validate_config(10, MAX_ENTRIES, &config).is_err());
+        // Zeroed data is a chain of entry 0 only
+        let config = ScopeChainConfig::from_generic_data(&[0; 32]);
+        assert!(validate_config(0, MAX_ENTRIES, &config).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! Median of several scope entries with outlier rejection, see [`ScopeMedianConfig`]
+//!
+//! The price is computed as follow:
+//! 1. Sources that are unset, frozen or older than `max_source_age_slots` are ignored.
+//! 2. The median of the remaining sources is computed.
+//! 3. Sources deviating from this median by more than `max_divergence_bps` are rejected.
+//! 4. The median of the remaining sources is the final price.
+//!
+//! The price is refused if less than `min_sources` sources remain after step 1 or step 3.
+use anchor_lang::prelude::*;
+use decimal_wad::rate::U128;
+
+use super::{get_source_price, validate_source, OracleGenericData};
+use crate::{
+    extension::FeedPrices,
+    utils::math::{price_from_u128, price_value_with_exp},
+    DatedPrice, ScopeError, ScopeResult,
+};
+
+pub const MAX_MEDIAN_SOURCES: usize = 8;
+
+const UNUSED_SOURCE: u16 = u16::MAX;
+
+/// Configuration of a `ScopeMedian` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ScopeMedianConfig {
+    /// Index of the source entries, unused slots are set to `u16::MAX`
+    pub sources: [u16; MAX_MEDIAN_SOURCES],
+    /// Maximum divergence from the median (in bps) before a source is rejected
+    pub max_divergence_bps: u16,
+    /// Minimum number of valid sources required to compute a price
+    pub min_sources: u16,
+    /// Sources older than this number of slots are ignored (0 to disable)
+    pub max_source_age_slots: u32,
+}
+
+impl OracleGenericData for ScopeMedianConfig {}
+
+impl ScopeMedianConfig {
+    pub fn new(sources: &[u16], max_divergence_bps: u16, min_sources: u16) -> ScopeResult<Self> {
+        if sources.len() > MAX_MEDIAN_SOURCES {
+            return Err(ScopeError::InvalidGenericData);
+        }
+        let mut config = Self {
+            sources: [UNUSED_SOURCE; MAX_MEDIAN_SOURCES],
+            max_divergence_bps,
+            min_sources,
+            max_source_age_slots: 0,
+        };
+        config.sources[..sources.len()].copy_from_slice(sources);
+        Ok(config)
+    }
+
+    pub fn used_sources(&self) -> impl Iterator<Item = u16> + '_ {
+        self.sources
+            .iter()
+            .copied()
+            .filter(|&source| source != UNUSED_SOURCE)
+    }
+}
+
+pub fn validate_config(
+    entry_id: usize,
+    num_entries: usize,
+    config: &ScopeMedianConfig,
+) -> ScopeResult<()> {
+    let mut nb_sources: u16 = 0;
+    for (i, source) in config.used_sources().enumerate() {
+        validate_source(entry_id, source, num_entries)?;
+        if config
+            .used_sources()
+            .skip(i + 1)
+            .any(|other| other == source)
+        {
+            msg!("Duplicated median source {}", source);
+            return Err(ScopeError::InvalidGenericData);
+        }
+        nb_sources += 1;
+    }
+    if config.min_sources == 0 || config.min_sources > nb_sources {
+        msg!(
+            "Invalid minimum number of sources {} for {} sources",
+            config.min_sources,
+            nb_sources
+        );
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
+pub fn get_price(
+    prices: &impl FeedPrices,
+    config: &ScopeMedianConfig,
+    clock: &Clock,
+) -> Result<DatedPrice> {
+    let min_sources = usize::from(config.min_sources).max(1);
+
+    let sources: Vec<DatedPrice> = config
+        .used_sources()
+        .filter_map(|source| get_source_price(prices, source).ok())
+        .filter(|price| {
+            config.max_source_age_slots == 0
+                || clock.slot.saturating_sub(price.last_updated_slot)
+                    <= u64::from(config.max_source_age_slots)
+        })
+        .collect();
+
+    if sources.len() < min_sources {
+        msg!(
+            "Not enough valid sources: {} for {} required",
+            sources.len(),
+            min_sources
+        );
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    // Express all sources with the same number of decimals
+    let exp = sources.iter().map(|price| price.price.exp).max().unwrap();
+    let values = sources
+        .iter()
+        .map(|price| price_value_with_exp(&price.price, exp))
+        .collect::<Option<Vec<U128>>>()
+        .ok_or(ScopeError::MathOverflow)?;
+
+    let first_median = median(values.clone());
+
+    let max_divergence_bps = U128::from(config.max_divergence_bps);
+    let accepted: Vec<(DatedPrice, U128)> = sources
+        .into_iter()
+        .zip(values)
+        .filter(|(_, value)| {
+            let divergence = if *value > first_median {
+                *value - first_median
+            } else {
+                first_median - *value
+            };
+            // An overflow of the allowed divergence means any divergence is accepted
+            let allowed = first_median.checked_mul(max_divergence_bps);
+            divergence
+                .checked_mul(U128::from(10_000u64))
+                .map_or(false, |div| allowed.map_or(true, |allowed| div <= allowed))
+        })
+        .collect();
+
+    if accepted.len() < min_sources {
+        msg!(
+            "Not enough sources close to the median: {} for {} required",
+            accepted.len(),
+            min_sources
+        );
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    let value = median(accepted.iter().map(|(_, value)| *value).collect());
+    let price = price_from_u128(value, exp)?;
+
+    // The aggregated price is as old as its oldest accepted source
+    let last_updated_slot = accepted
+        .iter()
+        .map(|(price, _)| price.last_updated_slot)
+        .min()
+        .unwrap();
+    let unix_timestamp = accepted
+        .iter()
+        .map(|(price, _)| price.unix_timestamp)
+        .min()
+        .unwrap();
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot,
+        unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// Median of a non-empty list of values, mean of the two middle values for even lengths
+fn median(mut values: Vec<U128>) -> U128 {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        // Average without overflow
+        let (low, high) = (values[mid - 1], values[mid]);
+        low + (high - low) / U128::from(2u64)
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OraclePrices, Price, MAX_ENTRIES};
+
+    const SLOT: u64 = 1_000;
+
+    fn prices(values: &[(u64, u64)]) -> OraclePrices {
+        let mut prices = OraclePrices {
+            oracle_mappings: Default::default(),
+            prices: [DatedPrice::default(); MAX_ENTRIES],
+        };
+        for (i, &(value, exp)) in values.iter().enumerate() {
+            prices.prices[i] = DatedPrice {
+                price: Price { value, exp },
+                last_updated_slot: SLOT + i as u64,
+                unix_timestamp: 100 + i as u64,
+                ..Default::default()
+            };
+        }
+        prices
+    }
+
+    fn clock() -> Clock {
+        Clock {
+            slot: SLOT + 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn median_odd_and_even() {
+        let values = |v: &[u64]| v.iter().copied().map(U128::from).collect::<Vec<_>>();
+        assert_eq!(median(values(&[3, 1, 2])), U128::from(2u64));
+        assert_eq!(median(values(&[4, 1, 3, 2])), U128::from(2u64));
+        assert_eq!(median(values(&[10, 20])), U128::from(15u64));
+    }
+
+    #[test]
+    fn median_of_sources_with_different_exponents() {
+        let prices = prices(&[(100_000, 3), (10_100, 2), (99_000_000, 6)]);
+        let config = ScopeMedianConfig::new(&[0, 1, 2], 500, 2).unwrap();
+        let price = get_price(&prices, &config, &clock()).unwrap();
+        assert_eq!(
+            price.price,
+            Price {
+                value: 100_000_000,
+                exp: 6
+            }
+        );
+        assert_eq!(price.last_updated_slot, SLOT);
+        assert_eq!(price.unix_timestamp, 100);
+    }
+
+    #[test]
+    fn outlier_is_rejected() {
+        let prices = prices(&[(100, 0), (101, 0), (250, 0)]);
+        let config = ScopeMedianConfig::new(&[0, 1, 2], 100, 2).unwrap();
+        let price = get_price(&prices, &config, &clock()).unwrap();
+        // Median of 100 and 101
+        assert_eq!(price.price, Price { value: 100, exp: 0 });
+        assert_eq!(price.last_updated_slot, SLOT);
+    }
+
+    #[test]
+    fn stale_source_is_ignored() {
+        let mut prices = prices(&[(100, 0), (102, 0), (250, 0)]);
+        prices.prices[2].last_updated_slot = 0;
+        let mut config = ScopeMedianConfig::new(&[0, 1, 2], 100, 2).unwrap();
+        config.max_source_age_slots = 100;
+        let price = get_price(&prices, &config, &clock()).unwrap();
+        assert_eq!(price.price, Price { value: 101, exp: 0 });
+    }
+
+    #[test]
+    fn frozen_source_is_ignored() {
+        let mut prices = prices(&[(100, 0), (102, 0), (101, 0)]);
+        prices.prices[2].set_frozen(true);
+        let config = ScopeMedianConfig::new(&[0, 1, 2], 100, 2).unwrap();
+        let price = get_price(&prices, &config, &clock()).unwrap();
+        assert_eq!(price.price, Price { value: 101, exp: 0 });
+        assert_eq!(price.last_updated_slot, SLOT);
+
+        let config = ScopeMedianConfig::new(&[0, 1, 2], 100, 3).unwrap();
+        assert!(get_price(&prices, &config, &clock()).is_err());
+    }
+
+    #[test]
+    fn not_enough_agreeing_sources() {
+        let prices = prices(&[(100, 0), (150, 0), (250, 0)]);
+        let config = ScopeMedianConfig::new(&[0, 1, 2], 100, 2).unwrap();
+        assert!(get_price(&prices, &config, &clock()).is_err());
+    }
+
+    #[test]
+    fn not_enough_sources() {
+        let prices = prices(&[(100, 0), (0, 0)]);
+        let config = ScopeMedianConfig::new(&[0, 1], 100, 2).unwrap();
+        assert!(get_price(&prices, &config, &clock()).is_err());
+    }
+
+    #[test]
+    fn config_validation() {
+        let valid = ScopeMedianConfig::new(&[0, 1, 2], 100, 2).unwrap();
+        assert!(validate_config(10, MAX_ENTRIES, &valid).is_ok());
+        // Self reference
+        assert!(validate_config(1, MAX_ENTRIES, &valid).is_err());
+        // Duplicated source
+        let duplicated = ScopeMedianConfig::new(&[0, 1, 1], 100, 2).unwrap();
+        assert!(validate_config(10, MAX_ENTRIES, &duplicated).is_err());
+        // Out of range source
+        let out_of_range = ScopeMedianConfig::new(&[0, 1, 512], 100, 2).unwrap();
+        assert!(validate_config(10, MAX_ENTRIES, &out_of_range).is_err());
+        // Source added by a resize of the feed
+        assert!(validate_config(10, MAX_ENTRIES + 1, &out_of_range).is_ok());
+        // Unreachable minimum
+        let min_too_high = ScopeMedianConfig::new(&[0, 1], 100, 3).unwrap();
+        assert!(validate_config(10, MAX_ENTRIES, &min_too_high).is_err());
+        // Zeroed data
+        assert!(validate_config(
+            10,
+            MAX_ENTRIES,
+            &ScopeMedianConfig::from_generic_data(&[0; 32])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn generic_data_roundtrip() {
+        let config = ScopeMedianConfig::new(&[3, 7, 42], 250, 2).unwrap();
+        let data = config.to_generic_data();
+        assert_eq!(ScopeMedianConfig::from_generic_data(&data), config);
+    }
+}
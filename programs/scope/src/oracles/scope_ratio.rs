@@ -0,0 +1,172 @@
+//! Reciprocal of a scope entry and ratio of two scope entries, see [`ScopeReciprocalConfig`] and
+//! [`ScopeRatioConfig`]
+//!
+//! e.g. `USD/SOL` is the reciprocal of `SOL/USD` and `mSOL/stSOL` is the ratio of `mSOL/USD`
+//! and `stSOL/USD`.
+use anchor_lang::prelude::*;
+
+use super::{get_source_price, validate_source, OracleGenericData};
+use crate::{
+    extension::FeedPrices, utils::math::price_div, DatedPrice, Price, ScopeError, ScopeResult,
+};
+
+/// Configuration of a `ScopeReciprocal` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ScopeReciprocalConfig {
+    /// Index of the entry to invert
+    pub source: u16,
+}
+
+impl OracleGenericData for ScopeReciprocalConfig {}
+
+/// Configuration of a `ScopeRatio` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ScopeRatioConfig {
+    /// Index of the entry divided
+    pub numerator: u16,
+    /// Index of the entry dividing
+    pub denominator: u16,
+}
+
+impl OracleGenericData for ScopeRatioConfig {}
+
+pub fn validate_reciprocal_config(
+    entry_id: usize,
+    num_entries: usize,
+    config: &ScopeReciprocalConfig,
+) -> ScopeResult<()> {
+    validate_source(entry_id, config.source, num_entries)
+}
+
+pub fn validate_ratio_config(
+    entry_id: usize,
+    num_entries: usize,
+    config: &ScopeRatioConfig,
+) -> ScopeResult<()> {
+    validate_source(entry_id, config.numerator, num_entries)?;
+    validate_source(entry_id, config.denominator, num_entries)?;
+    if config.numerator == config.denominator {
+        msg!("Ratio of an entry with itself");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
+pub fn get_reciprocal_price(
+    prices: &impl FeedPrices,
+    config: &ScopeReciprocalConfig,
+) -> Result<DatedPrice> {
+    let source = get_source_price(prices, config.source)?;
+    let one = Price { value: 1, exp: 0 };
+    Ok(DatedPrice {
+        price: price_div(&one, &source.price)?,
+        last_updated_slot: source.last_updated_slot,
+        unix_timestamp: source.unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// The ratio is as old as its oldest source
+pub fn get_ratio_price(prices: &impl FeedPrices, config: &ScopeRatioConfig) -> Result<DatedPrice> {
+    let numerator = get_source_price(prices, config.numerator)?;
+    let denominator = get_source_price(prices, config.denominator)?;
+    Ok(DatedPrice {
+        price: price_div(&numerator.price, &denominator.price)?,
+        last_updated_slot: numerator
+            .last_updated_slot
+            .min(denominator.last_updated_slot),
+        unix_timestamp: numerator.unix_timestamp.min(denominator.unix_timestamp),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils::math::price_value_with_exp, OraclePrices, MAX_ENTRIES};
+
+    fn prices() -> Box<OraclePrices> {
+        let mut prices: Box<OraclePrices> = Box::new(bytemuck::Zeroable::zeroed());
+        // mSOL/USD
+        prices.prices[1] = DatedPrice {
+            price: Price {
+                value: 22_000_000,
+                exp: 6,
+            },
+            last_updated_slot: 100,
+            unix_timestamp: 1_000,
+            ..Default::default()
+        };
+        // SOL/USD
+        prices.prices[2] = DatedPrice {
+            price: Price {
+                value: 2_000_000_000,
+                exp: 8,
+            },
+            last_updated_slot: 90,
+            unix_timestamp: 1_010,
+            ..Default::default()
+        };
+        prices
+    }
+
+    #[test]
+    fn reciprocal_price() {
+        let price = get_reciprocal_price(&*prices(), &ScopeReciprocalConfig { source: 2 }).unwrap();
+        // USD/SOL = 0.05
+        assert_eq!(price_value_with_exp(&price.price, 2), Some(5u64.into()));
+        assert_eq!(price.last_updated_slot, 90);
+        assert!(get_reciprocal_price(&*prices(), &ScopeReciprocalConfig { source: 3 }).is_err());
+    }
+
+    #[test]
+    fn ratio_price() {
+        let config = ScopeRatioConfig {
+            numerator: 1,
+            denominator: 2,
+        };
+        let price = get_ratio_price(&*prices(), &config).unwrap();
+        // mSOL/SOL = 1.1
+        assert_eq!(price_value_with_exp(&price.price, 2), Some(110u64.into()));
+        assert_eq!(price.last_updated_slot, 90);
+        assert_eq!(price.unix_timestamp, 1_000);
+
+        // Frozen sources are not used
+        let mut prices = prices();
+        prices.prices[2].set_frozen(true);
+        assert!(get_ratio_price(&*prices, &config).is_err());
+        assert!(get_reciprocal_price(&*prices, &ScopeReciprocalConfig { source: 2 }).is_err());
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(
+            validate_reciprocal_config(10, MAX_ENTRIES, &ScopeReciprocalConfig { source: 2 })
+                .is_ok()
+        );
+        assert!(
+            validate_reciprocal_config(2, MAX_ENTRIES, &ScopeReciprocalConfig { source: 2 })
+                .is_err()
+        );
+        let config = ScopeRatioConfig {
+            numerator: 1,
+            denominator: 2,
+        };
+        assert!(validate_ratio_config(10, MAX_ENTRIES, &config).is_ok());
+        assert!(validate_ratio_config(1, MAX_ENTRIES, &config).is_err());
+        // Same entry on both sides
+        let config = ScopeRatioConfig {
+            numerator: 1,
+            denominator: 1,
+        };
+        assert!(validate_ratio_config(10, MAX_ENTRIES, &config).is_err());
+        // Out of range
+        let config = ScopeRatioConfig {
+            numerator: 1,
+            denominator: MAX_ENTRIES as u16,
+        };
+        assert!(validate_ratio_config(10, MAX_ENTRIES, &config).is_err());
+    }
+}
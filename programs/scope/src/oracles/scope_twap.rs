@@ -0,0 +1,284 @@
+//! Time-weighted average price of another scope entry
+//!
+//! Every refresh of an entry records its price as an observation in the entry ring buffer of the
+//! `OracleTwaps` account (see [`record_observation`]). Observations are recorded at most every
+//! [`MIN_OBSERVATION_INTERVAL_S`] seconds so the buffer covers
+//! `TWAP_NUM_OBSERVATIONS * MIN_OBSERVATION_INTERVAL_S` seconds at minimum.
+//!
+//! The source entry and the averaging window are set in [`ScopeTwapConfig`].
+//!
+//! Each observation is weighted by the time it was the latest one, up to now, and only the part
+//! of this duration that is inside the window is accounted for.
+use anchor_lang::prelude::*;
+use decimal_wad::rate::U128;
+
+use super::{get_source_price, validate_source, OracleGenericData};
+use crate::{
+    extension::{FeedEntries, FeedPrices},
+    DatedPrice, EntryTwap, Price, ScopeError, ScopeResult, TwapObservation, TWAP_NUM_OBSERVATIONS,
+};
+
+/// Minimum time between two recorded observations of an entry
+pub const MIN_OBSERVATION_INTERVAL_S: u64 = 60;
+
+const NUM_OBSERVATIONS_U16: u16 = TWAP_NUM_OBSERVATIONS as u16;
+
+/// Configuration of a `ScopeTwap` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ScopeTwapConfig {
+    /// Index of the entry to average
+    pub source_entry: u16,
+    /// Minimum number of observations in the window required to compute a price
+    pub min_samples: u16,
+    /// Duration of the averaging window in seconds
+    pub window_s: u32,
+}
+
+impl OracleGenericData for ScopeTwapConfig {}
+
+pub fn validate_config(
+    entry_id: usize,
+    num_entries: usize,
+    config: &ScopeTwapConfig,
+) -> ScopeResult<()> {
+    validate_source(entry_id, config.source_entry, num_entries)?;
+    if config.window_s == 0 {
+        msg!("TWAP window cannot be empty");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    if config.min_samples == 0 || config.min_samples > NUM_OBSERVATIONS_U16 {
+        msg!(
+            "Invalid minimum number of samples {}, max is {}",
+            config.min_samples,
+            TWAP_NUM_OBSERVATIONS
+        );
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
+/// Record a new price of an entry in its observations ring buffer
+///
+/// Prices too close to the latest observation are ignored.
+/// The buffer is reset if the exponent of the price changes.
+pub fn record_observation(twap: &mut EntryTwap, price: &DatedPrice) {
+    let Price { value, exp } = price.price;
+    let unix_timestamp = price.unix_timestamp;
+    if value == 0 {
+        return;
+    }
+    if twap.len > 0 && twap.exp == exp {
+        let next_observation_ts = twap.observations[usize::from(twap.head)]
+            .unix_timestamp
+            .saturating_add(MIN_OBSERVATION_INTERVAL_S);
+        if unix_timestamp < next_observation_ts {
+            return;
+        }
+    } else {
+        twap.len = 0;
+        twap.exp = exp;
+    }
+
+    let head = if twap.len == 0 {
+        0
+    } else {
+        (twap.head + 1) % NUM_OBSERVATIONS_U16
+    };
+    twap.observations[usize::from(head)] = TwapObservation {
+        value,
+        unix_timestamp,
+    };
+    twap.head = head;
+    twap.len = (twap.len + 1).min(NUM_OBSERVATIONS_U16);
+}
+
+pub fn get_price(
+    twaps: &impl FeedEntries<Entry = EntryTwap>,
+    prices: &impl FeedPrices,
+    config: &ScopeTwapConfig,
+    clock: &Clock,
+) -> Result<DatedPrice> {
+    let source_price = get_source_price(prices, config.source_entry)?;
+    let twap = twaps
+        .entry(usize::from(config.source_entry))
+        .ok_or(ScopeError::BadTokenNb)?;
+
+    let now: u64 = clock.unix_timestamp.try_into().unwrap_or(0);
+    let window_start = now.saturating_sub(config.window_s.into());
+
+    let mut weighted_sum = U128::zero();
+    let mut total_weight: u64 = 0;
+    let mut samples: u16 = 0;
+    // Walk the observations from the most recent one, each one lasting until the next one
+    let mut end = now;
+    for i in 0..twap.len {
+        let index = (twap.head + NUM_OBSERVATIONS_U16 - i) % NUM_OBSERVATIONS_U16;
+        let observation = twap.observations[usize::from(index)];
+        let start = observation.unix_timestamp.max(window_start);
+        if end > start {
+            let weight = end - start;
+            weighted_sum = U128::from(observation.value)
+                .checked_mul(U128::from(weight))
+                .and_then(|value| weighted_sum.checked_add(value))
+                .ok_or(ScopeError::MathOverflow)?;
+            total_weight += weight;
+            samples += 1;
+        }
+        if observation.unix_timestamp <= window_start {
+            break;
+        }
+        end = end.min(observation.unix_timestamp);
+    }
+
+    if samples < config.min_samples || total_weight == 0 {
+        msg!(
+            "Not enough TWAP samples: {} for {} required",
+            samples,
+            config.min_samples
+        );
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    let value = (weighted_sum / U128::from(total_weight)).as_u64();
+
+    // The TWAP is as fresh as its source
+    Ok(DatedPrice {
+        price: Price {
+            value,
+            exp: twap.exp,
+        },
+        last_updated_slot: source_price.last_updated_slot,
+        unix_timestamp: source_price.unix_timestamp,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OraclePrices, OracleTwaps, MAX_ENTRIES};
+
+    const SOURCE: usize = 2;
+
+    fn dated_price(value: u64, unix_timestamp: u64) -> DatedPrice {
+        DatedPrice {
+            price: Price { value, exp: 6 },
+            last_updated_slot: unix_timestamp,
+            unix_timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn twaps_with(observations: &[(u64, u64)]) -> Box<OracleTwaps> {
+        let mut twaps: Box<OracleTwaps> = Box::new(bytemuck::Zeroable::zeroed());
+        for &(value, ts) in observations {
+            record_observation(&mut twaps.twaps[SOURCE], &dated_price(value, ts));
+        }
+        twaps
+    }
+
+    fn prices() -> Box<OraclePrices> {
+        let mut prices: Box<OraclePrices> = Box::new(bytemuck::Zeroable::zeroed());
+        prices.prices[SOURCE] = dated_price(1, 1_000);
+        prices
+    }
+
+    fn clock_at(unix_timestamp: i64) -> Clock {
+        Clock {
+            unix_timestamp,
+            ..Default::default()
+        }
+    }
+
+    fn config(window_s: u32, min_samples: u16) -> ScopeTwapConfig {
+        ScopeTwapConfig {
+            source_entry: SOURCE as u16,
+            min_samples,
+            window_s,
+        }
+    }
+
+    #[test]
+    fn observations_are_rate_limited() {
+        let twaps = twaps_with(&[(100, 1_000), (200, 1_030), (300, 1_060)]);
+        let twap = &twaps.twaps[SOURCE];
+        assert_eq!(twap.len, 2);
+        assert_eq!(twap.observations[usize::from(twap.head)].value, 300);
+    }
+
+    #[test]
+    fn ring_buffer_wraps() {
+        let observations: Vec<(u64, u64)> = (0..TWAP_NUM_OBSERVATIONS as u64 + 3)
+            .map(|i| (i + 1, i * MIN_OBSERVATION_INTERVAL_S))
+            .collect();
+        let twaps = twaps_with(&observations);
+        let twap = &twaps.twaps[SOURCE];
+        assert_eq!(usize::from(twap.len), TWAP_NUM_OBSERVATIONS);
+        assert_eq!(twap.head, 2);
+        assert_eq!(twap.observations[2].value, TWAP_NUM_OBSERVATIONS as u64 + 3);
+    }
+
+    #[test]
+    fn exponent_change_resets_observations() {
+        let mut twaps = twaps_with(&[(100, 1_000), (200, 1_060)]);
+        let mut new_price = dated_price(3_000, 1_120);
+        new_price.price.exp = 7;
+        record_observation(&mut twaps.twaps[SOURCE], &new_price);
+        let twap = &twaps.twaps[SOURCE];
+        assert_eq!(twap.len, 1);
+        assert_eq!(twap.exp, 7);
+    }
+
+    #[test]
+    fn time_weighted_average() {
+        let twaps = twaps_with(&[(100, 1_000), (200, 1_060), (400, 1_180)]);
+        // Window [1_000, 1_240]: 100 for 60s, 200 for 120s, 400 for 60s
+        let price = get_price(&*twaps, &*prices(), &config(240, 3), &clock_at(1_240)).unwrap();
+        assert_eq!(price.price, Price { value: 225, exp: 6 });
+        assert_eq!(price.unix_timestamp, 1_000);
+    }
+
+    #[test]
+    fn window_start_truncates_older_observation() {
+        let twaps = twaps_with(&[(100, 1_000), (200, 1_060), (400, 1_180)]);
+        // Window [1_120, 1_240]: 200 for 60s, 400 for 60s
+        let price = get_price(&*twaps, &*prices(), &config(120, 2), &clock_at(1_240)).unwrap();
+        assert_eq!(price.price.value, 300);
+    }
+
+    #[test]
+    fn not_enough_samples() {
+        let twaps = twaps_with(&[(100, 1_000), (200, 1_060), (400, 1_180)]);
+        // Window [1_170, 1_200]: only 400 and the observation before the window start
+        assert!(get_price(&*twaps, &*prices(), &config(30, 3), &clock_at(1_200)).is_err());
+        let empty: Box<OracleTwaps> = Box::new(bytemuck::Zeroable::zeroed());
+        assert!(get_price(&*empty, &*prices(), &config(30, 1), &clock_at(1_200)).is_err());
+    }
+
+    #[test]
+    fn frozen_source() {
+        let twaps = twaps_with(&[(100, 1_000), (200, 1_060), (400, 1_180)]);
+        let mut prices = prices();
+        prices.prices[SOURCE].set_frozen(true);
+        assert!(get_price(&*twaps, &*prices, &config(240, 3), &clock_at(1_240)).is_err());
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(validate_config(10, MAX_ENTRIES, &config(600, 5)).is_ok());
+        // Self reference
+        assert!(validate_config(SOURCE, MAX_ENTRIES, &config(600, 5)).is_err());
+        // Empty window
+        assert!(validate_config(10, MAX_ENTRIES, &config(0, 5)).is_err());
+        // Unreachable minimum
+        assert!(validate_config(10, MAX_ENTRIES, &config(600, NUM_OBSERVATIONS_U16 + 1)).is_err());
+        // Zeroed data
+        assert!(validate_config(
+            10,
+            MAX_ENTRIES,
+            &ScopeTwapConfig::from_generic_data(&[0; 32])
+        )
+        .is_err());
+    }
+}
@@ -1,13 +1,64 @@
-use anchor_lang::{prelude::*, solana_program::borsh::try_from_slice_unchecked};
-use spl_stake_pool::state::StakePool;
+//! Toolings to retrieve the price of SPL stake pool tokens (like scnSol)
+//!
+//! Stake pools are updated by a crank at the beginning of every epoch, the rate of a pool not
+//! updated yet in the current epoch is rejected. Entries can configure a grace period (see
+//! [`SplStakeConfig`]) during which the rate of such a pool is projected from its rate of change
+//! over the previous epoch instead.
 
-use crate::{DatedPrice, Price, Result, ScopeError};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{borsh::try_from_slice_unchecked, sysvar::epoch_schedule::EpochSchedule},
+};
+use solana_program::pubkey;
+use spl_stake_pool::state::{AccountType, StakePool};
+
+use super::{check_account_owner, OracleGenericData};
+use crate::{DatedPrice, Price, Result, ScopeError, ScopeResult};
+
+/// Stake pool programs sharing the SPL stake pool layout
+pub const STAKE_POOL_PROGRAM_IDS: [Pubkey; 4] = [
+    // SPL stake pool
+    spl_stake_pool::ID,
+    // Socean stake pool (scnSol)
+    pubkey!("5ocnV1qiCgaQR8Jb8xWnVbApfaygJ8tNoZfgPwsgx9kx"),
+    // Sanctum single validator stake pools
+    pubkey!("SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxdqvyvhY"),
+    // Sanctum multi validator stake pools
+    pubkey!("SPMBzsVUuoHA4Jm6KunbsotaahvVikZs1JyTW6iJvbn"),
+];
+
+/// Max configurable grace period after the start of an epoch (1 hour)
+pub const MAX_EPOCH_GRACE_PERIOD_S: u64 = 60 * 60;
 
 const DECIMALS: u32 = 15u32;
 
+/// Configuration of a `SplStake` entry, stored in the entry generic data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct SplStakeConfig {
+    /// Seconds after the start of an epoch during which the rate of a pool updated in the
+    /// previous epoch is projected, 0 disables the projection
+    pub epoch_grace_period_s: u64,
+}
+
+impl OracleGenericData for SplStakeConfig {}
+
+pub fn validate_config(config: &SplStakeConfig) -> ScopeResult<()> {
+    if config.epoch_grace_period_s > MAX_EPOCH_GRACE_PERIOD_S {
+        msg!(
+            "SPL Stake epoch grace period of {}s is above the max of {}s",
+            config.epoch_grace_period_s,
+            MAX_EPOCH_GRACE_PERIOD_S
+        );
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(())
+}
+
 // Gives the price of 1 staked SOL in SOL
 pub fn get_price(
     stake_pool_account_info: &AccountInfo,
+    config: &SplStakeConfig,
     current_clock: &Clock,
 ) -> Result<DatedPrice> {
     let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_account_info.data.borrow())
@@ -16,6 +67,13 @@ pub fn get_price(
             ScopeError::UnexpectedAccount
         })?;
 
+    if stake_pool.last_update_epoch != current_clock.epoch
+        && is_in_grace_period(&stake_pool, config, current_clock)
+    {
+        msg!("SPL Stake account not refreshed yet, projecting the previous epoch rate");
+        return get_projected_price(&stake_pool, current_clock);
+    }
+
     #[cfg(not(feature = "skip_price_validation"))]
     if stake_pool.last_update_epoch != current_clock.epoch {
         // The price has not been refreshed this epoch
@@ -40,6 +98,62 @@ pub fn get_price(
     Ok(dated_price)
 }
 
+/// The pool was updated in the previous epoch and the current epoch started less than the
+/// configured grace period ago
+fn is_in_grace_period(stake_pool: &StakePool, config: &SplStakeConfig, clock: &Clock) -> bool {
+    let elapsed_in_epoch = clock
+        .unix_timestamp
+        .saturating_sub(clock.epoch_start_timestamp);
+    config.epoch_grace_period_s > 0
+        && stake_pool.last_update_epoch.checked_add(1) == Some(clock.epoch)
+        && u64::try_from(elapsed_in_epoch).map_or(false, |s| s <= config.epoch_grace_period_s)
+}
+
+/// Price projected at the start of the current epoch
+fn get_projected_price(stake_pool: &StakePool, clock: &Clock) -> Result<DatedPrice> {
+    let value = projected_scaled_rate(stake_pool)?;
+    let epoch_start_slot = EpochSchedule::get()?.get_first_slot_in_epoch(clock.epoch);
+
+    Ok(DatedPrice {
+        price: Price {
+            value,
+            exp: DECIMALS.into(),
+        },
+        last_updated_slot: epoch_start_slot,
+        unix_timestamp: u64::try_from(clock.epoch_start_timestamp).unwrap_or(0),
+        ..Default::default()
+    })
+}
+
+/// Check that the account is an initialized stake pool with a usable exchange rate
+pub fn validate_stake_pool_info(stake_pool_account_info: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(stake_pool_account_info, &STAKE_POOL_PROGRAM_IDS)?;
+
+    let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_account_info.data.borrow())
+        .map_err(|_| {
+            msg!("Provided pubkey is not a SPL Stake account");
+            ScopeError::UnexpectedAccount
+        })?;
+    validate_stake_pool(&stake_pool)
+}
+
+fn validate_stake_pool(stake_pool: &StakePool) -> Result<()> {
+    if stake_pool.account_type != AccountType::StakePool {
+        msg!("Provided SPL Stake account is not an initialized stake pool");
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    match scaled_rate(stake_pool) {
+        Ok(value) if value > 0 => Ok(()),
+        _ => {
+            msg!("SPL Stake account has an invalid exchange rate");
+            err!(ScopeError::PriceNotValid)
+        }
+    }
+}
+
 fn scaled_rate(stake_pool: &StakePool) -> Result<u64> {
     const FACTOR: u64 = 10u64.pow(DECIMALS);
     stake_pool
@@ -47,6 +161,23 @@ fn scaled_rate(stake_pool: &StakePool) -> Result<u64> {
         .ok_or_else(|| ScopeError::MathOverflow.into())
 }
 
+/// Rate of the last update increased by its change over the previous epoch
+///
+/// Decreasing rates (e.g. after a slashing) are not projected, the last rate is used as is.
+fn projected_scaled_rate(stake_pool: &StakePool) -> Result<u64> {
+    const FACTOR: u128 = 10u128.pow(DECIMALS);
+    let rate = scaled_rate(stake_pool)?;
+    if stake_pool.last_epoch_pool_token_supply == 0 {
+        return Ok(rate);
+    }
+    let previous_rate = u128::from(stake_pool.last_epoch_total_lamports) * FACTOR
+        / u128::from(stake_pool.last_epoch_pool_token_supply);
+    let previous_rate = u64::try_from(previous_rate).map_err(|_| ScopeError::IntegerOverflow)?;
+    let change = rate.saturating_sub(previous_rate);
+    rate.checked_add(change)
+        .ok_or_else(|| ScopeError::MathOverflow.into())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -92,4 +223,107 @@ mod test {
         // Expect staked token price to be 2 tokens
         assert_eq!(scaled_rate(&stake_pool).unwrap(), 2 * 10u64.pow(DECIMALS));
     }
+
+    fn clock(epoch: u64, elapsed_in_epoch: i64) -> Clock {
+        Clock {
+            slot: 1_000,
+            epoch_start_timestamp: 1_000_000,
+            epoch,
+            leader_schedule_epoch: epoch + 1,
+            unix_timestamp: 1_000_000 + elapsed_in_epoch,
+        }
+    }
+
+    #[test]
+    pub fn grace_period() {
+        let stake_pool = StakePool {
+            last_update_epoch: 10,
+            ..Default::default()
+        };
+        let config = SplStakeConfig {
+            epoch_grace_period_s: 600,
+        };
+        assert!(is_in_grace_period(&stake_pool, &config, &clock(11, 600)));
+        // Too late in the epoch
+        assert!(!is_in_grace_period(&stake_pool, &config, &clock(11, 601)));
+        // Not updated for more than an epoch
+        assert!(!is_in_grace_period(&stake_pool, &config, &clock(12, 0)));
+        // Projection disabled
+        assert!(!is_in_grace_period(
+            &stake_pool,
+            &SplStakeConfig::default(),
+            &clock(11, 0)
+        ));
+    }
+
+    #[test]
+    pub fn projected_rate() {
+        // 1.1 SOL per token in the previous epoch, 1.2 now
+        let stake_pool = StakePool {
+            total_lamports: 12 * 10u64.pow(5),
+            pool_token_supply: 10u64.pow(6),
+            last_epoch_total_lamports: 11 * 10u64.pow(5),
+            last_epoch_pool_token_supply: 10u64.pow(6),
+            ..Default::default()
+        };
+        assert_eq!(
+            projected_scaled_rate(&stake_pool).unwrap(),
+            13 * 10u64.pow(DECIMALS - 1)
+        );
+
+        // Decreasing rates are not projected
+        let stake_pool = StakePool {
+            last_epoch_total_lamports: 13 * 10u64.pow(5),
+            ..stake_pool
+        };
+        assert_eq!(
+            projected_scaled_rate(&stake_pool).unwrap(),
+            12 * 10u64.pow(DECIMALS - 1)
+        );
+
+        // New pools without previous epoch
+        let stake_pool = StakePool {
+            last_epoch_total_lamports: 0,
+            last_epoch_pool_token_supply: 0,
+            ..stake_pool
+        };
+        assert_eq!(
+            projected_scaled_rate(&stake_pool).unwrap(),
+            12 * 10u64.pow(DECIMALS - 1)
+        );
+    }
+
+    #[test]
+    pub fn config_validation() {
+        assert!(validate_config(&SplStakeConfig::default()).is_ok());
+        assert!(validate_config(&SplStakeConfig {
+            epoch_grace_period_s: MAX_EPOCH_GRACE_PERIOD_S
+        })
+        .is_ok());
+        assert!(validate_config(&SplStakeConfig {
+            epoch_grace_period_s: MAX_EPOCH_GRACE_PERIOD_S + 1
+        })
+        .is_err());
+    }
+
+    #[test]
+    pub fn stake_pool_validation() {
+        let stake_pool = StakePool {
+            account_type: AccountType::StakePool,
+            total_lamports: 10u64.pow(5),
+            pool_token_supply: 10u64.pow(5),
+            ..Default::default()
+        };
+        assert!(validate_stake_pool(&stake_pool).is_ok());
+
+        // Uninitialized account
+        let mut invalid = stake_pool.clone();
+        invalid.account_type = AccountType::Uninitialized;
+        assert!(validate_stake_pool(&invalid).is_err());
+
+        // Pool tokens worth nothing
+        let mut invalid = stake_pool;
+        invalid.total_lamports = 0;
+        assert!(validate_stake_pool(&invalid).is_err());
+    }
 }
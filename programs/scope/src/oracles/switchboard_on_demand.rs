@@ -0,0 +1,278 @@
+//! Toolings to retrieve switchboard on-demand prices (`PullFeedAccountData`)
+//!
+//! On-demand feeds are updated by their users, the result stored in the feed is used only if:
+//!
+//! 1. It is not older than the `max_staleness` slots configured in the feed, or if the feed does
+//!    not set one, than the `max_age_slots` of the entry (or [`DEFAULT_MAX_STALENESS`]).
+//! 2. It aggregates at least the `min_sample_size` oracle samples configured in the feed.
+//! 3. Its standard deviation is within the entry max confidence interval.
+use std::cell::Ref;
+
+use anchor_lang::prelude::*;
+use solana_program::pubkey;
+
+pub use self::on_demand::*;
+use super::{
+    check_account_owner,
+    switchboard_v2::{scale_std_deviation, validate_confidence, SwitchboardDecimal},
+};
+use crate::{DatedPrice, Price, Result, ScopeError};
+
+/// Switchboard on-demand program, owner of the pull feeds
+pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey =
+    pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+
+/// Number of decimals of the values of pull feeds
+pub const PRECISION: u32 = 18;
+
+/// Max age of a result in slots when neither the feed nor the entry set one
+pub const DEFAULT_MAX_STALENESS: u32 = 250;
+
+pub fn get_price(
+    switchboard_feed_info: &AccountInfo,
+    clock: &Clock,
+    confidence_bps: u16,
+    max_age_slots: u32,
+) -> Result<DatedPrice> {
+    let feed = PullFeedAccountData::new(switchboard_feed_info)?;
+    let default_max_staleness = match max_age_slots {
+        0 => DEFAULT_MAX_STALENESS,
+        max_age_slots => max_age_slots,
+    };
+    let result = feed
+        .get_result(clock.slot, default_max_staleness)
+        .map_err(|e| {
+            msg!(
+                "Switchboard on-demand get result from feed {} failed",
+                switchboard_feed_info.key()
+            );
+            e
+        })?;
+
+    let value = result.value;
+    let std_dev = result.std_dev;
+    let price: Price = SwitchboardDecimal::new(value, PRECISION).try_into()?;
+    let confidence = scale_std_deviation(std_dev, PRECISION, price.exp);
+
+    if !cfg!(feature = "skip_price_validation")
+        && validate_confidence(value, PRECISION, std_dev, PRECISION, confidence_bps).is_err()
+    {
+        msg!(
+            "Validation of confidence interval for switchboard on-demand feed {} failed. Price: {:?}, std_dev: {:?}",
+            switchboard_feed_info.key(),
+            price,
+            std_dev
+        );
+        return err!(ScopeError::SwitchboardOnDemandError);
+    }
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: result.slot,
+        unix_timestamp: feed.last_update_timestamp.try_into().unwrap_or(0),
+        confidence,
+        ..Default::default()
+    })
+}
+
+pub fn validate_pull_feed_info(switchboard_feed_info: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    PullFeedAccountData::new(switchboard_feed_info)?;
+    Ok(())
+}
+
+mod on_demand {
+    use anchor_lang::__private::bytemuck::{self, Pod, Zeroable};
+
+    use super::*;
+
+    #[zero_copy]
+    #[repr(packed)]
+    #[derive(Default, Debug, PartialEq, Eq)]
+    pub struct OracleSubmission {
+        pub oracle: Pubkey,
+        pub slot: u64,
+        pub landed_at: u64,
+        pub value: i128,
+    }
+
+    #[zero_copy]
+    #[repr(packed)]
+    #[derive(Default, Debug, PartialEq)]
+    pub struct CompactResult {
+        pub std_dev: f32,
+        pub mean: f32,
+        pub slot: u64,
+    }
+
+    #[zero_copy]
+    #[repr(packed)]
+    #[derive(Default, Debug, PartialEq, Eq)]
+    pub struct CurrentResult {
+        // Median of the samples, with 18 decimals
+        pub value: i128,
+        pub std_dev: i128,
+        pub mean: i128,
+        pub range: i128,
+        pub min_value: i128,
+        pub max_value: i128,
+        // Number of samples aggregated in the result
+        pub num_samples: u8,
+        pub submission_idx: u8,
+        pub padding1: [u8; 6],
+        // Slot of the result (median of the samples slots)
+        pub slot: u64,
+        pub min_slot: u64,
+        pub max_slot: u64,
+    }
+
+    #[zero_copy]
+    #[repr(packed)]
+    #[derive(Debug)]
+    pub struct PullFeedAccountData {
+        pub submissions: [OracleSubmission; 32],
+        pub authority: Pubkey,
+        pub queue: Pubkey,
+        pub feed_hash: [u8; 32],
+        pub initialized_at: i64,
+        pub permissions: u64,
+        pub max_variance: u64,
+        pub min_responses: u32,
+        pub name: [u8; 32],
+        pub _padding1: [u8; 2],
+        pub historical_result_idx: u8,
+        // Minimum number of samples for the result to be used
+        pub min_sample_size: u8,
+        pub last_update_timestamp: i64,
+        pub lut_slot: u64,
+        pub _reserved1: [u8; 32],
+        pub result: CurrentResult,
+        // Maximum age of the result in slots
+        pub max_staleness: u32,
+        pub _padding2: [u8; 12],
+        pub historical_results: [CompactResult; 32],
+        pub _ebuf4: [u8; 8],
+        pub _ebuf3: [u8; 24],
+        pub _ebuf2: [u8; 256],
+    }
+
+    impl PullFeedAccountData {
+        pub fn new<'info>(
+            switchboard_feed: &'info AccountInfo,
+        ) -> Result<Ref<'info, PullFeedAccountData>> {
+            check_account_owner(switchboard_feed, &[SWITCHBOARD_ON_DEMAND_PROGRAM_ID])?;
+
+            let data = switchboard_feed.try_borrow_data()?;
+            let size = 8 + std::mem::size_of::<PullFeedAccountData>();
+            if data.len() < size || data[..8] != PullFeedAccountData::discriminator() {
+                msg!(
+                    "Switchboard pull feed {} has an invalid discriminator",
+                    switchboard_feed.key()
+                );
+                return err!(ScopeError::SwitchboardOnDemandError);
+            }
+
+            Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..size])))
+        }
+
+        /// Get the current result of the feed, `default_max_staleness` bounds its age when the
+        /// feed does not set a `max_staleness`
+        pub fn get_result(
+            &self,
+            current_slot: u64,
+            default_max_staleness: u32,
+        ) -> std::result::Result<CurrentResult, ScopeError> {
+            // Copy to avoid references to a packed struct
+            let result = self.result;
+            let num_samples = result.num_samples;
+            let min_sample_size = self.min_sample_size.max(1);
+            if num_samples < min_sample_size {
+                msg!("Switchboard price is invalid: min_sample_size: {min_sample_size} > result.num_samples: {num_samples}");
+                return Err(ScopeError::SwitchboardOnDemandError);
+            }
+            let max_staleness = match self.max_staleness {
+                0 => default_max_staleness,
+                max_staleness => max_staleness,
+            };
+            let age = current_slot.saturating_sub(result.slot);
+            if age > u64::from(max_staleness) {
+                msg!("Switchboard price is stale: {age} slots old, max_staleness: {max_staleness}");
+                return Err(ScopeError::PriceTooOld);
+            }
+            Ok(result)
+        }
+
+        pub fn discriminator() -> [u8; 8] {
+            [196, 27, 108, 196, 10, 215, 219, 40]
+        }
+    }
+
+    unsafe impl Pod for PullFeedAccountData {}
+    unsafe impl Zeroable for PullFeedAccountData {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(num_samples: u8, slot: u64) -> PullFeedAccountData {
+        let mut feed: PullFeedAccountData = bytemuck::Zeroable::zeroed();
+        feed.min_sample_size = 2;
+        feed.max_staleness = 100;
+        feed.result.value = 1_500_000_000_000_000_000;
+        feed.result.num_samples = num_samples;
+        feed.result.slot = slot;
+        feed
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(std::mem::size_of::<PullFeedAccountData>(), 3200);
+        assert_eq!(std::mem::size_of::<CurrentResult>(), 128);
+    }
+
+    #[test]
+    fn result_checks() {
+        assert!(feed(2, 1_000)
+            .get_result(1_100, DEFAULT_MAX_STALENESS)
+            .is_ok());
+        // Not enough samples
+        assert_eq!(
+            feed(1, 1_000).get_result(1_000, DEFAULT_MAX_STALENESS),
+            Err(ScopeError::SwitchboardOnDemandError)
+        );
+        // Stale
+        assert_eq!(
+            feed(2, 1_000).get_result(1_101, DEFAULT_MAX_STALENESS),
+            Err(ScopeError::PriceTooOld)
+        );
+        // Stale without a feed max staleness
+        let mut no_staleness = feed(2, 1_000);
+        no_staleness.max_staleness = 0;
+        assert!(no_staleness.get_result(1_050, 50).is_ok());
+        assert_eq!(
+            no_staleness.get_result(1_051, 50),
+            Err(ScopeError::PriceTooOld)
+        );
+        // A result is always required
+        let mut no_min = feed(0, 1_000);
+        no_min.min_sample_size = 0;
+        assert!(no_min.get_result(1_000, DEFAULT_MAX_STALENESS).is_err());
+    }
+
+    #[test]
+    fn result_to_price() {
+        let price: Price = SwitchboardDecimal::new(feed(2, 0).result.value, PRECISION)
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            price,
+            Price {
+                value: 15_000_000_000,
+                exp: 10
+            }
+        );
+    }
+}
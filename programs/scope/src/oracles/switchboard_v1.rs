@@ -1,12 +1,18 @@
 use std::{cmp::min, convert::TryInto};
 
 use anchor_lang::prelude::*;
+use solana_program::pubkey;
 use switchboard_program::{
     get_aggregator, get_aggregator_result, AggregatorState, RoundResult, SwitchboardAccountType,
 };
 
+use super::check_account_owner;
 use crate::{DatedPrice, Price, Result, ScopeError};
 
+/// Switchboard v1 program, owner of the aggregators
+pub const SWITCHBOARD_V1_PROGRAM_ID: Pubkey =
+    pubkey!("DtmE9D2CSB4L5D6A15mraeEjrGMm6auWVzgaD8hK2tZM");
+
 const SWITCHBOARD_V1_PRICE_DECIMALS: u32 = 8u32;
 const PRICE_MULTIPLIER: f64 = 10u64.pow(SWITCHBOARD_V1_PRICE_DECIMALS) as f64;
 const MAX_PRICE_FLOAT: f64 = 10_000_000_000f64; //we choose an arbitrarily high number to do a sanity check and avoid overflow in the multiplication below
@@ -42,6 +48,26 @@ pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
     validate_valid_price(price, slot, timestamp, aggregator, round_result)
 }
 
+pub fn validate_aggregator_info(switchboard_feed_info: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(switchboard_feed_info, &[SWITCHBOARD_V1_PROGRAM_ID])?;
+
+    let account_buf = switchboard_feed_info.try_borrow_data()?;
+    if account_buf.first() != Some(&(SwitchboardAccountType::TYPE_AGGREGATOR as u8)) {
+        msg!("switchboard address not of type aggregator");
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    drop(account_buf);
+
+    get_aggregator(switchboard_feed_info).map_err(|_| {
+        msg!("switchboard aggregator cannot be deserialized");
+        ScopeError::UnableToDeserializeAccount
+    })?;
+    Ok(())
+}
+
 pub fn validate_valid_price(
     price: u64,
     slot: u64,
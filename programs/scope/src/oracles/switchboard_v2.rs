@@ -1,16 +1,22 @@
 use std::convert::TryInto;
 
 use anchor_lang::prelude::*;
+use solana_program::pubkey;
 
+pub(super) use self::switchboard::SwitchboardDecimal;
 use self::switchboard::*;
-use crate::{DatedPrice, Price, Result, ScopeError};
+use crate::{
+    oracles::{check_account_owner, confidence_factor},
+    DatedPrice, Price, Result, ScopeError,
+};
 
-const MAX_EXPONENT: u32 = 10;
+/// Switchboard v2 program, owner of the aggregators
+pub const SWITCHBOARD_V2_PROGRAM_ID: Pubkey =
+    pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
 
-const MIN_CONFIDENCE_PERCENTAGE: u64 = 2u64;
-const CONFIDENCE_FACTOR: u64 = 100 / MIN_CONFIDENCE_PERCENTAGE;
+const MAX_EXPONENT: u32 = 10;
 
-pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
+pub fn get_price(switchboard_feed_info: &AccountInfo, confidence_bps: u16) -> Result<DatedPrice> {
     let feed = AggregatorAccountData::new(switchboard_feed_info)
         .map_err(|_| ScopeError::SwitchboardV2Error)?;
 
@@ -23,6 +29,8 @@ pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
     })?;
 
     let price: Price = price_switchboard_desc.try_into()?;
+    let std_deviation = feed.latest_confirmed_round.std_deviation;
+    let confidence = scale_std_deviation(std_deviation.mantissa, std_deviation.scale, price.exp);
 
     if !cfg!(feature = "skip_price_validation") {
         let stdev_mantissa = feed.latest_confirmed_round.std_deviation.mantissa;
@@ -32,6 +40,7 @@ pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
             price_switchboard_desc.scale,
             stdev_mantissa,
             stdev_scale,
+            confidence_bps,
         )
         .is_err()
         {
@@ -58,16 +67,47 @@ pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
         price,
         last_updated_slot,
         unix_timestamp,
+        confidence,
         ..Default::default()
     })
 }
 
-fn validate_confidence(
+pub fn validate_aggregator_info(switchboard_feed_info: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    check_account_owner(switchboard_feed_info, &[SWITCHBOARD_V2_PROGRAM_ID])?;
+    AggregatorAccountData::new(switchboard_feed_info)?;
+    Ok(())
+}
+
+/// Express the standard deviation with the price exponent, saturating if it does not fit
+pub(super) fn scale_std_deviation(stdev_mantissa: i128, stdev_scale: u32, exp: u64) -> u64 {
+    let stdev = stdev_mantissa.unsigned_abs();
+    let exp = u32::try_from(exp).unwrap_or(u32::MAX);
+    let scaled = if exp >= stdev_scale {
+        10_u128
+            .checked_pow(exp - stdev_scale)
+            .and_then(|factor| stdev.checked_mul(factor))
+            .unwrap_or(u128::MAX)
+    } else {
+        10_u128
+            .checked_pow(stdev_scale - exp)
+            .map_or(0, |factor| stdev / factor)
+    };
+    u64::try_from(scaled).unwrap_or(u64::MAX)
+}
+
+/// Validate that the stdev/price ratio is below `confidence_bps`
+pub(super) fn validate_confidence(
     price_mantissa: i128,
     price_scale: u32,
     stdev_mantissa: i128,
     stdev_scale: u32,
+    confidence_bps: u16,
 ) -> std::result::Result<(), ScopeError> {
+    let (factor_num, factor_den) = confidence_factor(confidence_bps);
+
     // Step 1: compute scaling factor to bring the stdev to the same scale as the price.
     let (scale_op, scale_diff): (&dyn Fn(i128, i128) -> Option<i128>, _) =
         if price_scale >= stdev_scale {
@@ -86,14 +126,17 @@ fn validate_confidence(
         .checked_pow(scale_diff)
         .ok_or(ScopeError::MathOverflow)?;
 
-    // Step 2: multiply the stdev by the CONFIDENCE_FACTOR and apply scaling factor.
+    // Step 2: multiply the stdev by the confidence factor and apply scaling factor.
 
     let stdev_x_confidence_factor_scaled = stdev_mantissa
-        .checked_mul(CONFIDENCE_FACTOR.into())
+        .checked_mul(factor_num.into())
         .and_then(|a| scale_op(a, scaling_factor))
         .ok_or(ScopeError::MathOverflow)?;
+    let price_x_confidence_factor = price_mantissa
+        .checked_mul(factor_den.into())
+        .ok_or(ScopeError::MathOverflow)?;
 
-    if stdev_x_confidence_factor_scaled >= price_mantissa {
+    if stdev_x_confidence_factor_scaled >= price_x_confidence_factor {
         Err(ScopeError::PriceNotValid)
     } else {
         Ok(())
@@ -202,6 +245,15 @@ mod switchboard {
         ) -> Result<Ref<'info, AggregatorAccountData>> {
             let data = switchboard_feed.try_borrow_data()?;
 
+            let size = 8 + std::mem::size_of::<AggregatorAccountData>();
+            if data.len() < size {
+                msg!(
+                    "Switchboard aggregator account is too small: {} bytes",
+                    data.len()
+                );
+                return err!(ScopeError::SwitchboardV2Error);
+            }
+
             let mut disc_bytes = [0u8; 8];
             disc_bytes.copy_from_slice(&data[..8]);
             if disc_bytes != AggregatorAccountData::discriminator() {
@@ -212,7 +264,7 @@ mod switchboard {
                 return err!(ScopeError::SwitchboardV2Error);
             }
 
-            Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..])))
+            Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..size])))
         }
 
         pub fn get_result(&self) -> std::result::Result<SwitchboardDecimal, ScopeError> {
@@ -284,6 +336,22 @@ mod tests {
     use proptest::prelude::*;
 
     use super::*;
+    use crate::oracles::DEFAULT_CONFIDENCE_BPS;
+
+    fn validate_confidence(
+        price_mantissa: i128,
+        price_scale: u32,
+        stdev_mantissa: i128,
+        stdev_scale: u32,
+    ) -> std::result::Result<(), ScopeError> {
+        super::validate_confidence(
+            price_mantissa,
+            price_scale,
+            stdev_mantissa,
+            stdev_scale,
+            DEFAULT_CONFIDENCE_BPS,
+        )
+    }
 
     const U64_MAX: i128 = std::u64::MAX as i128;
 
@@ -324,6 +392,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn std_deviation_with_price_exponent() {
+        assert_eq!(scale_std_deviation(1234, 3, 6), 1_234_000);
+        assert_eq!(scale_std_deviation(1234, 3, 1), 12);
+        assert_eq!(scale_std_deviation(-1234, 3, 3), 1234);
+        assert_eq!(
+            scale_std_deviation(5_000_000_000_000_000_000_000_000, 28, 5),
+            50
+        );
+        assert_eq!(scale_std_deviation(1, 0, 30), u64::MAX);
+    }
+
     #[test]
     fn test_valid_switchboard_v2_price() {
         assert!(validate_confidence(1, 1, 0, 1).is_ok());
@@ -387,6 +467,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_switchboard_v2_price_custom_confidence() {
+        // stdev at 5% of the price
+        assert!(super::validate_confidence(100_000, 3, 5_000, 3, 500).is_err());
+        assert!(super::validate_confidence(100_000, 3, 4_999, 3, 500).is_ok());
+        assert!(super::validate_confidence(100_000, 3, 5_000, 3, 501).is_ok());
+        // stdev at 0.5% of the price with a different scale
+        assert!(super::validate_confidence(100_000, 3, 5_000, 4, 50).is_err());
+        assert!(super::validate_confidence(100_000, 3, 4_999, 4, 50).is_ok());
+    }
+
     // Failure cases
 
     #[test]
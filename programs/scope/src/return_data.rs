@@ -0,0 +1,87 @@
+//! Typed access to the prices returned by the `get_prices` instruction
+//!
+//! Refresh instructions cannot be called through CPI, a program refreshing prices in the same
+//! transaction can read them back with `get_prices` instead of deserializing `OraclePrices`.
+
+use std::mem::size_of;
+
+use anchor_lang::prelude::*;
+use solana_program::program::{get_return_data, MAX_RETURN_DATA};
+
+use crate::{DatedPrice, ScopeError, ScopeResult};
+
+/// Max number of prices that fit in the return data of a single `get_prices` instruction
+pub const MAX_RETURNED_PRICES: usize = MAX_RETURN_DATA / size_of::<DatedPrice>();
+
+/// Decode the return data of a `get_prices` instruction, prices are in the requested order.
+pub fn decode_prices(data: &[u8]) -> ScopeResult<Vec<DatedPrice>> {
+    if data.is_empty() || data.len() % size_of::<DatedPrice>() != 0 {
+        return Err(ScopeError::ConversionFailure);
+    }
+    // Return data is not guaranteed to be aligned
+    Ok(data
+        .chunks_exact(size_of::<DatedPrice>())
+        .map(bytemuck::pod_read_unaligned)
+        .collect())
+}
+
+/// Read the prices returned by the last `get_prices` instruction invoked by the current program.
+pub fn get_returned_prices() -> ScopeResult<Vec<DatedPrice>> {
+    let (program_id, data) = get_return_data().ok_or(ScopeError::ConversionFailure)?;
+    if program_id != crate::ID {
+        return Err(ScopeError::UnexpectedAccount);
+    }
+    decode_prices(&data)
+}
+
+/// Invoke `get_prices` and decode its result.
+#[cfg(feature = "cpi")]
+pub fn get_prices<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetPrices<'info>>,
+    tokens: Vec<u16>,
+) -> Result<Vec<DatedPrice>> {
+    crate::cpi::get_prices(ctx, tokens)?;
+    Ok(get_returned_prices()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Price;
+
+    fn dated_price(value: u64, index: u16) -> DatedPrice {
+        DatedPrice {
+            price: Price { value, exp: 6 },
+            last_updated_slot: 10,
+            index,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn max_returned_prices() {
+        assert_eq!(MAX_RETURNED_PRICES, 18);
+    }
+
+    #[test]
+    fn decode_returned_prices() {
+        let prices = vec![dated_price(1, 3), dated_price(2, 0)];
+        let data: &[u8] = bytemuck::cast_slice(&prices);
+        assert_eq!(decode_prices(data).unwrap(), prices);
+
+        // Unaligned buffer
+        let mut unaligned = vec![0u8];
+        unaligned.extend_from_slice(data);
+        assert_eq!(decode_prices(&unaligned[1..]).unwrap(), prices);
+    }
+
+    #[test]
+    fn decode_invalid_return_data() {
+        let data: &[u8] = bytemuck::bytes_of(&dated_price(1, 0));
+        assert_eq!(
+            decode_prices(&data[1..]),
+            Err(ScopeError::ConversionFailure)
+        );
+        assert_eq!(decode_prices(&[]), Err(ScopeError::ConversionFailure));
+    }
+}
@@ -0,0 +1,191 @@
+//! Price arithmetic helpers shared by the oracles computing a price from other prices
+
+use decimal_wad::rate::U128;
+
+use crate::{Price, ScopeError, ScopeResult};
+
+/// Return `10^exp` as a `U128`, `None` if it does not fit
+pub fn ten_pow(exp: u64) -> Option<U128> {
+    let exp: u32 = exp.try_into().ok()?;
+    U128::from(10u128).checked_pow(exp.into())
+}
+
+/// Get the value of `price` expressed with `exp` decimals
+///
+/// Returns `None` if the value would overflow.
+/// Decimals are truncated if `exp` is lower than the price exponent.
+pub fn price_value_with_exp(price: &Price, exp: u64) -> Option<U128> {
+    let value = U128::from(price.value);
+    if exp >= price.exp {
+        value.checked_mul(ten_pow(exp - price.exp)?)
+    } else {
+        value.checked_div(ten_pow(price.exp - exp)?)
+    }
+}
+
+/// Build a price from a value with `exp` decimals that may not fit in a `u64`
+///
+/// Decimals are dropped until the value fits.
+pub fn price_from_u128(value: U128, exp: u64) -> ScopeResult<Price> {
+    let mut value = value;
+    let mut exp = exp;
+    while value > U128::from(u64::MAX) {
+        if exp == 0 {
+            return Err(ScopeError::IntegerOverflow);
+        }
+        value /= U128::from(10u64);
+        exp -= 1;
+    }
+    Ok(Price {
+        value: value.as_u64(),
+        exp,
+    })
+}
+
+/// Decimals added to the numerator of a division to keep the precision of the result
+const DIV_EXTRA_DECIMALS: u64 = 18;
+
+/// Divide the `numerator` price by the `denominator` price
+///
+/// The result keeps as many decimals as fit in a `u64`.
+pub fn price_div(numerator: &Price, denominator: &Price) -> ScopeResult<Price> {
+    if denominator.value == 0 {
+        return Err(ScopeError::MathOverflow);
+    }
+    // (n / 10^n_exp) / (d / 10^d_exp) = (n * 10^extra / d) / 10^(n_exp + extra - d_exp)
+    // `n * 10^extra` cannot overflow as `n` is a `u64`
+    let exp = numerator
+        .exp
+        .checked_add(DIV_EXTRA_DECIMALS)
+        .and_then(|exp| exp.checked_sub(denominator.exp))
+        .ok_or(ScopeError::MathOverflow)?;
+    let scaled_numerator = U128::from(numerator.value)
+        * ten_pow(DIV_EXTRA_DECIMALS).ok_or(ScopeError::MathOverflow)?;
+    price_from_u128(scaled_numerator / U128::from(denominator.value), exp)
+}
+
+/// Tell if the relative change from `previous` to `new` is at most `max_change_bps`
+pub fn is_price_change_within(previous: &Price, new: &Price, max_change_bps: u64) -> bool {
+    let exp = previous.exp.max(new.exp);
+    let (previous, new) = match (
+        price_value_with_exp(previous, exp),
+        price_value_with_exp(new, exp),
+    ) {
+        (Some(previous), Some(new)) => (previous, new),
+        _ => return false,
+    };
+    let change = if new > previous {
+        new - previous
+    } else {
+        previous - new
+    };
+    // An overflow of the allowed change means any change is accepted
+    let allowed = previous.checked_mul(U128::from(max_change_bps));
+    change
+        .checked_mul(U128::from(10_000u64))
+        .map_or(false, |change| {
+            allowed.map_or(true, |allowed| change <= allowed)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_price_up_and_down() {
+        let price = Price {
+            value: 123_456,
+            exp: 3,
+        };
+        assert_eq!(
+            price_value_with_exp(&price, 6),
+            Some(U128::from(123_456_000u64))
+        );
+        assert_eq!(price_value_with_exp(&price, 1), Some(U128::from(1_234u64)));
+        assert_eq!(
+            price_value_with_exp(&price, 3),
+            Some(U128::from(123_456u64))
+        );
+        assert_eq!(price_value_with_exp(&price, 100), None);
+    }
+
+    #[test]
+    fn reduce_precision_when_too_large() {
+        let value = U128::from(u64::MAX) * U128::from(100u64);
+        let price = price_from_u128(value, 10).unwrap();
+        assert_eq!(price.exp, 8);
+        assert_eq!(price.value, u64::MAX);
+
+        let price = price_from_u128(U128::from(42u64), 2).unwrap();
+        assert_eq!(price, Price { value: 42, exp: 2 });
+
+        assert_eq!(
+            price_from_u128(U128::from(u64::MAX) + U128::from(1u64), 0),
+            Err(ScopeError::IntegerOverflow)
+        );
+    }
+
+    #[test]
+    fn divide_prices() {
+        // 1 / 20
+        let one = Price { value: 1, exp: 0 };
+        let sol = Price {
+            value: 2_000_000_000,
+            exp: 8,
+        };
+        let price = price_div(&one, &sol).unwrap();
+        assert_eq!(price_value_with_exp(&price, 2), Some(U128::from(5u64)));
+        // 22 / 20 with different exponents
+        let msol = Price {
+            value: 22_000,
+            exp: 3,
+        };
+        let price = price_div(&msol, &sol).unwrap();
+        assert_eq!(price_value_with_exp(&price, 2), Some(U128::from(110u64)));
+        // Large result keeps less decimals
+        let large = Price {
+            value: 1_000_000_000_000,
+            exp: 0,
+        };
+        let price = price_div(&large, &one).unwrap();
+        assert_eq!(price.exp, 7);
+        assert_eq!(
+            price_value_with_exp(&price, 0),
+            Some(U128::from(1_000_000_000_000u64))
+        );
+        // Result does not fit
+        let max = Price {
+            value: u64::MAX,
+            exp: 0,
+        };
+        let tiny = Price { value: 1, exp: 6 };
+        assert_eq!(price_div(&max, &tiny), Err(ScopeError::IntegerOverflow));
+        // Division by zero
+        let zero = Price { value: 0, exp: 0 };
+        assert_eq!(price_div(&one, &zero), Err(ScopeError::MathOverflow));
+    }
+
+    #[test]
+    fn price_change_within_bounds() {
+        let previous = Price {
+            value: 1_000,
+            exp: 2,
+        };
+        // +1% with a different exponent
+        let new = Price {
+            value: 101_000,
+            exp: 4,
+        };
+        assert!(is_price_change_within(&previous, &new, 100));
+        assert!(!is_price_change_within(&previous, &new, 99));
+        // -1%
+        let new = Price { value: 990, exp: 2 };
+        assert!(is_price_change_within(&previous, &new, 100));
+        assert!(!is_price_change_within(&previous, &new, 99));
+        // No change
+        assert!(is_price_change_within(&previous, &previous, 0));
+        // Unlimited change
+        assert!(is_price_change_within(&previous, &new, u64::MAX));
+    }
+}
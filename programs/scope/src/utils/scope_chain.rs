@@ -5,8 +5,14 @@
 //!
 //! This tools used for ktokens for now but can be reused in the future for other price based on others available in scope.
 //!
-//! An account can store up to `crate::MAX_ENTRIES` chains.
-//! One chain is composed of at most 4 prices.
+//! An account can store up to `crate::MAX_ENTRIES` chains, one per token of the program using it.
+//! One chain is composed of at most 4 prices, which can be any entry of the feed: chains are read
+//! from all the entries of a resized feed when its prices are loaded with
+//! [`crate::extension::load`].
+//!
+//! Unused links are set to [`UNUSED_LINK`]. Chains written before feeds could be resized end with
+//! `MAX_ENTRIES`, which is a valid entry of a resized feed: they must be written again before
+//! being read from the entries of a resized feed.
 //!
 //! ## Example
 //!
@@ -87,11 +93,14 @@ use bytemuck;
 use decimal_wad::rate::U128;
 pub use strum::IntoEnumIterator;
 
-use crate::{DatedPrice, OraclePrices, Price, ScopeError, MAX_ENTRIES};
+use crate::{extension::FeedPrices, DatedPrice, Price, ScopeError, MAX_ENTRIES};
 
 /// Maximum length of a chain (4 so the size of one chain is the same as `u64`)
 pub const MAX_CHAIN_LENGTH: usize = 4;
 
+/// Index of the unused links at the end of a chain
+pub const UNUSED_LINK: u16 = u16::MAX;
+
 type RawChain = [u16; MAX_CHAIN_LENGTH];
 
 #[derive(Clone, Debug)]
@@ -126,7 +135,7 @@ where
         for (u16_id, t_id) in res.iter_mut().zip(chain.0.iter()) {
             *u16_id = match t_id {
                 Some(v) => (*v).into(),
-                None => MAX_ENTRIES as u16,
+                None => UNUSED_LINK,
             }
         }
         res
@@ -140,7 +149,7 @@ where
     fn from(chain: PriceChain<T>) -> Self {
         chain.0.map(|v| match v {
             Some(v) => v.into(),
-            None => MAX_ENTRIES as u16,
+            None => UNUSED_LINK,
         })
     }
 }
@@ -181,11 +190,11 @@ impl Debug for ScopeChainAccount {
         let chain_vec: Vec<Vec<u16>> = self
             .chain_array
             .iter()
-            .take_while(|chain| usize::from(chain[0]) < MAX_ENTRIES)
+            .take_while(|chain| chain[0] != UNUSED_LINK)
             .map(|chain| {
                 chain
                     .iter()
-                    .take_while(|&idx| usize::from(*idx) < MAX_ENTRIES)
+                    .take_while(|&&idx| idx != UNUSED_LINK)
                     .copied()
                     .collect::<Vec<u16>>()
             })
@@ -200,7 +209,7 @@ impl Debug for ScopeChainAccount {
 impl Default for ScopeChainAccount {
     fn default() -> Self {
         Self {
-            chain_array: [[MAX_ENTRIES.try_into().unwrap(); MAX_CHAIN_LENGTH]; MAX_ENTRIES],
+            chain_array: [[UNUSED_LINK; MAX_CHAIN_LENGTH]; MAX_ENTRIES],
         }
     }
 }
@@ -241,7 +250,7 @@ impl ScopeChainAccount {
         }
         // Set all remaining to default value
         for dst in chain_iter_mut.flatten() {
-            *dst = MAX_ENTRIES as u16;
+            *dst = UNUSED_LINK;
         }
         Ok(())
     }
@@ -271,14 +280,14 @@ impl ScopeChainAccount {
         }
         // Set all remaining to default value
         for dst in chain_iter_mut.flatten() {
-            *dst = MAX_ENTRIES as u16;
+            *dst = UNUSED_LINK;
         }
         Ok(())
     }
 
     pub fn get_price(
         &self,
-        prices: &OraclePrices,
+        prices: &impl FeedPrices,
         token_id: usize,
     ) -> Result<DatedPrice, ScopeChainError> {
         let chain = self
@@ -290,10 +299,13 @@ impl ScopeChainAccount {
 }
 
 pub fn get_price_from_chain(
-    prices: &OraclePrices,
+    prices: &impl FeedPrices,
     chain: &[u16; MAX_CHAIN_LENGTH],
 ) -> Result<DatedPrice, ScopeChainError> {
-    let price_chain = chain.map(usize::from).map(|id| prices.prices.get(id));
+    let price_chain = chain.map(|id| match id {
+        UNUSED_LINK => None,
+        id => prices.entry(usize::from(id)),
+    });
 
     let last_updated_slot = price_chain
         .iter()
@@ -318,7 +330,7 @@ pub fn get_price_from_chain(
         .iter()
         .filter_map(|&opt| opt.map(|price| price.price.exp))
         .last()
-        .unwrap(); // chain is never empty here by construction
+        .ok_or(ScopeChainError::NoChainForToken)?;
 
     // Compute token value by multiplying all value of the chain
     let product = price_chain
@@ -330,13 +342,17 @@ pub fn get_price_from_chain(
         .ok_or(ScopeChainError::MathOverflow)?;
 
     // Compute final value by removing extra decimals
-    let scale_down_decimals: u32 = total_decimals.checked_sub(exp).unwrap().try_into().unwrap(); // Cannot fail by construction of `total_decimals`
+    // Exponents are read from the prices of the feed, they are not trusted to be small
+    let scale_down_decimals: u32 = total_decimals
+        .checked_sub(exp)
+        .and_then(|decimals| decimals.try_into().ok())
+        .ok_or(ScopeChainError::MathOverflow)?;
     let scale_down_factor = U128::from(10u128)
         .checked_pow(U128::from(scale_down_decimals))
-        .unwrap();
+        .ok_or(ScopeChainError::MathOverflow)?;
     let value: u64 = product
         .checked_div(scale_down_factor)
-        .unwrap() // Cannot fail thanks to the early return
+        .ok_or(ScopeChainError::MathOverflow)?
         .try_into()
         .map_err(|_| ScopeChainError::IntegerConversionOverflow)?;
 
@@ -384,7 +400,7 @@ mod test {
     use super::{PriceChain, ScopeChainAccount, ScopeChainError};
     use crate::{
         scope_chain::{get_price_from_chain, MAX_CHAIN_LENGTH},
-        DatedPrice, OraclePrices,
+        DatedPrice, OraclePrices, Price,
     };
 
     #[test]
@@ -531,6 +547,39 @@ mod test {
         assert_eq!(dated_price.last_updated_slot, T0_SLOT);
     }
 
+    #[test]
+    fn price_from_chain_with_large_exponent() {
+        let mut scope_prices = get_test_scope_prices();
+        let sol_usdh: usize = ScopeId::SOL_USDH.into();
+        scope_prices.prices[sol_usdh].price = Price { value: 1, exp: 40 };
+        let chain: [u16; MAX_CHAIN_LENGTH] = [
+            ScopeId::SOL_USDH.into(),
+            ScopeId::USDH.into(),
+            u16::MAX,
+            u16::MAX,
+        ];
+
+        assert!(matches!(
+            get_price_from_chain(&scope_prices, &chain),
+            Err(ScopeChainError::MathOverflow)
+        ));
+    }
+
+    #[test]
+    fn price_from_chain_with_resized_feed_entry() {
+        let fixed_prices = get_test_scope_prices();
+        let mut scope_prices = fixed_prices.prices.to_vec();
+        scope_prices.push(DatedPrice::default());
+        scope_prices.push(fixed_prices.prices[usize::from(ScopeId::SOL_USDH)]);
+        let extended_entry = u16::try_from(crate::MAX_ENTRIES + 1).unwrap();
+        let chain: [u16; MAX_CHAIN_LENGTH] =
+            [extended_entry, ScopeId::USDH.into(), u16::MAX, u16::MAX];
+        let dated_price = get_price_from_chain(&scope_prices, &chain).unwrap();
+
+        assert_eq!(dated_price.price.value, 100 * 100 * 10_u64.pow(8));
+        assert_eq!(dated_price.price.exp, 8);
+    }
+
     #[test]
     fn one_token_chain() {
         let scope_prices = get_test_scope_prices();
@@ -42,8 +42,13 @@ pub async fn setup_scope(
         conf: configuration_acc,
         mapping: zero_copy_accounts.mapping.pubkey(),
         prices: zero_copy_accounts.prices.pubkey(),
+        twaps: zero_copy_accounts.twaps.pubkey(),
     };
 
+    // Record the TWAPs of the feed
+    let ix = operations::init_oracle_twaps_ix(&ctx.admin.pubkey(), &feed, &feed.twaps);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
     // Set up the mapping and oracles
     for conf in mapping {
         // Initialize oracle account
@@ -54,3 +59,18 @@ pub async fn setup_scope(
 
     (ctx, feed)
 }
+
+/// Set up a feed with a single entry, mapped once its oracle holds `price`
+///
+/// [`setup_scope`] maps the entries while their oracles hold an empty price, which is rejected by
+/// the oracle types validating the price account when it is mapped.
+pub async fn setup_scope_with_price(
+    feed_name: &str,
+    conf: &OracleConf,
+    price: &Price,
+) -> (TestContext, types::ScopeFeedDefinition) {
+    let (mut ctx, feed) = setup_scope(feed_name, Vec::new()).await;
+    mock_oracles::set_price(&mut ctx, &feed, conf, price).await;
+    operations::update_oracle_mapping(&mut ctx, &feed, conf).await;
+    (ctx, feed)
+}
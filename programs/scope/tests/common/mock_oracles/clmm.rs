@@ -0,0 +1,127 @@
+use anchor_lang::{
+    prelude::{borsh, Pubkey},
+    Discriminator,
+};
+use scope::{oracles::clmm::ClmmPool, Price};
+use yvaults::{
+    raydium_amm_v3::states::{ObservationState, PoolState},
+    whirlpool::state::Whirlpool,
+};
+
+use super::constant_product_lp::{get_mint_data, pool_accounts, TOKEN_PROGRAM_ID};
+
+/// Decimals of both tokens of the pools set by [`get_pool_and_accounts_for_price`]
+pub const DECIMALS: u8 = 6;
+
+pub fn id(pool: ClmmPool) -> Pubkey {
+    // Owner is checked by scope
+    pool.program_id()
+}
+
+/// Mints of token A and B of a mock pool, derived from the pool address
+pub fn mints(pool: &Pubkey) -> (Pubkey, Pubkey) {
+    let accounts = pool_accounts(pool);
+    (accounts.mint_a, accounts.mint_b)
+}
+
+/// Observation account of a mock raydium pool, derived from the pool address
+pub fn observation(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"observation", pool.as_ref()], &id(ClmmPool::RaydiumClmm)).0
+}
+
+/// Observation account of a raydium pool recording the given `(timestamp, sqrt_price_x64)`,
+/// newest first
+///
+/// The newest observation is at index 0 of the ring buffer, the observation index of the mock
+/// pools, so older observations wrap around to the end of the buffer.
+pub fn get_observation_data(pool_pk: &Pubkey, observations: &[(u32, u128)]) -> Vec<u8> {
+    let mut state: ObservationState = bytemuck::Zeroable::zeroed();
+    state.pool_id = *pool_pk;
+    let len = state.observations.len();
+    for (age, (timestamp, sqrt_price_x64)) in observations.iter().enumerate() {
+        let index = (len - age) % len;
+        state.observations[index].block_timestamp = *timestamp;
+        state.observations[index].sqrt_price_x64 = *sqrt_price_x64;
+    }
+    let mut data = ObservationState::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&state));
+    data
+}
+
+pub fn get_pool_data(
+    pool: ClmmPool,
+    pool_pk: &Pubkey,
+    sqrt_price_x64: u128,
+    liquidity: u128,
+) -> Vec<u8> {
+    let (mint_a, mint_b) = mints(pool_pk);
+    match pool {
+        ClmmPool::OrcaWhirlpool => {
+            let mut data = [0u8; Whirlpool::LEN];
+            data[0..8].copy_from_slice(&Whirlpool::discriminator());
+            let whirlpool = Whirlpool {
+                sqrt_price: sqrt_price_x64,
+                liquidity,
+                token_mint_a: mint_a,
+                token_mint_b: mint_b,
+                ..Default::default()
+            };
+            let bytes = borsh::to_vec(&whirlpool).unwrap();
+            data[8..].copy_from_slice(&bytes);
+            data.to_vec()
+        }
+        ClmmPool::RaydiumClmm => {
+            let mut data = [0u8; PoolState::LEN];
+            data[0..8].copy_from_slice(&PoolState::discriminator());
+            let state = PoolState {
+                sqrt_price_x64,
+                liquidity,
+                token_mint_0: mint_a,
+                token_mint_1: mint_b,
+                observation_key: observation(pool_pk),
+                ..Default::default()
+            };
+            data[8..].copy_from_slice(bytemuck::bytes_of(&state));
+            data.to_vec()
+        }
+    }
+}
+
+/// Pool and mint accounts (address, owner, data) of a pool at the given sqrt price
+pub fn get_pool_and_accounts(
+    pool: ClmmPool,
+    pool_pk: &Pubkey,
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> (Vec<u8>, Vec<(Pubkey, Pubkey, Vec<u8>)>) {
+    let (mint_a, mint_b) = mints(pool_pk);
+    let mint_accounts = vec![
+        (
+            mint_a,
+            TOKEN_PROGRAM_ID,
+            get_mint_data(u64::MAX, decimals_a),
+        ),
+        (
+            mint_b,
+            TOKEN_PROGRAM_ID,
+            get_mint_data(u64::MAX, decimals_b),
+        ),
+    ];
+    (
+        get_pool_data(pool, pool_pk, sqrt_price_x64, liquidity),
+        mint_accounts,
+    )
+}
+
+/// Deep pool of two tokens with [`DECIMALS`] decimals where token A is worth `price` token B
+pub fn get_pool_and_accounts_for_price(
+    pool: ClmmPool,
+    pool_pk: &Pubkey,
+    price: &Price,
+) -> (Vec<u8>, Vec<(Pubkey, Pubkey, Vec<u8>)>) {
+    let price = price.value as f64 / 10f64.powi(price.exp.try_into().unwrap());
+    let sqrt_price_x64 = (price.sqrt() * 2f64.powi(64)) as u128;
+    get_pool_and_accounts(pool, pool_pk, sqrt_price_x64, 1 << 80, DECIMALS, DECIMALS)
+}
@@ -0,0 +1,144 @@
+use anchor_lang::prelude::Pubkey;
+use scope::{
+    oracles::constant_product_lp::{
+        orca, raydium, ConstantProductPool, PoolAccounts, TokenAmount, ORCA_TOKEN_SWAP_PROGRAM_IDS,
+        RAYDIUM_AMM_V4_PROGRAM_ID,
+    },
+    Price,
+};
+use solana_sdk::pubkey;
+
+/// SPL token program, owner of the vaults and mints
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+const TOKEN_ACCOUNT_SIZE: usize = 165;
+const MINT_SIZE: usize = 82;
+
+pub fn id(pool: ConstantProductPool) -> Pubkey {
+    // Owner is checked by scope
+    match pool {
+        ConstantProductPool::OrcaTokenSwap => ORCA_TOKEN_SWAP_PROGRAM_IDS[0],
+        ConstantProductPool::RaydiumAmmV4 => RAYDIUM_AMM_V4_PROGRAM_ID,
+    }
+}
+
+/// Vaults and mints of a mock pool, derived from the pool address
+pub fn pool_accounts(pool: &Pubkey) -> PoolAccounts {
+    let derive =
+        |seed: &[u8]| Pubkey::find_program_address(&[seed, pool.as_ref()], &TOKEN_PROGRAM_ID).0;
+    PoolAccounts {
+        vault_a: derive(b"vault_a"),
+        vault_b: derive(b"vault_b"),
+        mint_a: derive(b"mint_a"),
+        mint_b: derive(b"mint_b"),
+        lp_mint: derive(b"lp_mint"),
+    }
+}
+
+fn write(data: &mut [u8], offset: usize, key: &Pubkey) {
+    data[offset..offset + 32].copy_from_slice(key.as_ref());
+}
+
+pub fn get_pool_data(pool: ConstantProductPool, accounts: &PoolAccounts) -> Vec<u8> {
+    match pool {
+        ConstantProductPool::OrcaTokenSwap => {
+            let mut data = vec![0; orca::SWAP_SIZE];
+            data[0] = orca::SWAP_V1_VERSION;
+            data[orca::IS_INITIALIZED_OFFSET] = 1;
+            data[orca::CURVE_TYPE_OFFSET] = orca::CONSTANT_PRODUCT_CURVE;
+            write(&mut data, orca::VAULT_A_OFFSET, &accounts.vault_a);
+            write(&mut data, orca::VAULT_B_OFFSET, &accounts.vault_b);
+            write(&mut data, orca::MINT_A_OFFSET, &accounts.mint_a);
+            write(&mut data, orca::MINT_B_OFFSET, &accounts.mint_b);
+            write(&mut data, orca::LP_MINT_OFFSET, &accounts.lp_mint);
+            data
+        }
+        ConstantProductPool::RaydiumAmmV4 => {
+            let mut data = vec![0; raydium::AMM_INFO_SIZE];
+            // Initialized status
+            data[raydium::STATUS_OFFSET] = 1;
+            write(&mut data, raydium::COIN_VAULT_OFFSET, &accounts.vault_a);
+            write(&mut data, raydium::PC_VAULT_OFFSET, &accounts.vault_b);
+            write(&mut data, raydium::COIN_MINT_OFFSET, &accounts.mint_a);
+            write(&mut data, raydium::PC_MINT_OFFSET, &accounts.mint_b);
+            write(&mut data, raydium::LP_MINT_OFFSET, &accounts.lp_mint);
+            data
+        }
+    }
+}
+
+pub fn get_token_account_data(mint: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0; TOKEN_ACCOUNT_SIZE];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    // Initialized state
+    data[108] = 1;
+    data
+}
+
+pub fn get_mint_data(supply: u64, decimals: u8) -> Vec<u8> {
+    let mut data = vec![0; MINT_SIZE];
+    data[36..44].copy_from_slice(&supply.to_le_bytes());
+    data[44] = decimals;
+    // Initialized
+    data[45] = 1;
+    data
+}
+
+/// Pool and extra accounts (address, owner, data) holding the given reserves and LP supply
+pub fn get_pool_and_accounts(
+    pool: ConstantProductPool,
+    pool_pk: &Pubkey,
+    reserve_a: &TokenAmount,
+    reserve_b: &TokenAmount,
+    lp_supply: &TokenAmount,
+) -> (Vec<u8>, Vec<(Pubkey, Pubkey, Vec<u8>)>) {
+    let accounts = pool_accounts(pool_pk);
+    let token_accounts = vec![
+        (
+            accounts.vault_a,
+            TOKEN_PROGRAM_ID,
+            get_token_account_data(&accounts.mint_a, reserve_a.amount),
+        ),
+        (
+            accounts.vault_b,
+            TOKEN_PROGRAM_ID,
+            get_token_account_data(&accounts.mint_b, reserve_b.amount),
+        ),
+        (
+            accounts.mint_a,
+            TOKEN_PROGRAM_ID,
+            get_mint_data(u64::MAX, reserve_a.decimals),
+        ),
+        (
+            accounts.mint_b,
+            TOKEN_PROGRAM_ID,
+            get_mint_data(u64::MAX, reserve_b.decimals),
+        ),
+        (
+            accounts.lp_mint,
+            TOKEN_PROGRAM_ID,
+            get_mint_data(lp_supply.amount, lp_supply.decimals),
+        ),
+    ];
+    (get_pool_data(pool, &accounts), token_accounts)
+}
+
+/// Pool holding `price` of each token (with `price.exp` decimals) for one LP token
+///
+/// With both tokens priced at 1, the LP token is worth `2 * price`.
+pub fn get_pool_and_accounts_for_price(
+    pool: ConstantProductPool,
+    pool_pk: &Pubkey,
+    price: &Price,
+) -> (Vec<u8>, Vec<(Pubkey, Pubkey, Vec<u8>)>) {
+    let reserve = TokenAmount {
+        amount: price.value,
+        decimals: price.exp.try_into().unwrap(),
+    };
+    let lp_supply = TokenAmount {
+        amount: 1_000_000,
+        decimals: 6,
+    };
+    get_pool_and_accounts(pool, pool_pk, &reserve, &reserve, &lp_supply)
+}
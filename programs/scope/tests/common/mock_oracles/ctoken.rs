@@ -0,0 +1,47 @@
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    solana_program::program_pack::Pack,
+};
+use scope::{
+    oracles::ctokens::{
+        solend::{LastUpdate, Reserve, ReserveCollateral, ReserveLiquidity, PROGRAM_VERSION},
+        SOLEND_PROGRAM_ID,
+    },
+    Price,
+};
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    SOLEND_PROGRAM_ID
+}
+
+pub fn get_reserve(price: &Price, clock: &Clock) -> Reserve {
+    Reserve {
+        version: PROGRAM_VERSION,
+        last_update: LastUpdate {
+            slot: clock.slot,
+            stale: false,
+        },
+        lending_market: Pubkey::new_unique(),
+        liquidity: ReserveLiquidity {
+            mint_pubkey: Pubkey::new_unique(),
+            available_amount: price.value,
+            ..Default::default()
+        },
+        collateral: ReserveCollateral {
+            mint_total_supply: 10_u64.pow(price.exp.try_into().unwrap()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+pub fn get_account_data(reserve: &Reserve) -> Vec<u8> {
+    let mut data = vec![0; Reserve::LEN];
+    reserve.pack_into_slice(&mut data);
+    data
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock) -> Vec<u8> {
+    get_account_data(&get_reserve(price, clock))
+}
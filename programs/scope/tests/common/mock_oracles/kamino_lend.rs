@@ -0,0 +1,42 @@
+use anchor_lang::prelude::{Clock, Pubkey};
+use scope::{
+    oracles::kamino_lend::{
+        Reserve, AVAILABLE_AMOUNT_OFFSET, COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET,
+        KAMINO_LEND_PROGRAM_ID, LAST_UPDATE_SLOT_OFFSET, RESERVE_DISCRIMINATOR,
+    },
+    Price,
+};
+
+/// Size of a Kamino Lend `Reserve` account
+const RESERVE_SIZE: usize = 8624;
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    KAMINO_LEND_PROGRAM_ID
+}
+
+pub fn get_reserve(price: &Price, clock: &Clock) -> Reserve {
+    Reserve {
+        last_update_slot: clock.slot,
+        available_amount: price.value,
+        collateral_mint_total_supply: 10_u64.pow(price.exp.try_into().unwrap()),
+        ..Default::default()
+    }
+}
+
+// Only the fields used by scope are written, nothing is borrowed
+pub fn get_account_data(reserve: &Reserve) -> Vec<u8> {
+    let mut data = vec![0; RESERVE_SIZE];
+    data[..8].copy_from_slice(&RESERVE_DISCRIMINATOR);
+    data[LAST_UPDATE_SLOT_OFFSET..LAST_UPDATE_SLOT_OFFSET + 8]
+        .copy_from_slice(&reserve.last_update_slot.to_le_bytes());
+    data[AVAILABLE_AMOUNT_OFFSET..AVAILABLE_AMOUNT_OFFSET + 8]
+        .copy_from_slice(&reserve.available_amount.to_le_bytes());
+    data[COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET..COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET + 8]
+        .copy_from_slice(&reserve.collateral_mint_total_supply.to_le_bytes());
+    data
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock) -> Vec<u8> {
+    get_account_data(&get_reserve(price, clock))
+}
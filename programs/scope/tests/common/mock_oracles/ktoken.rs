@@ -21,8 +21,8 @@ use crate::common::{
 };
 
 pub const fn id() -> Pubkey {
-    // It does not matter what the pubkey is
-    pubkey!("Kamino1111111111111111111111111111111111111")
+    // Owner of the strategy is checked by scope
+    kamino::ID
 }
 
 pub async fn get_ktoken_price_accounts(
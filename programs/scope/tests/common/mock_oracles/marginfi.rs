@@ -0,0 +1,39 @@
+use anchor_lang::prelude::{Clock, Pubkey};
+use scope::{
+    oracles::marginfi::{
+        Bank, ASSET_SHARE_VALUE_OFFSET, BANK_DISCRIMINATOR, I80F48_FRACTIONAL_BITS,
+        LAST_UPDATE_OFFSET, MARGINFI_PROGRAM_ID,
+    },
+    Price,
+};
+
+/// Size of a Marginfi `Bank` account
+const BANK_SIZE: usize = 1864;
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    MARGINFI_PROGRAM_ID
+}
+
+pub fn get_bank(price: &Price, clock: &Clock) -> Bank {
+    Bank {
+        asset_share_value: (i128::from(price.value) << I80F48_FRACTIONAL_BITS)
+            / 10_i128.pow(price.exp.try_into().unwrap()),
+        last_update: clock.unix_timestamp,
+    }
+}
+
+// Only the fields used by scope are written
+pub fn get_account_data(bank: &Bank) -> Vec<u8> {
+    let mut data = vec![0; BANK_SIZE];
+    data[..8].copy_from_slice(&BANK_DISCRIMINATOR);
+    data[ASSET_SHARE_VALUE_OFFSET..ASSET_SHARE_VALUE_OFFSET + 16]
+        .copy_from_slice(&bank.asset_share_value.to_le_bytes());
+    data[LAST_UPDATE_OFFSET..LAST_UPDATE_OFFSET + 8]
+        .copy_from_slice(&bank.last_update.to_le_bytes());
+    data
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock) -> Vec<u8> {
+    get_account_data(&get_bank(price, clock))
+}
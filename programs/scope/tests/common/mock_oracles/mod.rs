@@ -5,9 +5,19 @@ use solana_program::pubkey::Pubkey;
 use super::types::{OracleConf, TestContext};
 use crate::common::types::{ScopeFeedDefinition, TestOracleType};
 
+#[cfg(feature = "yvaults")]
+pub mod clmm;
+pub mod constant_product_lp;
+pub mod ctoken;
+pub mod kamino_lend;
 #[cfg(feature = "yvaults")]
 mod ktoken;
+pub mod marginfi;
+pub mod msol_stake;
 pub mod pyth;
+pub mod pyth_pull;
+pub mod spl_stake;
+pub mod switchboard_on_demand;
 pub mod switchboard_v2;
 
 #[async_recursion] // kTokens recursively create underlying token mappings
@@ -25,6 +35,56 @@ pub async fn set_price(
                 pyth::id(),
                 vec![],
             ),
+            TestOracleType::PythPull => (
+                pyth_pull::get_account_data_for_price(
+                    price,
+                    &clock,
+                    pyth_pull::feed_id(&conf.pubkey),
+                ),
+                pyth_pull::id(),
+                vec![],
+            ),
+            TestOracleType::SwitchboardOnDemand => (
+                switchboard_on_demand::get_account_data_for_price(price, &clock),
+                switchboard_on_demand::id(),
+                vec![],
+            ),
+            TestOracleType::CToken => (
+                ctoken::get_account_data_for_price(price, &clock),
+                ctoken::id(),
+                vec![],
+            ),
+            TestOracleType::SplStake => (
+                spl_stake::get_account_data_for_price(price, &clock),
+                spl_stake::id(),
+                vec![],
+            ),
+            TestOracleType::MsolStake => (
+                msol_stake::get_account_data_for_price(price, &clock),
+                msol_stake::id(),
+                vec![],
+            ),
+            TestOracleType::KaminoLendCollateral => (
+                kamino_lend::get_account_data_for_price(price, &clock),
+                kamino_lend::id(),
+                vec![],
+            ),
+            TestOracleType::MarginfiCollateral => (
+                marginfi::get_account_data_for_price(price, &clock),
+                marginfi::id(),
+                vec![],
+            ),
+            TestOracleType::ConstantProductLp { pool, .. } => {
+                let (pool_data, accounts) =
+                    constant_product_lp::get_pool_and_accounts_for_price(pool, &conf.pubkey, price);
+                (pool_data, constant_product_lp::id(pool), accounts)
+            }
+            #[cfg(feature = "yvaults")]
+            TestOracleType::ClmmSpot { pool, .. } => {
+                let (pool_data, accounts) =
+                    clmm::get_pool_and_accounts_for_price(pool, &conf.pubkey, price);
+                (pool_data, clmm::id(pool), accounts)
+            }
             TestOracleType::SwitchboardV2 => (
                 switchboard_v2::get_account_data_for_price(price, &clock),
                 switchboard_v2::id(),
@@ -0,0 +1,32 @@
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    AnchorSerialize,
+};
+use scope::{
+    oracles::msol_stake::{State, MARINADE_PROGRAM_ID, MSOL_PRICE_DENOMINATOR},
+    Price,
+};
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    MARINADE_PROGRAM_ID
+}
+
+pub fn get_state(price: &Price, _clock: &Clock) -> State {
+    let msol_price = u128::from(price.value) * u128::from(MSOL_PRICE_DENOMINATOR)
+        / 10_u128.pow(price.exp.try_into().unwrap());
+    State {
+        msol_price: msol_price.try_into().unwrap(),
+        ..Default::default()
+    }
+}
+
+pub fn get_account_data(state: &State) -> Vec<u8> {
+    let mut data = State::discriminator().to_vec();
+    state.serialize(&mut data).unwrap();
+    data
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock) -> Vec<u8> {
+    get_account_data(&get_state(price, clock))
+}
@@ -0,0 +1,46 @@
+use anchor_lang::{prelude::Pubkey, AnchorSerialize, Discriminator};
+use scope::{
+    oracles::pyth_pull::{
+        PriceFeedMessage, PriceUpdateV2, VerificationLevel, PYTH_RECEIVER_PROGRAM_ID,
+    },
+    Price,
+};
+use solana_program::clock::Clock;
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    PYTH_RECEIVER_PROGRAM_ID
+}
+
+/// The feed id of the test price update accounts is their address
+pub fn feed_id(price_update_pk: &Pubkey) -> [u8; 32] {
+    price_update_pk.to_bytes()
+}
+
+pub fn get_price_update(price: &Price, clock: &Clock, feed_id: [u8; 32]) -> PriceUpdateV2 {
+    PriceUpdateV2 {
+        write_authority: Pubkey::default(),
+        verification_level: VerificationLevel::Full,
+        price_message: PriceFeedMessage {
+            feed_id,
+            price: price.value as i64,
+            conf: 0,
+            exponent: -(price.exp as i32),
+            publish_time: clock.unix_timestamp,
+            prev_publish_time: clock.unix_timestamp,
+            ema_price: price.value as i64,
+            ema_conf: 0,
+        },
+        posted_slot: clock.slot,
+    }
+}
+
+pub fn get_account_data(price_update: &PriceUpdateV2) -> Vec<u8> {
+    let mut data = PriceUpdateV2::discriminator().to_vec();
+    price_update.serialize(&mut data).unwrap();
+    data
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock, feed_id: [u8; 32]) -> Vec<u8> {
+    get_account_data(&get_price_update(price, clock, feed_id))
+}
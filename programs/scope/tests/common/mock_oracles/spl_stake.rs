@@ -0,0 +1,29 @@
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    AnchorSerialize,
+};
+use scope::Price;
+use spl_stake_pool::state::{AccountType, StakePool};
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    spl_stake_pool::ID
+}
+
+pub fn get_stake_pool(price: &Price, clock: &Clock) -> StakePool {
+    StakePool {
+        account_type: AccountType::StakePool,
+        total_lamports: price.value,
+        pool_token_supply: 10_u64.pow(price.exp.try_into().unwrap()),
+        last_update_epoch: clock.epoch,
+        ..Default::default()
+    }
+}
+
+pub fn get_account_data(stake_pool: &StakePool) -> Vec<u8> {
+    stake_pool.try_to_vec().unwrap()
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock) -> Vec<u8> {
+    get_account_data(&get_stake_pool(price, clock))
+}
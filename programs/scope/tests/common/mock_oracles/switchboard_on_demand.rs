@@ -0,0 +1,37 @@
+use anchor_lang::prelude::{Clock, Pubkey};
+use scope::{
+    oracles::switchboard_on_demand::{
+        PullFeedAccountData, PRECISION, SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+    },
+    Price,
+};
+
+/// Max age of the test feeds results
+pub const MAX_STALENESS: u32 = 100;
+
+pub const fn id() -> Pubkey {
+    // Owner is checked by scope
+    SWITCHBOARD_ON_DEMAND_PROGRAM_ID
+}
+
+pub fn get_pull_feed(price: &Price, clock: &Clock) -> PullFeedAccountData {
+    let mut pull_feed: PullFeedAccountData = bytemuck::Zeroable::zeroed();
+    pull_feed.min_sample_size = 1;
+    pull_feed.max_staleness = MAX_STALENESS;
+    pull_feed.result.value =
+        i128::from(price.value) * 10_i128.pow(PRECISION - u32::try_from(price.exp).unwrap());
+    pull_feed.result.num_samples = 1;
+    pull_feed.result.slot = clock.slot;
+    pull_feed.last_update_timestamp = clock.unix_timestamp;
+    pull_feed
+}
+
+pub fn get_account_data(pull_feed: &PullFeedAccountData) -> Vec<u8> {
+    let mut data = PullFeedAccountData::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(pull_feed));
+    data
+}
+
+pub fn get_account_data_for_price(price: &Price, clock: &Clock) -> Vec<u8> {
+    get_account_data(&get_pull_feed(price, clock))
+}
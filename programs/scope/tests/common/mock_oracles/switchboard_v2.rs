@@ -1,10 +1,9 @@
 use anchor_lang::prelude::{Clock, Pubkey};
-use scope::Price;
-use solana_sdk::pubkey;
+use scope::{oracles::switchboard_v2::SWITCHBOARD_V2_PROGRAM_ID, Price};
 
 pub const fn id() -> Pubkey {
-    // It does not matter what the pubkey is
-    pubkey!("Switchv211111111111111111111111111111111111")
+    // Owner is checked by scope
+    SWITCHBOARD_V2_PROGRAM_ID
 }
 
 pub fn get_account_data_for_price(_price: &Price, _clock: &Clock) -> Vec<u8> {
@@ -1,7 +1,9 @@
 use anchor_lang::{InstructionData, ToAccountMetas};
+use scope::GENERIC_DATA_SIZE;
 use solana_program::{
     clock::Clock,
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
     sysvar::{instructions::ID as SYSVAR_INSTRUCTIONS_ID, SysvarId},
 };
 use solana_sdk::signature::Signer;
@@ -12,30 +14,125 @@ use crate::common::{
     utils,
 };
 
-pub async fn update_oracle_mapping(
-    ctx: &mut TestContext,
+pub fn init_oracle_twaps_ix(
+    admin: &Pubkey,
+    feed: &types::ScopeFeedDefinition,
+    oracle_twaps: &Pubkey,
+) -> Instruction {
+    let accounts = scope::accounts::InitOracleTwaps {
+        admin: *admin,
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: *oracle_twaps,
+    };
+    let args = scope::instruction::InitOracleTwaps {
+        feed_name: feed.feed_name.clone(),
+    };
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub fn resize_feed_ix(
+    admin: &Pubkey,
+    feed: &types::ScopeFeedDefinition,
+    num_entries: u16,
+) -> Instruction {
+    let accounts = scope::accounts::ResizeFeed {
+        admin: *admin,
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        system_program: solana_program::system_program::id(),
+    };
+    let args = scope::instruction::ResizeFeed {
+        num_entries,
+        feed_name: feed.feed_name.clone(),
+    };
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub fn update_mapping_ix(
+    ctx: &TestContext,
     feed: &types::ScopeFeedDefinition,
     conf: &OracleConf,
-) {
+) -> Instruction {
+    update_mapping_with_generic_data_ix(ctx, feed, conf, conf.generic_data())
+}
+
+pub fn update_mapping_with_generic_data_ix(
+    ctx: &TestContext,
+    feed: &types::ScopeFeedDefinition,
+    conf: &OracleConf,
+    generic_data: [u8; GENERIC_DATA_SIZE],
+) -> Instruction {
     let accounts = scope::accounts::UpdateOracleMapping {
         admin: ctx.admin.pubkey(),
         configuration: feed.conf,
         oracle_mappings: feed.mapping,
         price_info: conf.pubkey,
     };
-    let args = scope::instruction::UpdateMapping {
+    let args = scope::instruction::UpdateMappingAndGenericData {
         feed_name: feed.feed_name.clone(),
         token: conf.token.try_into().unwrap(),
         price_type: conf.price_type.to_u8(),
+        generic_data,
     };
-    let ix = Instruction {
+    Instruction {
         program_id: scope::id(),
         accounts: accounts.to_account_metas(None),
         data: args.data(),
-    };
+    }
+}
+
+pub async fn update_oracle_mapping(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    conf: &OracleConf,
+) {
+    let ix = update_mapping_ix(ctx, feed, conf);
     ctx.send_transaction(&[ix]).await.unwrap();
 }
 
+/// Refresh instruction of one entry, with the given extra accounts
+pub fn refresh_one_ix(
+    feed: &types::ScopeFeedDefinition,
+    conf: &OracleConf,
+    extra_accounts: &[Pubkey],
+) -> Instruction {
+    let mut accounts = scope::accounts::RefreshOne {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
+        price_info: conf.pubkey,
+        clock: Clock::id(),
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        extra_accounts
+            .iter()
+            .map(|pk| AccountMeta::new_readonly(*pk, false)),
+    );
+    let args = scope::instruction::RefreshOnePrice {
+        token: conf.token.try_into().unwrap(),
+    };
+    Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: args.data(),
+    }
+}
+
 pub async fn refresh_price(
     ctx: &mut TestContext,
     feed: &types::ScopeFeedDefinition,
@@ -44,6 +141,8 @@ pub async fn refresh_price(
     let mut accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         price_info: conf.pubkey,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
@@ -12,6 +12,7 @@ use solana_sdk::{
     signer::Signer,
     signers::Signers,
     transaction::Transaction,
+    transaction_context::TransactionReturnData,
 };
 use types::*;
 
@@ -29,6 +30,18 @@ pub fn program() -> ProgramTest {
     prog
 }
 
+/// Wrap the instructions to be executed through the test CPI caller program
+pub fn through_cpi(ixs: &[Instruction]) -> Vec<Instruction> {
+    ixs.iter()
+        .map(|ix| {
+            let mut cpi_accounts = Vec::with_capacity(ix.accounts.len() + 1);
+            cpi_accounts.push(AccountMeta::new_readonly(ix.program_id, false));
+            cpi_accounts.extend_from_slice(&ix.accounts);
+            Instruction::new_with_bytes(TEST_CPI_CALLER_PK, &ix.data, cpi_accounts)
+        })
+        .collect()
+}
+
 pub async fn start(test: ProgramTest, admin: Keypair, bot: Keypair) -> TestContext {
     let mut context = test.start_with_context().await;
     let rent = context.banks_client.get_rent().await.unwrap();
@@ -76,7 +89,10 @@ impl TestContext {
         if account_data[0..8] != T::DISCRIMINATOR {
             return Err(TestError::BadDiscriminator);
         }
-        let data_ref = &account_data[8..];
+        // Only the fixed layout of resized accounts
+        let data_ref = account_data
+            .get(8..8 + std::mem::size_of::<T>())
+            .ok_or(TestError::CannotDeserialize)?;
         bytemuck::try_from_bytes(data_ref)
             .map_err(|_| TestError::CannotDeserialize)
             .copied()
@@ -159,6 +175,14 @@ impl TestContext {
             .unwrap()
     }
 
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.context.warp_to_slot(slot).unwrap();
+    }
+
+    pub fn set_clock(&mut self, clock: &Clock) {
+        self.context.set_sysvar(clock);
+    }
+
     pub async fn get_now_timestamp(&mut self) -> u64 {
         let clock: Clock = self
             .context
@@ -183,15 +207,7 @@ impl TestContext {
         &mut self,
         ixs: &[Instruction],
     ) -> Result<(), BanksClientError> {
-        let instruction_cpi: Vec<Instruction> = ixs
-            .iter()
-            .map(|ix| {
-                let mut cpi_accounts = Vec::with_capacity(ix.accounts.len() + 1);
-                cpi_accounts.push(AccountMeta::new_readonly(ix.program_id, false));
-                cpi_accounts.extend_from_slice(&ix.accounts);
-                Instruction::new_with_bytes(TEST_CPI_CALLER_PK, &ix.data, cpi_accounts)
-            })
-            .collect();
+        let instruction_cpi = through_cpi(ixs);
         let tx = Transaction::new_signed_with_payer(
             &instruction_cpi,
             Some(&self.admin.pubkey()),
@@ -201,6 +217,26 @@ impl TestContext {
         self.context.banks_client.process_transaction(tx).await
     }
 
+    /// Simulate the instructions and get the return data of the transaction
+    pub async fn simulate_transaction_return_data(
+        &mut self,
+        ixs: &[Instruction],
+    ) -> Result<Option<TransactionReturnData>, BanksClientError> {
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.bot.pubkey()),
+            &[&self.bot],
+            self.context.banks_client.get_latest_blockhash().await?,
+        );
+        let res = self.context.banks_client.simulate_transaction(tx).await?;
+        if let Some(Err(e)) = res.result {
+            return Err(BanksClientError::TransactionError(e));
+        }
+        Ok(res
+            .simulation_details
+            .and_then(|details| details.return_data))
+    }
+
     pub async fn send_transaction_with_bot(
         &mut self,
         ixs: &[Instruction],
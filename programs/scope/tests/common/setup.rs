@@ -1,5 +1,5 @@
 use anchor_lang::prelude::Pubkey;
-use scope::{OracleMappings, OraclePrices};
+use scope::{OracleMappings, OraclePrices, OracleTwaps};
 use solana_program_test::ProgramTest;
 use solana_sdk::{
     account::Account, commitment_config::CommitmentLevel, signature::Keypair, signer::Signer,
@@ -58,6 +58,7 @@ impl ScopeZeroCopyAccounts {
         Self {
             mapping: Keypair::new(),
             prices: Keypair::new(),
+            twaps: Keypair::new(),
         }
     }
 
@@ -78,5 +79,13 @@ impl ScopeZeroCopyAccounts {
                 &scope::ID,
             ),
         );
+        test.add_account(
+            self.twaps.pubkey(),
+            Account::new(
+                u32::MAX as u64,
+                std::mem::size_of::<OracleTwaps>() + 8,
+                &scope::ID,
+            ),
+        );
     }
 }
@@ -1,5 +1,13 @@
 use anchor_lang::prelude::{Pubkey, Rent};
-use scope::oracles::OracleType;
+use scope::{
+    oracles::{
+        clmm::{ClmmConfig, ClmmPool},
+        constant_product_lp::{ConstantProductLpConfig, ConstantProductPool},
+        pyth_pull::PythPullConfig,
+        OracleGenericData, OracleType,
+    },
+    GENERIC_DATA_SIZE,
+};
 use solana_program_test::{BanksClientError, ProgramTestContext};
 use solana_sdk::signature::Keypair;
 use thiserror::Error;
@@ -26,6 +34,41 @@ pub struct OracleConf {
     pub pubkey: Pubkey,
 }
 
+impl OracleConf {
+    /// Generic data registered in the oracle mapping along the oracle account
+    pub fn generic_data(&self) -> [u8; GENERIC_DATA_SIZE] {
+        match self.price_type {
+            TestOracleType::PythPull => PythPullConfig {
+                feed_id: crate::common::mock_oracles::pyth_pull::feed_id(&self.pubkey),
+            }
+            .to_generic_data(),
+            TestOracleType::ConstantProductLp {
+                pool,
+                price_a,
+                price_b,
+            } => ConstantProductLpConfig {
+                pool: pool.into(),
+                price_a,
+                price_b,
+            }
+            .to_generic_data(),
+            TestOracleType::ClmmSpot {
+                pool,
+                invert,
+                min_quote_depth,
+                twap_duration_s,
+            } => ClmmConfig {
+                min_quote_depth,
+                pool: pool.into(),
+                invert: invert.into(),
+                twap_duration_s,
+            }
+            .to_generic_data(),
+            _ => [0; GENERIC_DATA_SIZE],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum TestOracleType {
     Pyth,
@@ -42,6 +85,29 @@ pub enum TestOracleType {
     KToken(DEX),
     /// Pyth Exponentially-Weighted Moving Average
     PythEMA,
+    /// Pyth price posted by the pyth receiver program (pull oracle)
+    PythPull,
+    /// Switchboard on-demand pull feed
+    SwitchboardOnDemand,
+    /// Marinade staked SOL (mSOL)
+    MsolStake,
+    /// Kamino Lend collateral token
+    KaminoLendCollateral,
+    /// Marginfi bank deposit
+    MarginfiCollateral,
+    /// LP token of a constant product pool priced from two entries of the feed
+    ConstantProductLp {
+        pool: ConstantProductPool,
+        price_a: u16,
+        price_b: u16,
+    },
+    /// Spot price of a concentrated liquidity pool, or the average of its observations
+    ClmmSpot {
+        pool: ClmmPool,
+        invert: bool,
+        min_quote_depth: u64,
+        twap_duration_s: u32,
+    },
 }
 
 impl TestOracleType {
@@ -61,6 +127,13 @@ impl Into<OracleType> for TestOracleType {
             TestOracleType::SplStake => OracleType::SplStake,
             TestOracleType::KToken(_) => OracleType::KToken,
             TestOracleType::PythEMA => OracleType::PythEMA,
+            TestOracleType::PythPull => OracleType::PythPull,
+            TestOracleType::SwitchboardOnDemand => OracleType::SwitchboardOnDemand,
+            TestOracleType::MsolStake => OracleType::MsolStake,
+            TestOracleType::KaminoLendCollateral => OracleType::KaminoLendCollateral,
+            TestOracleType::MarginfiCollateral => OracleType::MarginfiCollateral,
+            TestOracleType::ConstantProductLp { .. } => OracleType::ConstantProductLp,
+            TestOracleType::ClmmSpot { .. } => OracleType::ClmmSpot,
             TestOracleType::DeprecatedPlaceholder => {
                 panic!("DeprecatedPlaceholder is not a valid oracle type")
             }
@@ -73,6 +146,7 @@ pub struct ScopeFeedDefinition {
     pub conf: Pubkey,
     pub mapping: Pubkey,
     pub prices: Pubkey,
+    pub twaps: Pubkey,
 }
 
 pub struct TestContext {
@@ -86,4 +160,5 @@ pub struct TestContext {
 pub struct ScopeZeroCopyAccounts {
     pub mapping: Keypair,
     pub prices: Keypair,
+    pub twaps: Keypair,
 }
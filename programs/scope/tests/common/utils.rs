@@ -3,11 +3,16 @@ use std::fmt::Debug;
 use num_enum::TryFromPrimitive;
 use num_traits::cast::FromPrimitive;
 use scope::oracles::OracleType;
+#[cfg(feature = "yvaults")]
+use scope::oracles::{clmm::ClmmConfig, OracleGenericData};
 use solana_program::instruction::{AccountMeta, InstructionError};
 use solana_program_test::BanksClientError;
 use solana_sdk::transaction::TransactionError;
 
-use crate::common::types::{OracleConf, TestContext};
+use crate::common::{
+    mock_oracles,
+    types::{OracleConf, TestContext},
+};
 
 pub async fn get_refresh_list_accounts(
     ctx: &mut TestContext,
@@ -32,6 +37,25 @@ pub async fn get_remaining_accounts(_ctx: &mut TestContext, conf: &OracleConf) -
         OracleType::KToken => {
             panic!("KToken oracle type is not supported")
         }
+        OracleType::ConstantProductLp => {
+            let pool_accounts = mock_oracles::constant_product_lp::pool_accounts(&conf.pubkey);
+            accounts.extend(
+                pool_accounts
+                    .to_vec()
+                    .into_iter()
+                    .map(|pk| AccountMeta::new_readonly(pk, false)),
+            );
+        }
+        #[cfg(feature = "yvaults")]
+        OracleType::ClmmSpot => {
+            let (mint_a, mint_b) = mock_oracles::clmm::mints(&conf.pubkey);
+            accounts.push(AccountMeta::new_readonly(mint_a, false));
+            accounts.push(AccountMeta::new_readonly(mint_b, false));
+            if ClmmConfig::from_generic_data(&conf.generic_data()).is_twap() {
+                let observation = mock_oracles::clmm::observation(&conf.pubkey);
+                accounts.push(AccountMeta::new_readonly(observation, false));
+            }
+        }
         _ => {} // No remaining accounts to add
     }
     accounts
@@ -0,0 +1,255 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
+use common::*;
+use scope::Configuration;
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use types::*;
+
+use crate::{common::utils::AnchorErrorCode, utils::map_anchor_error};
+
+// Set admin cached:
+// - [x] Wrong feed name
+// - [x] Wrong config account
+// - [x] Wrong admin
+
+// Approve admin cached:
+// - [x] Wrong feed name
+// - [x] Wrong config account
+// - [x] Wrong admin cached
+// - [x] Previous admin loses its rights
+
+fn set_admin_cached_ix(
+    admin: &Pubkey,
+    configuration: &Pubkey,
+    feed_name: &str,
+    new_admin: &Pubkey,
+) -> Instruction {
+    let accounts = scope::accounts::SetAdminCached {
+        admin: *admin,
+        configuration: *configuration,
+    };
+    let args = scope::instruction::SetAdminCached {
+        new_admin: *new_admin,
+        feed_name: feed_name.to_string(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn approve_admin_cached_ix(
+    admin_cached: &Pubkey,
+    configuration: &Pubkey,
+    feed_name: &str,
+) -> Instruction {
+    let accounts = scope::accounts::ApproveAdminCached {
+        admin_cached: *admin_cached,
+        configuration: *configuration,
+    };
+    let args = scope::instruction::ApproveAdminCached {
+        feed_name: feed_name.to_string(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+/// Setup a feed with a funded keypair already set as admin cached
+async fn setup_with_admin_cached() -> (TestContext, ScopeFeedDefinition, Keypair) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let new_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &new_admin.pubkey())
+        .await;
+
+    let ix = set_admin_cached_ix(
+        &ctx.admin.pubkey(),
+        &feed.conf,
+        &feed.feed_name,
+        &new_admin.pubkey(),
+    );
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    (ctx, feed, new_admin)
+}
+
+// Working admin handover
+#[tokio::test]
+async fn test_working_admin_handover() {
+    let (mut ctx, feed, new_admin) = setup_with_admin_cached().await;
+
+    let conf: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(conf.admin, ctx.admin.pubkey());
+    assert_eq!(conf.admin_cached, new_admin.pubkey());
+
+    let ix = approve_admin_cached_ix(&new_admin.pubkey(), &feed.conf, &feed.feed_name);
+    ctx.send_transaction_with_payer(&[ix], &new_admin)
+        .await
+        .unwrap();
+
+    let conf: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(conf.admin, new_admin.pubkey());
+    assert_eq!(conf.admin_cached, Pubkey::default());
+
+    // The new admin can now propose another admin
+    let ix = set_admin_cached_ix(
+        &new_admin.pubkey(),
+        &feed.conf,
+        &feed.feed_name,
+        &Pubkey::new_unique(),
+    );
+    ctx.send_transaction_with_payer(&[ix], &new_admin)
+        .await
+        .unwrap();
+}
+
+// - [ ] Set admin cached: Wrong feed name
+#[tokio::test]
+async fn test_set_admin_cached_wrong_feed_name() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let ix = set_admin_cached_ix(
+        &ctx.admin.pubkey(),
+        &feed.conf,
+        "randomFeed",
+        &Pubkey::new_unique(),
+    );
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Set admin cached: Wrong config account
+#[tokio::test]
+async fn test_set_admin_cached_wrong_config_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    // Create a fake config account
+    let fake_config_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.conf, &fake_config_pk).await;
+
+    let ix = set_admin_cached_ix(
+        &ctx.admin.pubkey(),
+        &fake_config_pk,
+        &feed.feed_name,
+        &Pubkey::new_unique(),
+    );
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Set admin cached: Wrong admin
+#[tokio::test]
+async fn test_set_admin_cached_wrong_admin() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    // New (bad) admin
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let ix = set_admin_cached_ix(
+        &fake_admin.pubkey(),
+        &feed.conf,
+        &feed.feed_name,
+        &fake_admin.pubkey(),
+    );
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Approve admin cached: Wrong feed name
+#[tokio::test]
+async fn test_approve_admin_cached_wrong_feed_name() {
+    let (mut ctx, feed, new_admin) = setup_with_admin_cached().await;
+
+    let ix = approve_admin_cached_ix(&new_admin.pubkey(), &feed.conf, "randomFeed");
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &new_admin).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Approve admin cached: Wrong config account
+#[tokio::test]
+async fn test_approve_admin_cached_wrong_config_account() {
+    let (mut ctx, feed, new_admin) = setup_with_admin_cached().await;
+
+    // Create a fake config account
+    let fake_config_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.conf, &fake_config_pk).await;
+
+    let ix = approve_admin_cached_ix(&new_admin.pubkey(), &fake_config_pk, &feed.feed_name);
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &new_admin).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Approve admin cached: Wrong admin cached
+#[tokio::test]
+async fn test_approve_admin_cached_wrong_admin_cached() {
+    let (mut ctx, feed, _new_admin) = setup_with_admin_cached().await;
+
+    // New (bad) admin
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let ix = approve_admin_cached_ix(&fake_admin.pubkey(), &feed.conf, &feed.feed_name);
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+
+    // The current admin cannot approve on behalf of the admin cached either
+    let ix = approve_admin_cached_ix(&ctx.admin.pubkey(), &feed.conf, &feed.feed_name);
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Previous admin loses its rights
+#[tokio::test]
+async fn test_previous_admin_loses_rights() {
+    let (mut ctx, feed, new_admin) = setup_with_admin_cached().await;
+
+    let ix = approve_admin_cached_ix(&new_admin.pubkey(), &feed.conf, &feed.feed_name);
+    ctx.send_transaction_with_payer(&[ix], &new_admin)
+        .await
+        .unwrap();
+
+    let ix = set_admin_cached_ix(
+        &ctx.admin.pubkey(),
+        &feed.conf,
+        &feed.feed_name,
+        &ctx.admin.pubkey(),
+    );
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
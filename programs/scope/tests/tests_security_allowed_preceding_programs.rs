@@ -0,0 +1,238 @@
+mod common;
+
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    InstructionData, ToAccountMetas,
+};
+use common::*;
+use scope::{Configuration, OraclePrices, Price, ScopeError, MAX_ALLOWED_PRECEDING_PROGRAMS};
+use solana_program::{
+    instruction::Instruction,
+    sysvar::{instructions::ID as SYSVAR_INSTRUCTIONS_ID, SysvarId},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
+use types::*;
+
+use crate::{
+    common::{
+        runner::{through_cpi, TEST_CPI_CALLER_PK},
+        utils::AnchorErrorCode,
+    },
+    utils::{map_anchor_error, map_scope_error},
+};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_PYTH2_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePyth2PriceAccount1111111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_ORACLE_CONF: [OracleConf; 2] = [TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
+
+const TEST_PRICE: Price = Price {
+    value: 1_500_000,
+    exp: 6,
+};
+
+// Set allowed preceding programs:
+// - [x] Wrong admin
+// - [x] Too many programs
+// - [x] Default pubkey, scope program or duplicated program
+
+// Refresh:
+// - [x] Refresh preceded by an allowed program
+// - [x] Refresh preceded by a program not allowed anymore
+// - [x] Refresh in CPI from an allowed program
+
+fn set_allowed_preceding_programs_ix(
+    admin: &Pubkey,
+    feed: &ScopeFeedDefinition,
+    programs: Vec<Pubkey>,
+) -> Instruction {
+    let accounts = scope::accounts::SetAllowedPrecedingPrograms {
+        admin: *admin,
+        configuration: feed.conf,
+    };
+    let args = scope::instruction::SetAllowedPrecedingPrograms {
+        programs,
+        feed_name: feed.feed_name.clone(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+async fn refresh_list_ix(ctx: &mut TestContext, feed: &ScopeFeedDefinition) -> Instruction {
+    let mut accounts = scope::accounts::RefreshList {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
+        clock: Clock::id(),
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    for conf in TEST_ORACLE_CONF.iter() {
+        let mut refresh_accounts = utils::get_refresh_list_accounts(ctx, conf).await;
+        accounts.append(&mut refresh_accounts);
+    }
+
+    let args = scope::instruction::RefreshPriceList {
+        tokens: TEST_ORACLE_CONF.map(|conf| conf.token as u16).to_vec(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: args.data(),
+    }
+}
+
+/// Read only instruction of the test CPI caller program
+fn preceding_ix(feed: &ScopeFeedDefinition) -> Instruction {
+    let accounts = scope::accounts::GetPrices {
+        oracle_prices: feed.prices,
+    };
+    let args = scope::instruction::GetPrices { tokens: vec![0] };
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    through_cpi(&[ix]).pop().unwrap()
+}
+
+async fn setup_with_prices() -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+    for conf in TEST_ORACLE_CONF.iter() {
+        mock_oracles::set_price(&mut ctx, &feed, conf, &TEST_PRICE).await;
+    }
+    (ctx, feed)
+}
+
+#[tokio::test]
+async fn test_working_set_allowed_preceding_programs() {
+    let (mut ctx, feed) = setup_with_prices().await;
+
+    let programs = vec![TEST_CPI_CALLER_PK, Pubkey::new_unique()];
+    let ix = set_allowed_preceding_programs_ix(&ctx.admin.pubkey(), &feed, programs.clone());
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let conf: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(conf.allowed_preceding_programs[..2], programs[..]);
+    assert!(conf.allowed_preceding_programs[2..]
+        .iter()
+        .all(|pk| *pk == Pubkey::default()));
+    assert!(conf.is_allowed_preceding_program(&TEST_CPI_CALLER_PK));
+    // Empty slots do not allow the system program
+    assert!(!conf.is_allowed_preceding_program(&Pubkey::default()));
+
+    // Refresh preceded by an instruction of the allowed program
+    let refresh_ix = refresh_list_ix(&mut ctx, &feed).await;
+    ctx.send_transaction_with_bot(&[preceding_ix(&feed), refresh_ix])
+        .await
+        .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    for conf in TEST_ORACLE_CONF.iter() {
+        assert_eq!(data.prices[conf.token].price, TEST_PRICE);
+    }
+}
+
+// - [ ] Refresh preceded by a program not allowed anymore
+#[tokio::test]
+async fn test_refresh_preceded_by_removed_program() {
+    let (mut ctx, feed) = setup_with_prices().await;
+
+    // Not allowed by default
+    let refresh_ix = refresh_list_ix(&mut ctx, &feed).await;
+    let res = ctx
+        .send_transaction_with_bot(&[preceding_ix(&feed), refresh_ix.clone()])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::RefreshWithUnexpectedIxs);
+
+    let ix =
+        set_allowed_preceding_programs_ix(&ctx.admin.pubkey(), &feed, vec![TEST_CPI_CALLER_PK]);
+    ctx.send_transaction(&[ix]).await.unwrap();
+    let ix = set_allowed_preceding_programs_ix(&ctx.admin.pubkey(), &feed, vec![]);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let res = ctx
+        .send_transaction_with_bot(&[preceding_ix(&feed), refresh_ix])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::RefreshWithUnexpectedIxs);
+}
+
+// - [ ] Refresh in CPI from an allowed program
+#[tokio::test]
+async fn test_refresh_in_cpi_from_allowed_program() {
+    let (mut ctx, feed) = setup_with_prices().await;
+
+    let ix =
+        set_allowed_preceding_programs_ix(&ctx.admin.pubkey(), &feed, vec![TEST_CPI_CALLER_PK]);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let refresh_ix = refresh_list_ix(&mut ctx, &feed).await;
+    let res = ctx.send_transaction_through_cpi(&[refresh_ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::RefreshInCPI);
+}
+
+// - [ ] Set allowed preceding programs: Wrong admin
+#[tokio::test]
+async fn test_set_allowed_preceding_programs_wrong_admin() {
+    let (mut ctx, feed) = setup_with_prices().await;
+
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let ix =
+        set_allowed_preceding_programs_ix(&fake_admin.pubkey(), &feed, vec![TEST_CPI_CALLER_PK]);
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Set allowed preceding programs: Too many programs
+#[tokio::test]
+async fn test_set_too_many_allowed_preceding_programs() {
+    let (mut ctx, feed) = setup_with_prices().await;
+
+    let programs = (0..=MAX_ALLOWED_PRECEDING_PROGRAMS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let ix = set_allowed_preceding_programs_ix(&ctx.admin.pubkey(), &feed, programs);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidAllowedPrograms
+    );
+}
+
+// - [ ] Set allowed preceding programs: Default pubkey, scope program or duplicated program
+#[tokio::test]
+async fn test_set_invalid_allowed_preceding_programs() {
+    let (mut ctx, feed) = setup_with_prices().await;
+
+    for programs in [
+        vec![Pubkey::default()],
+        vec![TEST_CPI_CALLER_PK, scope::id()],
+        vec![TEST_CPI_CALLER_PK, TEST_CPI_CALLER_PK],
+    ] {
+        let ix = set_allowed_preceding_programs_ix(&ctx.admin.pubkey(), &feed, programs);
+        assert_eq!(
+            map_scope_error(ctx.send_transaction(&[ix]).await),
+            ScopeError::InvalidAllowedPrograms
+        );
+    }
+}
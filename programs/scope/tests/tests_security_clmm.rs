@@ -0,0 +1,369 @@
+#![cfg(feature = "yvaults")]
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use scope::{oracles::clmm::ClmmPool, OraclePrices, ScopeError};
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::{
+    common::mock_oracles::{
+        clmm::{self, get_pool_and_accounts},
+        constant_product_lp::{get_mint_data, TOKEN_PROGRAM_ID},
+    },
+    utils::map_scope_error,
+};
+
+const TEST_ORCA_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeWhirPoo11111111111111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::ClmmSpot {
+        pool: ClmmPool::OrcaWhirlpool,
+        invert: false,
+        min_quote_depth: 0,
+        twap_duration_s: 0,
+    },
+};
+
+const TEST_RAYDIUM_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeRaydiumC1mmPoo1111111111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::ClmmSpot {
+        pool: ClmmPool::RaydiumClmm,
+        invert: false,
+        min_quote_depth: 0,
+        twap_duration_s: 0,
+    },
+};
+
+// Token B of the orca pool priced in token A
+const TEST_ORCA_INVERTED_ORACLE: OracleConf = OracleConf {
+    token: 2,
+    price_type: TestOracleType::ClmmSpot {
+        pool: ClmmPool::OrcaWhirlpool,
+        invert: true,
+        min_quote_depth: 0,
+        twap_duration_s: 0,
+    },
+    ..TEST_ORCA_ORACLE
+};
+
+// Average of the observations of the raydium pool over the last 2 minutes
+const TEST_RAYDIUM_TWAP_ORACLE: OracleConf = OracleConf {
+    token: 3,
+    price_type: TestOracleType::ClmmSpot {
+        pool: ClmmPool::RaydiumClmm,
+        invert: false,
+        min_quote_depth: 0,
+        twap_duration_s: 120,
+    },
+    ..TEST_RAYDIUM_ORACLE
+};
+
+/// Sqrt price of 0.25 token B smallest units per token A smallest unit
+const SQRT_PRICE_X64: u128 = 1 << 63;
+/// Token A has 9 decimals and token B 6: 1 token A is worth 250 token B
+const DECIMALS_A: u8 = 9;
+const DECIMALS_B: u8 = 6;
+
+// - [x] Refresh of a pool below the minimum depth
+// - [x] Refresh of a pool without liquidity in range
+// - [x] Wrong mint extra account
+// - [x] Missing extra accounts
+// - [x] Mapping of a pool not owned by the CLMM program
+// - [x] Mapping of a pool of another CLMM
+// - [x] Refresh of a TWAP with observations not covering its duration
+// - [x] Refresh of a TWAP with the observation account of another pool
+// - [x] Mapping of a TWAP on a whirlpool
+
+fn pool_kind(conf: &OracleConf) -> ClmmPool {
+    match conf.price_type {
+        TestOracleType::ClmmSpot { pool, .. } => pool,
+        _ => panic!("Not a CLMM oracle"),
+    }
+}
+
+fn set_pool(ctx: &mut TestContext, conf: &OracleConf, liquidity: u128) {
+    let pool = pool_kind(conf);
+    let (pool_data, accounts) = get_pool_and_accounts(
+        pool,
+        &conf.pubkey,
+        SQRT_PRICE_X64,
+        liquidity,
+        DECIMALS_A,
+        DECIMALS_B,
+    );
+    for (address, owner, data) in accounts {
+        ctx.set_account(&address, data, &owner);
+    }
+    ctx.set_account(&conf.pubkey, pool_data, &clmm::id(pool));
+}
+
+async fn setup_clmm(confs: Vec<OracleConf>) -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, confs.clone()).await;
+    for conf in confs {
+        set_pool(&mut ctx, &conf, 1 << 64);
+    }
+    (ctx, feed)
+}
+
+/// Record observations of the raydium pool, newest first, `(seconds ago, sqrt price)`
+async fn set_observations(ctx: &mut TestContext, conf: &OracleConf, observations: &[(u64, u128)]) {
+    let now = ctx.get_now_timestamp().await;
+    let observations: Vec<(u32, u128)> = observations
+        .iter()
+        .map(|(age, sqrt_price_x64)| ((now - age).try_into().unwrap(), *sqrt_price_x64))
+        .collect();
+    ctx.set_account(
+        &clmm::observation(&conf.pubkey),
+        clmm::get_observation_data(&conf.pubkey, &observations),
+        &clmm::id(ClmmPool::RaydiumClmm),
+    );
+}
+
+async fn get_price_value(
+    ctx: &mut TestContext,
+    feed: &ScopeFeedDefinition,
+    conf: &OracleConf,
+    exp: u64,
+) -> u64 {
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let price = data.prices[conf.token].price;
+    price.value / 10u64.pow((price.exp - exp).try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_working_refresh_clmm() {
+    let (mut ctx, feed) = setup_clmm(vec![
+        TEST_ORCA_ORACLE,
+        TEST_RAYDIUM_ORACLE,
+        TEST_ORCA_INVERTED_ORACLE,
+    ])
+    .await;
+
+    for conf in [TEST_ORCA_ORACLE, TEST_RAYDIUM_ORACLE] {
+        operations::refresh_price(&mut ctx, &feed, &conf).await;
+        let price = get_price_value(&mut ctx, &feed, &conf, 6).await;
+        assert_eq!(price, 250_000_000);
+    }
+
+    operations::refresh_price(&mut ctx, &feed, &TEST_ORCA_INVERTED_ORACLE).await;
+    // 0.004 token A per token B, the inversion rounds down
+    let price = get_price_value(&mut ctx, &feed, &TEST_ORCA_INVERTED_ORACLE, 12).await;
+    assert!(price.abs_diff(4_000_000_000) <= 1, "Inverted price {price}");
+}
+
+#[tokio::test]
+async fn test_working_refresh_raydium_twap() {
+    let (mut ctx, feed) = setup_clmm(vec![TEST_RAYDIUM_TWAP_ORACLE]).await;
+
+    // The spot price moved to 250 since the last observation at twice the sqrt price
+    set_observations(
+        &mut ctx,
+        &TEST_RAYDIUM_TWAP_ORACLE,
+        &[(1_000, 2 * SQRT_PRICE_X64)],
+    )
+    .await;
+    operations::refresh_price(&mut ctx, &feed, &TEST_RAYDIUM_TWAP_ORACLE).await;
+    let price = get_price_value(&mut ctx, &feed, &TEST_RAYDIUM_TWAP_ORACLE, 6).await;
+    assert_eq!(price, 1_000_000_000);
+}
+
+#[tokio::test]
+async fn test_refresh_twap_with_missing_observations() {
+    let (mut ctx, feed) = setup_clmm(vec![TEST_RAYDIUM_TWAP_ORACLE]).await;
+    let (mint_a, mint_b) = clmm::mints(&TEST_RAYDIUM_TWAP_ORACLE.pubkey);
+    let observation = clmm::observation(&TEST_RAYDIUM_TWAP_ORACLE.pubkey);
+
+    // The pool was created a minute ago
+    set_observations(&mut ctx, &TEST_RAYDIUM_TWAP_ORACLE, &[(60, SQRT_PRICE_X64)]).await;
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_RAYDIUM_TWAP_ORACLE,
+            &[mint_a, mint_b, observation],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+
+    // Observation account not provided
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_RAYDIUM_TWAP_ORACLE,
+            &[mint_a, mint_b],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+}
+
+#[tokio::test]
+async fn test_refresh_twap_with_wrong_observation_account() {
+    let (mut ctx, feed) = setup_clmm(vec![TEST_RAYDIUM_TWAP_ORACLE]).await;
+    let (mint_a, mint_b) = clmm::mints(&TEST_RAYDIUM_TWAP_ORACLE.pubkey);
+
+    // Observations of another pool with a higher price
+    let other_pool = OracleConf {
+        pubkey: Pubkey::new_unique(),
+        ..TEST_RAYDIUM_TWAP_ORACLE
+    };
+    set_observations(&mut ctx, &other_pool, &[(1_000, 2 * SQRT_PRICE_X64)]).await;
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_RAYDIUM_TWAP_ORACLE,
+            &[mint_a, mint_b, clmm::observation(&other_pool.pubkey)],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+}
+
+#[tokio::test]
+async fn test_refresh_with_shallow_pool() {
+    // At least 1000 token B backing the in-range liquidity
+    let conf = OracleConf {
+        price_type: TestOracleType::ClmmSpot {
+            pool: ClmmPool::OrcaWhirlpool,
+            invert: false,
+            min_quote_depth: 1_000_000_000,
+            twap_duration_s: 0,
+        },
+        ..TEST_ORCA_ORACLE
+    };
+    let (mut ctx, feed) = setup_clmm(vec![conf]).await;
+    let (mint_a, mint_b) = clmm::mints(&conf.pubkey);
+
+    // Virtual reserve of token B is half of the liquidity at this price
+    set_pool(&mut ctx, &conf, 1_999_999_998);
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(&feed, &conf, &[mint_a, mint_b])])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+
+    set_pool(&mut ctx, &conf, 2_000_000_000);
+    operations::refresh_price(&mut ctx, &feed, &conf).await;
+    let price = get_price_value(&mut ctx, &feed, &conf, 6).await;
+    assert_eq!(price, 250_000_000);
+}
+
+#[tokio::test]
+async fn test_refresh_without_liquidity_in_range() {
+    let (mut ctx, feed) = setup_clmm(vec![TEST_RAYDIUM_ORACLE]).await;
+    let (mint_a, mint_b) = clmm::mints(&TEST_RAYDIUM_ORACLE.pubkey);
+
+    set_pool(&mut ctx, &TEST_RAYDIUM_ORACLE, 0);
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_RAYDIUM_ORACLE,
+            &[mint_a, mint_b],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
+
+#[tokio::test]
+async fn test_refresh_with_wrong_mint() {
+    let (mut ctx, feed) = setup_clmm(vec![TEST_ORCA_ORACLE]).await;
+    let (mint_a, _) = clmm::mints(&TEST_ORCA_ORACLE.pubkey);
+
+    // Mint with more decimals lowering the price
+    let fake_mint = Pubkey::new_unique();
+    ctx.set_account(&fake_mint, get_mint_data(u64::MAX, 12), &TOKEN_PROGRAM_ID);
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_ORCA_ORACLE,
+            &[mint_a, fake_mint],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+}
+
+#[tokio::test]
+async fn test_refresh_with_missing_extra_accounts() {
+    let (mut ctx, feed) = setup_clmm(vec![TEST_ORCA_ORACLE]).await;
+    let (mint_a, _) = clmm::mints(&TEST_ORCA_ORACLE.pubkey);
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_ORCA_ORACLE,
+            &[mint_a],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+}
+
+#[tokio::test]
+async fn test_mapping_with_wrong_owner() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![]).await;
+
+    let (pool_data, _) = get_pool_and_accounts(
+        ClmmPool::OrcaWhirlpool,
+        &TEST_ORCA_ORACLE.pubkey,
+        SQRT_PRICE_X64,
+        1 << 64,
+        DECIMALS_A,
+        DECIMALS_B,
+    );
+    ctx.set_account(&TEST_ORCA_ORACLE.pubkey, pool_data, &Pubkey::new_unique());
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &TEST_ORCA_ORACLE);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::WrongAccountOwner
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_pool_of_another_clmm() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![]).await;
+
+    // A whirlpool owned by the raydium program
+    let (pool_data, _) = get_pool_and_accounts(
+        ClmmPool::OrcaWhirlpool,
+        &TEST_RAYDIUM_ORACLE.pubkey,
+        SQRT_PRICE_X64,
+        1 << 64,
+        DECIMALS_A,
+        DECIMALS_B,
+    );
+    ctx.set_account(
+        &TEST_RAYDIUM_ORACLE.pubkey,
+        pool_data,
+        &clmm::id(ClmmPool::RaydiumClmm),
+    );
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &TEST_RAYDIUM_ORACLE);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidAccountDiscriminator
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_twap_of_whirlpool() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![]).await;
+    set_pool(&mut ctx, &TEST_ORCA_ORACLE, 1 << 64);
+
+    // Whirlpools do not record observations
+    let conf = OracleConf {
+        price_type: TestOracleType::ClmmSpot {
+            pool: ClmmPool::OrcaWhirlpool,
+            invert: false,
+            min_quote_depth: 0,
+            twap_duration_s: 120,
+        },
+        ..TEST_ORCA_ORACLE
+    };
+    let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidGenericData
+    );
+}
@@ -0,0 +1,335 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use scope::{
+    oracles::constant_product_lp::{ConstantProductPool, TokenAmount},
+    OraclePrices, Price, ScopeError, MAX_ENTRIES_U16,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey, signer::Signer};
+use types::*;
+
+use crate::{
+    common::mock_oracles::constant_product_lp::{self, get_pool_and_accounts},
+    utils::map_scope_error,
+};
+
+const TEST_PYTH_A_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_PYTH_B_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePyth2PriceAccount1111111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_RAYDIUM_LP_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeRaydiumAmmPoo11111111111111111111111111"),
+    token: 2,
+    price_type: TestOracleType::ConstantProductLp {
+        pool: ConstantProductPool::RaydiumAmmV4,
+        price_a: 0,
+        price_b: 1,
+    },
+};
+
+const TEST_ORCA_LP_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeTokenSwapPoo111111111111111111111111111"),
+    token: 3,
+    price_type: TestOracleType::ConstantProductLp {
+        pool: ConstantProductPool::OrcaTokenSwap,
+        price_a: 0,
+        price_b: 1,
+    },
+};
+
+// - [x] Wrong pool vault extra account
+// - [x] Missing extra accounts
+// - [x] Mapping of a pool not owned by the AMM program
+// - [x] Mapping of a pool of another AMM
+// - [x] Mapping with the entry itself as source price
+// - [x] Mapping with a source out of the feed
+// - [x] Refresh with an entry added by a resize as source price
+// - [x] Refresh with a source without price
+
+fn amount(amount: u64, decimals: u8) -> TokenAmount {
+    TokenAmount {
+        amount: amount * 10u64.pow(decimals.into()),
+        decimals,
+    }
+}
+
+fn set_pool(
+    ctx: &mut TestContext,
+    conf: &OracleConf,
+    reserve_a: &TokenAmount,
+    reserve_b: &TokenAmount,
+    lp_supply: &TokenAmount,
+) {
+    let pool = match conf.price_type {
+        TestOracleType::ConstantProductLp { pool, .. } => pool,
+        _ => panic!("Not a constant product LP oracle"),
+    };
+    let (pool_data, accounts) =
+        get_pool_and_accounts(pool, &conf.pubkey, reserve_a, reserve_b, lp_supply);
+    for (address, owner, data) in accounts {
+        ctx.set_account(&address, data, &owner);
+    }
+    ctx.set_account(&conf.pubkey, pool_data, &constant_product_lp::id(pool));
+}
+
+// Token A at $2 and token B at $1, the LP pool is set but not mapped
+async fn setup_sources(conf: &OracleConf) -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(
+        DEFAULT_FEED_NAME,
+        vec![TEST_PYTH_A_ORACLE, TEST_PYTH_B_ORACLE],
+    )
+    .await;
+    for (source, value) in [(TEST_PYTH_A_ORACLE, 2), (TEST_PYTH_B_ORACLE, 1)] {
+        mock_oracles::set_price(&mut ctx, &feed, &source, &Price { value, exp: 0 }).await;
+        operations::refresh_price(&mut ctx, &feed, &source).await;
+    }
+    // 1000 A and 2000 B for 100 LP tokens
+    set_pool(
+        &mut ctx,
+        conf,
+        &amount(1_000, 9),
+        &amount(2_000, 6),
+        &amount(100, 6),
+    );
+    (ctx, feed)
+}
+
+async fn setup_lp(conf: &OracleConf) -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = setup_sources(conf).await;
+    operations::update_oracle_mapping(&mut ctx, &feed, conf).await;
+    (ctx, feed)
+}
+
+async fn get_lp_price(ctx: &mut TestContext, feed: &ScopeFeedDefinition, conf: &OracleConf) -> u64 {
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let price = data.prices[conf.token].price;
+    // In USD with 6 decimals
+    price.value / 10u64.pow((price.exp - 6).try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_working_refresh_lp() {
+    for conf in [TEST_RAYDIUM_LP_ORACLE, TEST_ORCA_LP_ORACLE] {
+        let (mut ctx, feed) = setup_lp(&conf).await;
+
+        operations::refresh_price(&mut ctx, &feed, &conf).await;
+
+        // $4000 of liquidity for 100 LP tokens
+        let price = get_lp_price(&mut ctx, &feed, &conf).await;
+        assert!(price.abs_diff(40_000_000) <= 1, "LP price {price}");
+    }
+}
+
+#[tokio::test]
+async fn test_swap_does_not_move_lp_price() {
+    let (mut ctx, feed) = setup_lp(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    // Imbalanced pool with the same invariant: raw reserves are now worth $8500
+    set_pool(
+        &mut ctx,
+        &TEST_RAYDIUM_LP_ORACLE,
+        &amount(4_000, 9),
+        &amount(500, 6),
+        &amount(100, 6),
+    );
+    operations::refresh_price(&mut ctx, &feed, &TEST_RAYDIUM_LP_ORACLE).await;
+
+    let price = get_lp_price(&mut ctx, &feed, &TEST_RAYDIUM_LP_ORACLE).await;
+    assert!(price.abs_diff(40_000_000) <= 1, "LP price {price}");
+}
+
+#[tokio::test]
+async fn test_refresh_with_wrong_vault() {
+    let (mut ctx, feed) = setup_lp(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    let mut extra_accounts =
+        constant_product_lp::pool_accounts(&TEST_RAYDIUM_LP_ORACLE.pubkey).to_vec();
+    // Vault holding more tokens
+    let fake_vault = Pubkey::new_unique();
+    ctx.set_account(
+        &fake_vault,
+        constant_product_lp::get_token_account_data(&extra_accounts[2], u64::MAX),
+        &constant_product_lp::TOKEN_PROGRAM_ID,
+    );
+    extra_accounts[0] = fake_vault;
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_RAYDIUM_LP_ORACLE,
+            &extra_accounts,
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+}
+
+#[tokio::test]
+async fn test_refresh_with_missing_extra_accounts() {
+    let (mut ctx, feed) = setup_lp(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    let extra_accounts =
+        constant_product_lp::pool_accounts(&TEST_RAYDIUM_LP_ORACLE.pubkey).to_vec();
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_RAYDIUM_LP_ORACLE,
+            &extra_accounts[..4],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+}
+
+#[tokio::test]
+async fn test_mapping_with_wrong_owner() {
+    let (mut ctx, feed) = setup_sources(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    let data = ctx
+        .context
+        .banks_client
+        .get_account(TEST_RAYDIUM_LP_ORACLE.pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    ctx.set_account(&TEST_RAYDIUM_LP_ORACLE.pubkey, data, &Pubkey::new_unique());
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &TEST_RAYDIUM_LP_ORACLE);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::WrongAccountOwner
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_pool_of_another_amm() {
+    let (mut ctx, feed) = setup_sources(&TEST_ORCA_LP_ORACLE).await;
+
+    // An orca pool owned by the raydium program
+    let data = ctx
+        .context
+        .banks_client
+        .get_account(TEST_ORCA_LP_ORACLE.pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let conf = OracleConf {
+        price_type: TEST_RAYDIUM_LP_ORACLE.price_type,
+        ..TEST_ORCA_LP_ORACLE
+    };
+    ctx.set_account(
+        &conf.pubkey,
+        data,
+        &constant_product_lp::id(ConstantProductPool::RaydiumAmmV4),
+    );
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::UnableToDeserializeAccount
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_itself_as_source() {
+    let (mut ctx, feed) = setup_sources(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    let conf = OracleConf {
+        price_type: TestOracleType::ConstantProductLp {
+            pool: ConstantProductPool::RaydiumAmmV4,
+            price_a: 0,
+            price_b: TEST_RAYDIUM_LP_ORACLE.token.try_into().unwrap(),
+        },
+        ..TEST_RAYDIUM_LP_ORACLE
+    };
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidGenericData
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_source_out_of_the_feed() {
+    let (mut ctx, feed) = setup_sources(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    // The feed has not been resized
+    let conf = OracleConf {
+        price_type: TestOracleType::ConstantProductLp {
+            pool: ConstantProductPool::RaydiumAmmV4,
+            price_a: 0,
+            price_b: MAX_ENTRIES_U16,
+        },
+        ..TEST_RAYDIUM_LP_ORACLE
+    };
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidGenericData
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_with_extended_entry_as_source() {
+    let (mut ctx, feed) = setup_sources(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    let ix = operations::resize_feed_ix(&ctx.admin.pubkey(), &feed, MAX_ENTRIES_U16 + 2);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    // Token B priced by an entry added by the resize
+    let extended_b = OracleConf {
+        token: usize::from(MAX_ENTRIES_U16) + 1,
+        ..TEST_PYTH_B_ORACLE
+    };
+    operations::update_oracle_mapping(&mut ctx, &feed, &extended_b).await;
+    operations::refresh_price(&mut ctx, &feed, &extended_b).await;
+
+    let conf = OracleConf {
+        price_type: TestOracleType::ConstantProductLp {
+            pool: ConstantProductPool::RaydiumAmmV4,
+            price_a: 0,
+            price_b: MAX_ENTRIES_U16 + 1,
+        },
+        ..TEST_RAYDIUM_LP_ORACLE
+    };
+    operations::update_oracle_mapping(&mut ctx, &feed, &conf).await;
+    operations::refresh_price(&mut ctx, &feed, &conf).await;
+
+    let price = get_lp_price(&mut ctx, &feed, &conf).await;
+    assert!(price.abs_diff(40_000_000) <= 1, "LP price {price}");
+}
+
+#[tokio::test]
+async fn test_refresh_with_source_without_price() {
+    let (mut ctx, feed) = setup_sources(&TEST_RAYDIUM_LP_ORACLE).await;
+
+    // Token B priced by an entry never refreshed
+    let conf = OracleConf {
+        price_type: TestOracleType::ConstantProductLp {
+            pool: ConstantProductPool::RaydiumAmmV4,
+            price_a: 0,
+            price_b: 10,
+        },
+        ..TEST_RAYDIUM_LP_ORACLE
+    };
+    operations::update_oracle_mapping(&mut ctx, &feed, &conf).await;
+
+    let extra_accounts = constant_product_lp::pool_accounts(&conf.pubkey).to_vec();
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(&feed, &conf, &extra_accounts)])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    InstructionData, ToAccountMetas,
+};
+use common::*;
+use scope::{return_data::decode_prices, OraclePrices, Price, ScopeError};
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::{common::runner::through_cpi, utils::map_scope_error};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_PYTH2_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePyth2PriceAccount1111111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_ORACLE_CONF: [OracleConf; 2] = [TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
+
+// - [x] Prices returned in the requested order
+// - [x] Prices returned through CPI
+// - [x] Token out of range
+// - [x] Too many tokens requested
+// - [x] Unexpected extra account
+
+fn get_prices_ix(feed: &ScopeFeedDefinition, tokens: Vec<u16>) -> Instruction {
+    let accounts = scope::accounts::GetPrices {
+        oracle_prices: feed.prices,
+    };
+    let args = scope::instruction::GetPrices { tokens };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+async fn setup_refreshed_prices() -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+    for (i, conf) in TEST_ORACLE_CONF.iter().enumerate() {
+        mock_oracles::set_price(
+            &mut ctx,
+            &feed,
+            conf,
+            &Price {
+                value: (i as u64) + 1,
+                exp: 6,
+            },
+        )
+        .await;
+        operations::refresh_price(&mut ctx, &feed, conf).await;
+    }
+    (ctx, feed)
+}
+
+#[tokio::test]
+async fn test_working_get_prices() {
+    let (mut ctx, feed) = setup_refreshed_prices().await;
+
+    let ix = get_prices_ix(&feed, vec![1, 0, 1]);
+    let return_data = ctx
+        .simulate_transaction_return_data(&[ix])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(return_data.program_id, scope::id());
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let prices = decode_prices(&return_data.data).unwrap();
+    assert_eq!(prices, vec![data.prices[1], data.prices[0], data.prices[1]]);
+    assert_eq!(prices[0].price.value, 2);
+    assert_eq!(prices[1].price.value, 1);
+}
+
+#[tokio::test]
+async fn test_get_prices_through_cpi() {
+    let (mut ctx, feed) = setup_refreshed_prices().await;
+
+    let ixs = through_cpi(&[get_prices_ix(&feed, vec![0])]);
+    let return_data = ctx
+        .simulate_transaction_return_data(&ixs)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(return_data.program_id, scope::id());
+
+    let prices = decode_prices(&return_data.data).unwrap();
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices[0].price.value, 1);
+}
+
+#[tokio::test]
+async fn test_get_prices_token_out_of_range() {
+    let (mut ctx, feed) = setup_refreshed_prices().await;
+
+    let ix = get_prices_ix(&feed, vec![0, scope::MAX_ENTRIES_U16]);
+    assert_eq!(
+        map_scope_error(ctx.simulate_transaction_return_data(&[ix]).await),
+        ScopeError::BadTokenNb
+    );
+}
+
+#[tokio::test]
+async fn test_get_prices_too_many_tokens() {
+    let (mut ctx, feed) = setup_refreshed_prices().await;
+
+    let tokens = vec![0; scope::return_data::MAX_RETURNED_PRICES + 1];
+    let ix = get_prices_ix(&feed, tokens);
+    assert_eq!(
+        map_scope_error(ctx.simulate_transaction_return_data(&[ix]).await),
+        ScopeError::TooManyPricesRequested
+    );
+}
+
+#[tokio::test]
+async fn test_get_prices_with_extra_account() {
+    let (mut ctx, feed) = setup_refreshed_prices().await;
+
+    let mut ix = get_prices_ix(&feed, vec![0]);
+    ix.accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    assert_eq!(
+        map_scope_error(ctx.simulate_transaction_return_data(&[ix]).await),
+        ScopeError::UnexpectedAccount
+    );
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use scope::{Configuration, OraclePrices, OracleTwaps, Price, ScopeError};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
+use types::*;
+
+use crate::{
+    common::utils::AnchorErrorCode,
+    utils::{map_anchor_error, map_scope_error},
+};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+// - [x] Refresh a feed that does not record TWAPs
+// - [x] Attach a TWAPs account to a feed without one
+// - [x] Feed that already has a TWAPs account
+// - [x] Wrong admin
+
+// Feed that does not record TWAPs and a new empty TWAPs account
+async fn setup_feed_without_twaps() -> (TestContext, ScopeFeedDefinition, Pubkey) {
+    let (mut ctx, mut feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    let mut configuration: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    configuration.oracle_twaps = Pubkey::default();
+    ctx.set_zero_copy_account(&feed.conf, &configuration);
+    feed.twaps = feed.prices;
+
+    let new_twaps = Pubkey::new_unique();
+    ctx.set_account(
+        &new_twaps,
+        vec![0; 8 + std::mem::size_of::<OracleTwaps>()],
+        &scope::id(),
+    );
+    (ctx, feed, new_twaps)
+}
+
+#[tokio::test]
+async fn test_refresh_without_twaps() {
+    let (mut ctx, feed, _) = setup_feed_without_twaps().await;
+
+    // The prices account is passed in place of the TWAPs account
+    let price = Price {
+        value: 1_500_000,
+        exp: 6,
+    };
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &price).await;
+    operations::refresh_price(&mut ctx, &feed, &TEST_PYTH_ORACLE).await;
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(prices.prices[TEST_PYTH_ORACLE.token].price, price);
+}
+
+#[tokio::test]
+async fn test_working_init_oracle_twaps() {
+    let (mut ctx, mut feed, new_twaps) = setup_feed_without_twaps().await;
+
+    let ix = operations::init_oracle_twaps_ix(&ctx.admin.pubkey(), &feed, &new_twaps);
+    ctx.send_transaction(&[ix]).await.unwrap();
+    feed.twaps = new_twaps;
+
+    let configuration: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(configuration.oracle_twaps, feed.twaps);
+    let twaps: OracleTwaps = ctx.get_zero_copy_account(&feed.twaps).await.unwrap();
+    assert_eq!(twaps.oracle_prices, feed.prices);
+    assert_eq!(twaps.oracle_mappings, feed.mapping);
+
+    // Refreshes now record the observations of the prices
+    let price = Price {
+        value: 1_500_000,
+        exp: 6,
+    };
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &price).await;
+    operations::refresh_price(&mut ctx, &feed, &TEST_PYTH_ORACLE).await;
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(prices.prices[TEST_PYTH_ORACLE.token].price, price);
+    let twaps: OracleTwaps = ctx.get_zero_copy_account(&feed.twaps).await.unwrap();
+    assert_eq!(twaps.twaps[TEST_PYTH_ORACLE.token].len, 1);
+}
+
+#[tokio::test]
+async fn test_oracle_twaps_already_set() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let new_twaps = Pubkey::new_unique();
+    ctx.set_account(
+        &new_twaps,
+        vec![0; 8 + std::mem::size_of::<OracleTwaps>()],
+        &scope::id(),
+    );
+
+    let ix = operations::init_oracle_twaps_ix(&ctx.admin.pubkey(), &feed, &new_twaps);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::OracleTwapsAlreadySet
+    );
+}
+
+#[tokio::test]
+async fn test_init_oracle_twaps_wrong_admin() {
+    let (mut ctx, feed, new_twaps) = setup_feed_without_twaps().await;
+
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let ix = operations::init_oracle_twaps_ix(&fake_admin.pubkey(), &feed, &new_twaps);
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
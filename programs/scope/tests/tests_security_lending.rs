@@ -0,0 +1,148 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use scope::{OraclePrices, Price, ScopeError};
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::{
+    common::mock_oracles::{kamino_lend, marginfi},
+    utils::map_scope_error,
+};
+
+const TEST_KAMINO_LEND_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeKaminoLendReserve1111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::KaminoLendCollateral,
+};
+
+const TEST_MARGINFI_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeMarginfiBank111111111111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::MarginfiCollateral,
+};
+
+// - [x] Mapping of an account not owned by the lending program
+// - [x] Mapping of an account of another lending program
+// - [x] Refresh with a negative asset share value
+
+#[tokio::test]
+async fn test_working_refresh_kamino_lend() {
+    let (mut ctx, feed) = fixtures::setup_scope_with_price(
+        DEFAULT_FEED_NAME,
+        &TEST_KAMINO_LEND_ORACLE,
+        &Price {
+            value: 1_250,
+            exp: 3,
+        },
+    )
+    .await;
+
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(
+        &feed,
+        &TEST_KAMINO_LEND_ORACLE,
+        &[],
+    )])
+    .await
+    .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let dated_price = data.prices[TEST_KAMINO_LEND_ORACLE.token];
+    assert_eq!(dated_price.price.value, 1_250_000_000_000_000);
+    assert_eq!(dated_price.price.exp, 15);
+    assert_eq!(dated_price.last_updated_slot, ctx.get_clock().await.slot);
+}
+
+#[tokio::test]
+async fn test_working_refresh_marginfi() {
+    let (mut ctx, feed) = fixtures::setup_scope_with_price(
+        DEFAULT_FEED_NAME,
+        &TEST_MARGINFI_ORACLE,
+        &Price {
+            value: 1_125,
+            exp: 3,
+        },
+    )
+    .await;
+
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(
+        &feed,
+        &TEST_MARGINFI_ORACLE,
+        &[],
+    )])
+    .await
+    .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let dated_price = data.prices[TEST_MARGINFI_ORACLE.token];
+    assert_eq!(dated_price.price.value, 1_125_000_000_000_000);
+    assert_eq!(dated_price.price.exp, 15);
+    let clock = ctx.get_clock().await;
+    assert_eq!(dated_price.unix_timestamp, clock.unix_timestamp as u64);
+}
+
+#[tokio::test]
+async fn test_mapping_with_wrong_owner() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let clock = ctx.get_clock().await;
+    let data = kamino_lend::get_account_data_for_price(&Price { value: 1, exp: 0 }, &clock);
+    ctx.set_account(&TEST_KAMINO_LEND_ORACLE.pubkey, data, &Pubkey::new_unique());
+    let data = marginfi::get_account_data_for_price(&Price { value: 1, exp: 0 }, &clock);
+    // Right layout but owned by another lending program
+    ctx.set_account(&TEST_MARGINFI_ORACLE.pubkey, data, &kamino_lend::id());
+
+    for conf in [&TEST_KAMINO_LEND_ORACLE, &TEST_MARGINFI_ORACLE] {
+        let ix = operations::update_mapping_ix(&ctx, &feed, conf);
+        assert_eq!(
+            map_scope_error(ctx.send_transaction(&[ix]).await),
+            ScopeError::WrongAccountOwner
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_mapping_with_account_of_another_type() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let clock = ctx.get_clock().await;
+    // A marginfi bank mapped as a kamino reserve
+    let data = marginfi::get_account_data_for_price(&Price { value: 1, exp: 0 }, &clock);
+    ctx.set_account(&TEST_KAMINO_LEND_ORACLE.pubkey, data, &kamino_lend::id());
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &TEST_KAMINO_LEND_ORACLE);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidAccountDiscriminator
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_with_negative_asset_share_value() {
+    let (mut ctx, feed) = fixtures::setup_scope_with_price(
+        DEFAULT_FEED_NAME,
+        &TEST_MARGINFI_ORACLE,
+        &Price { value: 1, exp: 0 },
+    )
+    .await;
+
+    let clock = ctx.get_clock().await;
+    let mut bank = marginfi::get_bank(&Price { value: 1, exp: 0 }, &clock);
+    bank.asset_share_value = -bank.asset_share_value;
+    ctx.set_account(
+        &TEST_MARGINFI_ORACLE.pubkey,
+        marginfi::get_account_data(&bank),
+        &marginfi::id(),
+    );
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_MARGINFI_ORACLE,
+            &[],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
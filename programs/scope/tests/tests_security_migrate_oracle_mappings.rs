@@ -0,0 +1,123 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
+use common::*;
+use scope::{extension, OracleMappings, OraclePrices, Price, ScopeError, MAX_ENTRIES};
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
+use types::*;
+
+use crate::{
+    common::utils::AnchorErrorCode,
+    utils::{map_anchor_error, map_scope_error},
+};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+// Size of the mappings accounts created before the `generic_data` column:
+// discriminator, price info accounts, price types and reserved space
+const LEGACY_MAPPINGS_SIZE: usize = 8 + MAX_ENTRIES * (32 + 1 + 8);
+
+// - [x] Migrate a legacy mappings account, keeping its entries
+// - [x] Already migrated
+// - [x] Wrong admin
+
+fn migrate_ix(admin: &Pubkey, feed: &ScopeFeedDefinition) -> Instruction {
+    let accounts = scope::accounts::MigrateOracleMappings {
+        admin: *admin,
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+        system_program: solana_program::system_program::id(),
+    };
+    let args = scope::instruction::MigrateOracleMappings {
+        feed_name: feed.feed_name.clone(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+async fn setup_legacy_feed() -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    let mut data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    data.truncate(LEGACY_MAPPINGS_SIZE);
+    ctx.set_account(&feed.mapping, data, &scope::id());
+    (ctx, feed)
+}
+
+#[tokio::test]
+async fn test_working_migrate_oracle_mappings() {
+    let (mut ctx, feed) = setup_legacy_feed().await;
+    assert!(ctx
+        .get_zero_copy_account::<OracleMappings>(&feed.mapping)
+        .await
+        .is_err());
+
+    // The account grows by at most 10 KiB per instruction
+    let ix = migrate_ix(&ctx.admin.pubkey(), &feed);
+    ctx.send_transaction(&[ix.clone()]).await.unwrap();
+    let data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    assert_eq!(data.len(), LEGACY_MAPPINGS_SIZE + 10 * 1024);
+
+    ctx.send_transaction(&[ix]).await.unwrap();
+    let data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    assert_eq!(
+        data.len(),
+        extension::account_size::<OracleMappings>(MAX_ENTRIES)
+    );
+
+    // Existing entries are kept and can be refreshed
+    let mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert_eq!(
+        mappings.price_info_accounts[TEST_PYTH_ORACLE.token],
+        TEST_PYTH_ORACLE.pubkey
+    );
+    assert_eq!(
+        mappings.price_types[TEST_PYTH_ORACLE.token],
+        TEST_PYTH_ORACLE.price_type.to_u8()
+    );
+    assert_eq!(mappings.generic_data[TEST_PYTH_ORACLE.token], [0; 32]);
+
+    let price = Price {
+        value: 1_500_000,
+        exp: 6,
+    };
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &price).await;
+    operations::refresh_price(&mut ctx, &feed, &TEST_PYTH_ORACLE).await;
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(prices.prices[TEST_PYTH_ORACLE.token].price, price);
+}
+
+#[tokio::test]
+async fn test_already_migrated() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let ix = migrate_ix(&ctx.admin.pubkey(), &feed);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidFeedSize
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_wrong_admin() {
+    let (mut ctx, feed) = setup_legacy_feed().await;
+
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let ix = migrate_ix(&fake_admin.pubkey(), &feed);
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use scope::{OraclePrices, Price, ScopeError};
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::{common::mock_oracles::msol_stake, utils::map_scope_error};
+
+const TEST_MSOL_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeMarinadeStateAccount1111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::MsolStake,
+};
+
+// - [x] Mapping of an account not owned by marinade
+// - [x] Mapping of an account that is not a marinade state
+// - [x] Refresh with an empty exchange rate
+
+#[tokio::test]
+async fn test_working_refresh_msol_stake() {
+    let (mut ctx, feed) = fixtures::setup_scope_with_price(
+        DEFAULT_FEED_NAME,
+        &TEST_MSOL_ORACLE,
+        &Price {
+            value: 1_125,
+            exp: 3,
+        },
+    )
+    .await;
+
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(&feed, &TEST_MSOL_ORACLE, &[])])
+        .await
+        .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let dated_price = data.prices[TEST_MSOL_ORACLE.token];
+    // Same 15 decimals convention as spl stake pools
+    assert_eq!(dated_price.price.value, 1_125_000_000_000_000);
+    assert_eq!(dated_price.price.exp, 15);
+}
+
+#[tokio::test]
+async fn test_mapping_with_wrong_owner() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let clock = ctx.get_clock().await;
+    let data = msol_stake::get_account_data_for_price(&Price { value: 1, exp: 0 }, &clock);
+    ctx.set_account(&TEST_MSOL_ORACLE.pubkey, data, &Pubkey::new_unique());
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &TEST_MSOL_ORACLE);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::WrongAccountOwner
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_wrong_discriminator() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let clock = ctx.get_clock().await;
+    let mut data = msol_stake::get_account_data_for_price(&Price { value: 1, exp: 0 }, &clock);
+    data[0] = 0;
+    ctx.set_account(&TEST_MSOL_ORACLE.pubkey, data, &msol_stake::id());
+
+    let ix = operations::update_mapping_ix(&ctx, &feed, &TEST_MSOL_ORACLE);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidAccountDiscriminator
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_with_empty_rate() {
+    let (mut ctx, feed) = fixtures::setup_scope_with_price(
+        DEFAULT_FEED_NAME,
+        &TEST_MSOL_ORACLE,
+        &Price { value: 1, exp: 0 },
+    )
+    .await;
+
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_MSOL_ORACLE, &Price::default()).await;
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(&feed, &TEST_MSOL_ORACLE, &[])])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
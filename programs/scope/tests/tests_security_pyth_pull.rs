@@ -0,0 +1,212 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use scope::{oracles::pyth_pull::VerificationLevel, OraclePrices, Price, ScopeError};
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::{common::mock_oracles::pyth_pull, utils::map_scope_error};
+
+const TEST_PYTH_PULL_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPu11PriceAccount1111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::PythPull,
+};
+
+// - [x] Mapping without feed id
+// - [x] Mapping with another feed id
+// - [x] Mapping of an account not owned by the pyth receiver
+// - [x] Refresh with another feed posted in the account
+// - [x] Refresh with a partially verified update
+// - [x] Refresh with an old update posted again
+
+#[tokio::test]
+async fn test_working_refresh_pyth_pull() {
+    let (mut ctx, feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_PULL_ORACLE]).await;
+
+    let clock = ctx.get_clock().await;
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_PULL_ORACLE,
+        &Price {
+            value: 1_500_000,
+            exp: 6,
+        },
+    )
+    .await;
+
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(
+        &feed,
+        &TEST_PYTH_PULL_ORACLE,
+        &[],
+    )])
+    .await
+    .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let dated_price = data.prices[TEST_PYTH_PULL_ORACLE.token];
+    assert_eq!(dated_price.price.value, 1_500_000);
+    assert_eq!(dated_price.price.exp, 6);
+    // The price is as old as the published price
+    assert_eq!(dated_price.unix_timestamp, clock.unix_timestamp as u64);
+    assert!(dated_price.last_updated_slot <= ctx.get_clock().await.slot);
+}
+
+#[tokio::test]
+async fn test_mapping_without_feed_id() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_PULL_ORACLE, &Price::default()).await;
+
+    let ix = operations::update_mapping_with_generic_data_ix(
+        &ctx,
+        &feed,
+        &TEST_PYTH_PULL_ORACLE,
+        [0; scope::GENERIC_DATA_SIZE],
+    );
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidGenericData
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_another_feed_id() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_PULL_ORACLE, &Price::default()).await;
+
+    let ix = operations::update_mapping_with_generic_data_ix(
+        &ctx,
+        &feed,
+        &TEST_PYTH_PULL_ORACLE,
+        [1; scope::GENERIC_DATA_SIZE],
+    );
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::PriceNotValid
+    );
+}
+
+#[tokio::test]
+async fn test_mapping_with_wrong_owner() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let clock = ctx.get_clock().await;
+    let data = pyth_pull::get_account_data_for_price(
+        &Price::default(),
+        &clock,
+        pyth_pull::feed_id(&TEST_PYTH_PULL_ORACLE.pubkey),
+    );
+    ctx.set_account(&TEST_PYTH_PULL_ORACLE.pubkey, data, &Pubkey::new_unique());
+
+    let ix = operations::update_mapping_with_generic_data_ix(
+        &ctx,
+        &feed,
+        &TEST_PYTH_PULL_ORACLE,
+        TEST_PYTH_PULL_ORACLE.generic_data(),
+    );
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::WrongAccountOwner
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_with_another_feed() {
+    let (mut ctx, feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_PULL_ORACLE]).await;
+
+    // Another feed is posted in the mapped account
+    let clock = ctx.get_clock().await;
+    let data = pyth_pull::get_account_data_for_price(&Price { value: 1, exp: 6 }, &clock, [1; 32]);
+    ctx.set_account(&TEST_PYTH_PULL_ORACLE.pubkey, data, &pyth_pull::id());
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_PYTH_PULL_ORACLE,
+            &[],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
+
+#[tokio::test]
+async fn test_refresh_partially_verified() {
+    let (mut ctx, feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_PULL_ORACLE]).await;
+
+    let clock = ctx.get_clock().await;
+    let mut price_update = pyth_pull::get_price_update(
+        &Price { value: 1, exp: 6 },
+        &clock,
+        pyth_pull::feed_id(&TEST_PYTH_PULL_ORACLE.pubkey),
+    );
+    price_update.verification_level = VerificationLevel::Partial { num_signatures: 5 };
+    ctx.set_account(
+        &TEST_PYTH_PULL_ORACLE.pubkey,
+        pyth_pull::get_account_data(&price_update),
+        &pyth_pull::id(),
+    );
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_PYTH_PULL_ORACLE,
+            &[],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
+
+#[tokio::test]
+async fn test_refresh_old_update_posted_again() {
+    let (mut ctx, feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_PULL_ORACLE]).await;
+
+    let clock = ctx.get_clock().await;
+    let feed_id = pyth_pull::feed_id(&TEST_PYTH_PULL_ORACLE.pubkey);
+    let mut old_price_update =
+        pyth_pull::get_price_update(&Price { value: 1, exp: 6 }, &clock, feed_id);
+    let mut price_update =
+        pyth_pull::get_price_update(&Price { value: 2, exp: 6 }, &clock, feed_id);
+    price_update.price_message.publish_time += 10;
+    ctx.set_account(
+        &TEST_PYTH_PULL_ORACLE.pubkey,
+        pyth_pull::get_account_data(&price_update),
+        &pyth_pull::id(),
+    );
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(
+        &feed,
+        &TEST_PYTH_PULL_ORACLE,
+        &[],
+    )])
+    .await
+    .unwrap();
+
+    // The older update is posted again, in a later slot
+    ctx.warp_to_slot(clock.slot + 10);
+    old_price_update.posted_slot = clock.slot + 10;
+    ctx.set_account(
+        &TEST_PYTH_PULL_ORACLE.pubkey,
+        pyth_pull::get_account_data(&old_price_update),
+        &pyth_pull::id(),
+    );
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_PYTH_PULL_ORACLE,
+            &[],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(data.prices[TEST_PYTH_PULL_ORACLE.token].price.value, 2);
+}
@@ -1,9 +1,9 @@
 use anchor_lang::{
     prelude::{AccountMeta, Clock, Pubkey},
-    InstructionData, ToAccountMetas,
+    Discriminator, InstructionData, ToAccountMetas,
 };
 use common::*;
-use scope::{OraclePrices, Price, ScopeError};
+use scope::{OraclePrices, OracleTwaps, Price, ScopeError};
 use solana_program::{
     instruction::Instruction,
     sysvar::{instructions::ID as SYSVAR_INSTRUCTIONS_ID, SysvarId},
@@ -42,6 +42,7 @@ const TEST_ORACLE_CONF: [OracleConf; 4] = [
 const TEST_ORACLE_CONF: [OracleConf; 2] = [TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
 
 // - [x] Wrong oracle mapping
+// - [x] Wrong oracle twaps
 // - [x] Wrong oracle account (copy)
 // - [x] Wrong oracle account (mixing indexes)
 // - [x] Wrong sysvar instruction account
@@ -55,6 +56,12 @@ const TEST_ORACLE_CONF: [OracleConf; 2] = [TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
 // - [x] Wrong kToken additional orca position account
 // - [x] Wrong kToken additional scope prices account
 
+// Switchboard on-demand:
+// - [x] Stale pull feed result
+// - [x] Stale pull feed result without a feed max staleness
+// - [x] Pull feed result with not enough samples
+// - [x] Pull feed not owned by the switchboard on-demand program
+
 #[tokio::test]
 async fn test_working_refresh_list() {
     let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
@@ -77,6 +84,8 @@ async fn test_working_refresh_list() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
     }
@@ -134,6 +143,8 @@ async fn test_wrong_oracle_mapping() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: fake_mapping_pk,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
     }
@@ -159,6 +170,48 @@ async fn test_wrong_oracle_mapping() {
     );
 }
 
+// - [ ] Wrong oracle twaps
+#[tokio::test]
+async fn test_wrong_oracle_twaps() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+
+    // Create a fake twaps account not linked to the feed prices
+    let fake_twaps_pk = Pubkey::new_unique();
+    let mut fake_twaps_data = OracleTwaps::DISCRIMINATOR.to_vec();
+    fake_twaps_data.resize(8 + std::mem::size_of::<OracleTwaps>(), 0);
+    ctx.set_account(&fake_twaps_pk, fake_twaps_data, &scope::id());
+
+    // Refresh
+    let mut accounts = scope::accounts::RefreshList {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: fake_twaps_pk,
+        configuration: feed.conf,
+        clock: Clock::id(),
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    for conf in TEST_ORACLE_CONF.iter() {
+        let mut refresh_accounts = utils::get_refresh_list_accounts(&mut ctx, conf).await;
+        accounts.append(&mut refresh_accounts);
+    }
+
+    let args = scope::instruction::RefreshPriceList {
+        tokens: TEST_ORACLE_CONF.map(|conf| conf.token as u16).to_vec(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_bot(&[ix]).await),
+        ScopeError::UnexpectedAccount
+    );
+}
+
 // - [ ] Wrong oracle account (copy)
 #[tokio::test]
 async fn test_wrong_oracle_account_with_copy() {
@@ -187,6 +240,8 @@ async fn test_wrong_oracle_account_with_copy() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
     }
@@ -241,6 +296,8 @@ async fn test_wrong_index_oracle_account() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
     }
@@ -297,6 +354,8 @@ async fn test_wrong_sysvar_instructions() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: wrong_sysvar_account,
     }
@@ -343,6 +402,8 @@ async fn test_refresh_through_cpi() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
     }
@@ -409,6 +470,8 @@ async fn test_refresh_with_unexpected_ix() {
     let mut accounts = scope::accounts::RefreshList {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
     }
@@ -486,6 +549,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -557,6 +622,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -635,6 +702,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -710,6 +779,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -784,6 +855,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -854,6 +927,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -928,6 +1003,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -999,6 +1076,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -1073,6 +1152,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -1144,6 +1225,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshList {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         }
@@ -1184,3 +1267,188 @@ mod ktoken_tests {
         );
     }
 }
+
+mod switchboard_on_demand_tests {
+    use super::*;
+    use crate::common::mock_oracles::switchboard_on_demand;
+
+    const TEST_SB_ON_DEMAND_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeSwitchboardonDemandFeedAccount111111111"),
+        token: 2,
+        price_type: TestOracleType::SwitchboardOnDemand,
+    };
+
+    const TEST_ORACLE_CONF: [OracleConf; 2] = [TEST_PYTH_ORACLE, TEST_SB_ON_DEMAND_ORACLE];
+
+    const TEST_PRICE: Price = Price {
+        value: 1_500_000,
+        exp: 6,
+    };
+
+    async fn refresh_list_ix(ctx: &mut TestContext, feed: &ScopeFeedDefinition) -> Instruction {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
+            clock: Clock::id(),
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        for conf in TEST_ORACLE_CONF.iter() {
+            let mut refresh_accounts = utils::get_refresh_list_accounts(ctx, conf).await;
+            accounts.append(&mut refresh_accounts);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: TEST_ORACLE_CONF.map(|conf| conf.token as u16).to_vec(),
+        };
+
+        Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        }
+    }
+
+    async fn set_prices(ctx: &mut TestContext, feed: &ScopeFeedDefinition) {
+        for conf in TEST_ORACLE_CONF.iter() {
+            mock_oracles::set_price(ctx, feed, conf, &TEST_PRICE).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_list_switchboard_on_demand() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+
+        set_prices(&mut ctx, &feed).await;
+
+        let ix = refresh_list_ix(&mut ctx, &feed).await;
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // The 18 decimals of the pull feed are capped to 10
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        let price = data.prices[TEST_SB_ON_DEMAND_ORACLE.token].price;
+        assert_eq!(price.value, 15_000_000_000);
+        assert_eq!(price.exp, 10);
+    }
+
+    // - [ ] Stale pull feed result
+    #[tokio::test]
+    async fn test_stale_switchboard_on_demand() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+
+        set_prices(&mut ctx, &feed).await;
+
+        // Get past the feed max staleness and only update the pyth price
+        let clock = ctx.get_clock().await;
+        ctx.warp_to_slot(clock.slot + u64::from(switchboard_on_demand::MAX_STALENESS) + 1);
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &TEST_PRICE).await;
+
+        let ix = refresh_list_ix(&mut ctx, &feed).await;
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // The stale price is skipped and the reason recorded in the entry
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_PYTH_ORACLE.token].price, TEST_PRICE);
+        assert_eq!(data.prices[TEST_PYTH_ORACLE.token].last_error_slot, 0);
+        let skipped = data.prices[TEST_SB_ON_DEMAND_ORACLE.token];
+        assert_eq!(skipped.price.value, 0);
+        assert_eq!(skipped.last_error_code, u32::from(ScopeError::PriceTooOld));
+        assert_eq!(skipped.last_error_slot, ctx.get_clock().await.slot);
+    }
+
+    // - [ ] Stale pull feed result without a feed max staleness
+    #[tokio::test]
+    async fn test_stale_switchboard_on_demand_without_max_staleness() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+
+        set_prices(&mut ctx, &feed).await;
+
+        let clock = ctx.get_clock().await;
+        let mut pull_feed = switchboard_on_demand::get_pull_feed(&TEST_PRICE, &clock);
+        pull_feed.max_staleness = 0;
+        ctx.set_account(
+            &TEST_SB_ON_DEMAND_ORACLE.pubkey,
+            switchboard_on_demand::get_account_data(&pull_feed),
+            &switchboard_on_demand::id(),
+        );
+
+        // The entry has no max age either, scope default bound applies
+        ctx.warp_to_slot(
+            clock.slot
+                + u64::from(scope::oracles::switchboard_on_demand::DEFAULT_MAX_STALENESS)
+                + 1,
+        );
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &TEST_PRICE).await;
+
+        let ix = refresh_list_ix(&mut ctx, &feed).await;
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_PYTH_ORACLE.token].price, TEST_PRICE);
+        let skipped = data.prices[TEST_SB_ON_DEMAND_ORACLE.token];
+        assert_eq!(skipped.price.value, 0);
+        assert_eq!(skipped.last_error_code, u32::from(ScopeError::PriceTooOld));
+    }
+
+    // - [ ] Pull feed result with not enough samples
+    #[tokio::test]
+    async fn test_not_enough_samples_switchboard_on_demand() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+
+        set_prices(&mut ctx, &feed).await;
+
+        let clock = ctx.get_clock().await;
+        let mut pull_feed = switchboard_on_demand::get_pull_feed(&TEST_PRICE, &clock);
+        pull_feed.min_sample_size = 3;
+        pull_feed.result.num_samples = 2;
+        ctx.set_account(
+            &TEST_SB_ON_DEMAND_ORACLE.pubkey,
+            switchboard_on_demand::get_account_data(&pull_feed),
+            &switchboard_on_demand::id(),
+        );
+
+        let ix = refresh_list_ix(&mut ctx, &feed).await;
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_PYTH_ORACLE.token].price, TEST_PRICE);
+        assert_eq!(data.prices[TEST_SB_ON_DEMAND_ORACLE.token].price.value, 0);
+        assert_eq!(
+            data.prices[TEST_SB_ON_DEMAND_ORACLE.token].last_error_code,
+            u32::from(ScopeError::SwitchboardOnDemandError)
+        );
+    }
+
+    // - [ ] Pull feed not owned by the switchboard on-demand program
+    #[tokio::test]
+    async fn test_wrong_owner_switchboard_on_demand() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, TEST_ORACLE_CONF.to_vec()).await;
+
+        set_prices(&mut ctx, &feed).await;
+
+        let clock = ctx.get_clock().await;
+        ctx.set_account(
+            &TEST_SB_ON_DEMAND_ORACLE.pubkey,
+            switchboard_on_demand::get_account_data_for_price(&TEST_PRICE, &clock),
+            &Pubkey::new_unique(),
+        );
+
+        let ix = refresh_list_ix(&mut ctx, &feed).await;
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_PYTH_ORACLE.token].price, TEST_PRICE);
+        assert_eq!(data.prices[TEST_SB_ON_DEMAND_ORACLE.token].price.value, 0);
+        assert_eq!(
+            data.prices[TEST_SB_ON_DEMAND_ORACLE.token].last_error_code,
+            u32::from(ScopeError::WrongAccountOwner)
+        );
+    }
+}
@@ -32,6 +32,7 @@ const TEST_PYTH2_ORACLE: OracleConf = OracleConf {
 };
 
 // - [x] Wrong oracle mapping
+// - [x] Wrong oracle twaps
 // - [x] Wrong oracle account (copy)
 // - [x] Wrong oracle account (mixing indexes)
 // - [x] Wrong sysvar instruction account
@@ -63,6 +64,8 @@ async fn test_working_refresh_one() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         price_info: TEST_PYTH_ORACLE.pubkey,
@@ -108,6 +111,8 @@ async fn test_wrong_oracle_mapping() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: fake_mapping_pk,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         price_info: TEST_PYTH_ORACLE.pubkey,
@@ -129,6 +134,42 @@ async fn test_wrong_oracle_mapping() {
     );
 }
 
+// - [ ] Wrong oracle twaps
+#[tokio::test]
+async fn test_wrong_oracle_twaps() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    // Create a fake twaps account, referencing the right prices account
+    let fake_twaps_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.twaps, &fake_twaps_pk).await;
+
+    // Refresh
+    let accounts = scope::accounts::RefreshOne {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: fake_twaps_pk,
+        configuration: feed.conf,
+        clock: Clock::id(),
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        price_info: TEST_PYTH_ORACLE.pubkey,
+    };
+
+    let args = scope::instruction::RefreshOnePrice {
+        token: TEST_PYTH_ORACLE.token.try_into().unwrap(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_bot(&[ix]).await),
+        ScopeError::UnexpectedAccount
+    );
+}
+
 // - [ ] Wrong oracle account (copy)
 #[tokio::test]
 async fn test_wrong_oracle_account_with_copy() {
@@ -152,6 +193,8 @@ async fn test_wrong_oracle_account_with_copy() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         price_info: fake_price_account,
@@ -192,6 +235,8 @@ async fn test_wrong_index_oracle_account() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         price_info: TEST_PYTH_ORACLE.pubkey,
@@ -235,6 +280,8 @@ async fn test_wrong_sysvar_instructions() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: wrong_sysvar_account,
         price_info: TEST_PYTH_ORACLE.pubkey,
@@ -273,6 +320,8 @@ async fn test_refresh_through_cpi() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         price_info: TEST_PYTH_ORACLE.pubkey,
@@ -330,6 +379,8 @@ async fn test_refresh_with_unexpected_ix() {
     let accounts = scope::accounts::RefreshOne {
         oracle_prices: feed.prices,
         oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
         clock: Clock::id(),
         instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
         price_info: TEST_PYTH_ORACLE.pubkey,
@@ -387,6 +438,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_ORCA_KTOKEN_ORACLE.pubkey,
@@ -433,6 +486,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_RAYDIUM_KTOKEN_ORACLE.pubkey,
@@ -493,6 +548,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_ORCA_KTOKEN_ORACLE.pubkey,
@@ -551,6 +608,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_RAYDIUM_KTOKEN_ORACLE.pubkey,
@@ -613,6 +672,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_ORCA_KTOKEN_ORACLE.pubkey,
@@ -675,6 +736,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_RAYDIUM_KTOKEN_ORACLE.pubkey,
@@ -733,6 +796,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_ORCA_KTOKEN_ORACLE.pubkey,
@@ -790,6 +855,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_RAYDIUM_KTOKEN_ORACLE.pubkey,
@@ -848,6 +915,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_ORCA_KTOKEN_ORACLE.pubkey,
@@ -906,6 +975,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_RAYDIUM_KTOKEN_ORACLE.pubkey,
@@ -964,6 +1035,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_ORCA_KTOKEN_ORACLE.pubkey,
@@ -1022,6 +1095,8 @@ mod ktoken_tests {
         let mut accounts = scope::accounts::RefreshOne {
             oracle_prices: feed.prices,
             oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            configuration: feed.conf,
             clock: Clock::id(),
             instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
             price_info: TEST_RAYDIUM_KTOKEN_ORACLE.pubkey,
@@ -0,0 +1,237 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
+use common::*;
+use scope::{
+    extension::{self, MAX_FEED_ENTRIES},
+    return_data::decode_prices,
+    Configuration, OracleMappings, OraclePrices, OracleTwaps, Price, ScopeError, MAX_ENTRIES,
+};
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
+use types::*;
+
+use crate::{
+    common::utils::AnchorErrorCode,
+    utils::{map_anchor_error, map_scope_error},
+};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_EXTENDED_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePyth2PriceAccount1111111111111111111111"),
+    token: MAX_ENTRIES + 1,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_NUM_ENTRIES: u16 = 514;
+
+// - [x] Resize the feed
+// - [x] Resize a feed that does not record TWAPs
+// - [x] Map, refresh and get the price of an entry beyond the fixed layout
+// - [x] Wrong admin
+// - [x] Shrink or keep the same size
+// - [x] Grow too much in one instruction
+// - [x] Grow above the max number of entries
+
+async fn setup_resized_feed() -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    let ix = operations::resize_feed_ix(&ctx.admin.pubkey(), &feed, TEST_NUM_ENTRIES);
+    ctx.send_transaction(&[ix]).await.unwrap();
+    (ctx, feed)
+}
+
+#[tokio::test]
+async fn test_working_resize_feed() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    let price = Price {
+        value: 1_500_000,
+        exp: 6,
+    };
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &price).await;
+    operations::refresh_price(&mut ctx, &feed, &TEST_PYTH_ORACLE).await;
+
+    let ix = operations::resize_feed_ix(&ctx.admin.pubkey(), &feed, TEST_NUM_ENTRIES);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let num_entries = usize::from(TEST_NUM_ENTRIES);
+    let data = ctx.get_account_data(&feed.prices).await.unwrap();
+    assert_eq!(
+        data.len(),
+        extension::account_size::<OraclePrices>(num_entries)
+    );
+    assert_eq!(
+        extension::num_entries::<OraclePrices>(&data),
+        Ok(num_entries)
+    );
+    let data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    assert_eq!(
+        extension::num_entries::<OracleMappings>(&data),
+        Ok(num_entries)
+    );
+    let data = ctx.get_account_data(&feed.twaps).await.unwrap();
+    assert_eq!(
+        extension::num_entries::<OracleTwaps>(&data),
+        Ok(num_entries)
+    );
+
+    // Entries of the fixed layout are untouched
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(prices.prices[TEST_PYTH_ORACLE.token].price, price);
+    let mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert_eq!(
+        mappings.price_info_accounts[TEST_PYTH_ORACLE.token],
+        TEST_PYTH_ORACLE.pubkey
+    );
+}
+
+#[tokio::test]
+async fn test_resize_feed_without_twaps() {
+    let (mut ctx, mut feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    let mut configuration: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    configuration.oracle_twaps = Pubkey::default();
+    ctx.set_zero_copy_account(&feed.conf, &configuration);
+    feed.twaps = feed.prices;
+
+    let ix = operations::resize_feed_ix(&ctx.admin.pubkey(), &feed, TEST_NUM_ENTRIES);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let data = ctx.get_account_data(&feed.prices).await.unwrap();
+    assert_eq!(
+        extension::num_entries::<OraclePrices>(&data),
+        Ok(usize::from(TEST_NUM_ENTRIES))
+    );
+}
+
+#[tokio::test]
+async fn test_extended_entry() {
+    let (mut ctx, feed) = setup_resized_feed().await;
+
+    let price = Price {
+        value: 2_500_000,
+        exp: 6,
+    };
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_EXTENDED_PYTH_ORACLE, &price).await;
+    operations::update_oracle_mapping(&mut ctx, &feed, &TEST_EXTENDED_PYTH_ORACLE).await;
+    operations::refresh_price(&mut ctx, &feed, &TEST_EXTENDED_PYTH_ORACLE).await;
+
+    let data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    let mappings = extension::read_entries::<OracleMappings>(&data).unwrap();
+    assert_eq!(
+        mappings[TEST_EXTENDED_PYTH_ORACLE.token].price_info_account,
+        TEST_EXTENDED_PYTH_ORACLE.pubkey
+    );
+    assert_eq!(
+        mappings[TEST_EXTENDED_PYTH_ORACLE.token].price_type,
+        TEST_EXTENDED_PYTH_ORACLE.price_type.to_u8()
+    );
+
+    let data = ctx.get_account_data(&feed.prices).await.unwrap();
+    let prices = extension::read_entries::<OraclePrices>(&data).unwrap();
+    let extended_price = prices[TEST_EXTENDED_PYTH_ORACLE.token];
+    assert_eq!(extended_price.price, price);
+    assert_eq!(
+        usize::from(extended_price.index),
+        TEST_EXTENDED_PYTH_ORACLE.token
+    );
+    // Neighbour entries are not affected
+    assert_eq!(prices[MAX_ENTRIES].price, Price::default());
+    assert_eq!(prices.len(), usize::from(TEST_NUM_ENTRIES));
+
+    let data = ctx.get_account_data(&feed.twaps).await.unwrap();
+    let twaps = extension::read_entries::<OracleTwaps>(&data).unwrap();
+    assert_eq!(twaps[TEST_EXTENDED_PYTH_ORACLE.token].len, 1);
+
+    // Returned like any other price
+    let accounts = scope::accounts::GetPrices {
+        oracle_prices: feed.prices,
+    };
+    let args = scope::instruction::GetPrices {
+        tokens: vec![TEST_EXTENDED_PYTH_ORACLE.token.try_into().unwrap(), 0],
+    };
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    let return_data = ctx
+        .simulate_transaction_return_data(&[ix.clone()])
+        .await
+        .unwrap()
+        .unwrap();
+    let returned = decode_prices(&return_data.data).unwrap();
+    assert_eq!(returned, vec![extended_price, prices[0]]);
+
+    // Entries beyond the new size are still out of range
+    let args = scope::instruction::GetPrices {
+        tokens: vec![TEST_NUM_ENTRIES],
+    };
+    let ix = Instruction {
+        data: args.data(),
+        ..ix
+    };
+    assert_eq!(
+        map_scope_error(ctx.simulate_transaction_return_data(&[ix]).await),
+        ScopeError::BadTokenNb
+    );
+}
+
+#[tokio::test]
+async fn test_resize_feed_wrong_admin() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let ix = operations::resize_feed_ix(&fake_admin.pubkey(), &feed, TEST_NUM_ENTRIES);
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+#[tokio::test]
+async fn test_resize_feed_not_growing() {
+    let (mut ctx, feed) = setup_resized_feed().await;
+
+    for num_entries in [
+        TEST_NUM_ENTRIES,
+        TEST_NUM_ENTRIES - 1,
+        scope::MAX_ENTRIES_U16,
+    ] {
+        let ix = operations::resize_feed_ix(&ctx.admin.pubkey(), &feed, num_entries);
+        assert_eq!(
+            map_scope_error(ctx.send_transaction(&[ix]).await),
+            ScopeError::InvalidFeedSize
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_resize_feed_too_large() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    // TWAP entries do not fit in the max growth of an account in one instruction
+    let ix = operations::resize_feed_ix(&ctx.admin.pubkey(), &feed, scope::MAX_ENTRIES_U16 + 10);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidFeedSize
+    );
+
+    let ix = operations::resize_feed_ix(
+        &ctx.admin.pubkey(),
+        &feed,
+        (MAX_FEED_ENTRIES + 1).try_into().unwrap(),
+    );
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidFeedSize
+    );
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use anchor_lang::prelude::Clock;
+use common::*;
+use scope::{
+    oracles::{
+        spl_stake::{SplStakeConfig, MAX_EPOCH_GRACE_PERIOD_S},
+        OracleGenericData,
+    },
+    OraclePrices, Price, ScopeError,
+};
+use solana_program::epoch_schedule::EpochSchedule;
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::{common::mock_oracles::spl_stake, utils::map_scope_error};
+
+const TEST_SPL_STAKE_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeSp1StakePoo1Account11111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::SplStake,
+};
+
+const GRACE_PERIOD_S: u64 = 600;
+
+// - [x] Pool not updated in the current epoch without grace period
+// - [x] Pool not updated in the current epoch after the grace period
+// - [x] Mapping with a grace period above the max
+
+/// Map a pool updated in the current epoch at 1.2 SOL per token, 1.1 in the previous epoch
+async fn setup_spl_stake(epoch_grace_period_s: u64) -> (TestContext, ScopeFeedDefinition) {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let clock = ctx.get_clock().await;
+    let mut stake_pool = spl_stake::get_stake_pool(&Price { value: 12, exp: 1 }, &clock);
+    stake_pool.last_epoch_total_lamports = 11;
+    stake_pool.last_epoch_pool_token_supply = 10;
+    ctx.set_account(
+        &TEST_SPL_STAKE_ORACLE.pubkey,
+        spl_stake::get_account_data(&stake_pool),
+        &spl_stake::id(),
+    );
+
+    let ix = operations::update_mapping_with_generic_data_ix(
+        &ctx,
+        &feed,
+        &TEST_SPL_STAKE_ORACLE,
+        SplStakeConfig {
+            epoch_grace_period_s,
+        }
+        .to_generic_data(),
+    );
+    ctx.send_transaction(&[ix]).await.unwrap();
+    (ctx, feed)
+}
+
+/// Move to the next epoch, started `elapsed_in_epoch` seconds ago
+async fn start_next_epoch(ctx: &mut TestContext, elapsed_in_epoch: i64) -> Clock {
+    let mut clock = ctx.get_clock().await;
+    clock.epoch += 1;
+    clock.epoch_start_timestamp = clock.unix_timestamp - elapsed_in_epoch;
+    ctx.set_clock(&clock);
+    clock
+}
+
+#[tokio::test]
+async fn test_working_refresh_in_updated_epoch() {
+    let (mut ctx, feed) = setup_spl_stake(GRACE_PERIOD_S).await;
+    let clock = ctx.get_clock().await;
+
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(
+        &feed,
+        &TEST_SPL_STAKE_ORACLE,
+        &[],
+    )])
+    .await
+    .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let dated_price = data.prices[TEST_SPL_STAKE_ORACLE.token];
+    assert_eq!(dated_price.price.value, 1_200_000_000_000_000);
+    assert_eq!(dated_price.price.exp, 15);
+    assert_eq!(dated_price.last_updated_slot, clock.slot);
+}
+
+#[tokio::test]
+async fn test_projected_price_in_grace_period() {
+    let (mut ctx, feed) = setup_spl_stake(GRACE_PERIOD_S).await;
+    let clock = start_next_epoch(&mut ctx, GRACE_PERIOD_S as i64).await;
+    let epoch_schedule: EpochSchedule = ctx
+        .context
+        .banks_client
+        .get_sysvar::<EpochSchedule>()
+        .await
+        .unwrap();
+
+    ctx.send_transaction_with_bot(&[operations::refresh_one_ix(
+        &feed,
+        &TEST_SPL_STAKE_ORACLE,
+        &[],
+    )])
+    .await
+    .unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let dated_price = data.prices[TEST_SPL_STAKE_ORACLE.token];
+    // The rate keeps increasing by 0.1 SOL per epoch
+    assert_eq!(dated_price.price.value, 1_300_000_000_000_000);
+    assert_eq!(dated_price.price.exp, 15);
+    // The price is dated at the start of the epoch
+    assert_eq!(
+        dated_price.last_updated_slot,
+        epoch_schedule.get_first_slot_in_epoch(clock.epoch)
+    );
+    assert_eq!(
+        dated_price.unix_timestamp,
+        u64::try_from(clock.epoch_start_timestamp).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_stale_price_after_grace_period() {
+    let (mut ctx, feed) = setup_spl_stake(GRACE_PERIOD_S).await;
+    start_next_epoch(&mut ctx, GRACE_PERIOD_S as i64 + 1).await;
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_SPL_STAKE_ORACLE,
+            &[],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
+
+#[tokio::test]
+async fn test_stale_price_without_grace_period() {
+    let (mut ctx, feed) = setup_spl_stake(0).await;
+    start_next_epoch(&mut ctx, 0).await;
+
+    let res = ctx
+        .send_transaction_with_bot(&[operations::refresh_one_ix(
+            &feed,
+            &TEST_SPL_STAKE_ORACLE,
+            &[],
+        )])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+}
+
+#[tokio::test]
+async fn test_mapping_with_too_long_grace_period() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_SPL_STAKE_ORACLE,
+        &Price { value: 1, exp: 0 },
+    )
+    .await;
+
+    let ix = operations::update_mapping_with_generic_data_ix(
+        &ctx,
+        &feed,
+        &TEST_SPL_STAKE_ORACLE,
+        SplStakeConfig {
+            epoch_grace_period_s: MAX_EPOCH_GRACE_PERIOD_S + 1,
+        }
+        .to_generic_data(),
+    );
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidGenericData
+    );
+}
@@ -0,0 +1,288 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
+use common::*;
+use scope::{EntryConfigUpdate, OracleMappings, ScopeError};
+use solana_program::instruction::Instruction;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{
+    common::utils::AnchorErrorCode,
+    utils::{map_anchor_error, map_scope_error},
+};
+
+const TEST_TOKEN: u64 = 3;
+
+// - [x] Wrong feed name
+// - [x] Wrong config account
+// - [x] Wrong mapping account
+// - [x] Wrong admin
+// - [x] Token out of range
+// - [x] Confidence above 100%
+
+// Working update entry config
+#[tokio::test]
+async fn test_working_update_entry_config() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::MaxAgeSlots(150),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert_eq!(mappings.max_age_slots[TEST_TOKEN as usize], 150);
+}
+
+// Working update of the max change per slot
+#[tokio::test]
+async fn test_working_update_max_change_bps_per_slot() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::MaxChangeBpsPerSlot(25),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert_eq!(mappings.max_change_bps_per_slot[TEST_TOKEN as usize], 25);
+    assert_eq!(mappings.max_age_slots[TEST_TOKEN as usize], 0);
+}
+
+// Working update of the max confidence
+#[tokio::test]
+async fn test_working_update_confidence_bps() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::ConfidenceBps(500),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert_eq!(mappings.confidence_bps[TEST_TOKEN as usize], 500);
+}
+
+// - [ ] Confidence above 100%
+#[tokio::test]
+async fn test_confidence_bps_above_full() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::ConfidenceBps(10_001),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::InvalidEntryConfig,
+    );
+}
+
+// - [ ] Wrong feed name
+#[tokio::test]
+async fn test_wrong_feed_name() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: "randomFeed".to_string(),
+        update: EntryConfigUpdate::MaxAgeSlots(150),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Wrong config account
+#[tokio::test]
+async fn test_wrong_config_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    // Create a fake config account
+    let fake_config_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.conf, &fake_config_pk).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: fake_config_pk,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::MaxAgeSlots(150),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Wrong mapping account
+#[tokio::test]
+async fn test_wrong_mapping_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    // Create a fake mapping account
+    let fake_mapping_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.mapping, &fake_mapping_pk).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: fake_mapping_pk,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::MaxAgeSlots(150),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction(&[ix]).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Wrong admin
+#[tokio::test]
+async fn test_wrong_admin() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    // New (bad) admin
+    let fake_admin = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_admin.pubkey())
+        .await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: fake_admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: TEST_TOKEN,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::MaxAgeSlots(150),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &fake_admin).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Token out of range
+#[tokio::test]
+async fn test_token_out_of_range() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let accounts = scope::accounts::UpdateEntryConfig {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+    };
+    let args = scope::instruction::UpdateEntryConfig {
+        token: scope::MAX_ENTRIES as u64,
+        feed_name: feed.feed_name.clone(),
+        update: EntryConfigUpdate::MaxAgeSlots(150),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction(&[ix]).await),
+        ScopeError::BadTokenNb,
+    );
+}
@@ -0,0 +1,426 @@
+mod common;
+
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    InstructionData, ToAccountMetas,
+};
+use common::*;
+use scope::{Configuration, FreezeTarget, OraclePrices, Price, ScopeError};
+use solana_program::{
+    instruction::Instruction,
+    sysvar::{instructions::ID as SYSVAR_INSTRUCTIONS_ID, SysvarId},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
+use types::*;
+
+use crate::{
+    common::utils::AnchorErrorCode,
+    utils::{map_anchor_error, map_scope_error},
+};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+};
+
+const TEST_PYTH2_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePyth2PriceAccount1111111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::Pyth,
+};
+
+// Set guardian:
+// - [x] Wrong admin
+
+// Update freeze:
+// - [x] Wrong feed name
+// - [x] Wrong config account
+// - [x] Wrong prices account
+// - [x] Unknown signer
+// - [x] Guardian cannot unfreeze
+// - [x] Guardian cannot update mappings
+// - [x] Token out of range
+
+// Refresh:
+// - [x] Refresh one of a frozen entry
+// - [x] Refresh list skips frozen entries
+// - [x] Refresh of a paused feed
+
+fn set_guardian_ix(
+    admin: &Pubkey,
+    configuration: &Pubkey,
+    feed_name: &str,
+    new_guardian: &Pubkey,
+) -> Instruction {
+    let accounts = scope::accounts::SetGuardian {
+        admin: *admin,
+        configuration: *configuration,
+    };
+    let args = scope::instruction::SetGuardian {
+        new_guardian: *new_guardian,
+        feed_name: feed_name.to_string(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+fn update_freeze_ix(
+    authority: &Pubkey,
+    feed: &ScopeFeedDefinition,
+    target: FreezeTarget,
+    frozen: bool,
+) -> Instruction {
+    let accounts = scope::accounts::UpdateFreeze {
+        authority: *authority,
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+    };
+    let args = scope::instruction::UpdateFreeze {
+        feed_name: feed.feed_name.clone(),
+        target,
+        frozen,
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+async fn refresh_list_ix(
+    ctx: &mut TestContext,
+    feed: &ScopeFeedDefinition,
+    confs: &[OracleConf],
+) -> Instruction {
+    let mut accounts = scope::accounts::RefreshList {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        configuration: feed.conf,
+        clock: Clock::id(),
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    for conf in confs {
+        let mut refresh_accounts = utils::get_refresh_list_accounts(ctx, conf).await;
+        accounts.append(&mut refresh_accounts);
+    }
+    let args = scope::instruction::RefreshPriceList {
+        tokens: confs.iter().map(|conf| conf.token as u16).collect(),
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: args.data(),
+    }
+}
+
+/// Setup a feed with a funded guardian
+async fn setup_with_guardian() -> (TestContext, ScopeFeedDefinition, Keypair) {
+    let (mut ctx, feed) =
+        fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE]).await;
+
+    let guardian = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &guardian.pubkey())
+        .await;
+
+    let ix = set_guardian_ix(
+        &ctx.admin.pubkey(),
+        &feed.conf,
+        &feed.feed_name,
+        &guardian.pubkey(),
+    );
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    (ctx, feed, guardian)
+}
+
+// Working freeze and unfreeze of an entry
+#[tokio::test]
+async fn test_working_freeze_entry() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let conf: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(conf.guardian, guardian.pubkey());
+
+    let target = FreezeTarget::Entry(TEST_PYTH_ORACLE.token as u16);
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, target, true);
+    ctx.send_transaction_with_payer(&[ix], &guardian)
+        .await
+        .unwrap();
+
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert!(prices.prices[TEST_PYTH_ORACLE.token].is_frozen());
+
+    // Only the admin can unfreeze
+    let ix = update_freeze_ix(&ctx.admin.pubkey(), &feed, target, false);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert!(!prices.prices[TEST_PYTH_ORACLE.token].is_frozen());
+}
+
+// Working pause and unpause of the feed
+#[tokio::test]
+async fn test_working_pause_feed() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, FreezeTarget::Feed, true);
+    ctx.send_transaction_with_payer(&[ix], &guardian)
+        .await
+        .unwrap();
+
+    let conf: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(conf.paused, 1);
+
+    let ix = update_freeze_ix(&ctx.admin.pubkey(), &feed, FreezeTarget::Feed, false);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let conf: Configuration = ctx.get_zero_copy_account(&feed.conf).await.unwrap();
+    assert_eq!(conf.paused, 0);
+}
+
+// - [ ] Set guardian: Wrong admin
+#[tokio::test]
+async fn test_set_guardian_wrong_admin() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let ix = set_guardian_ix(
+        &guardian.pubkey(),
+        &feed.conf,
+        &feed.feed_name,
+        &guardian.pubkey(),
+    );
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Update freeze: Wrong feed name
+#[tokio::test]
+async fn test_update_freeze_wrong_feed_name() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let mut ix = update_freeze_ix(&guardian.pubkey(), &feed, FreezeTarget::Feed, true);
+    ix.data = scope::instruction::UpdateFreeze {
+        feed_name: "randomFeed".to_string(),
+        target: FreezeTarget::Feed,
+        frozen: true,
+    }
+    .data();
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Update freeze: Wrong config account
+#[tokio::test]
+async fn test_update_freeze_wrong_config_account() {
+    let (mut ctx, mut feed, guardian) = setup_with_guardian().await;
+
+    // Create a fake config account
+    let fake_config_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.conf, &fake_config_pk).await;
+    feed.conf = fake_config_pk;
+
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, FreezeTarget::Feed, true);
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        AnchorErrorCode::ConstraintSeeds,
+    );
+}
+
+// - [ ] Update freeze: Wrong prices account
+#[tokio::test]
+async fn test_update_freeze_wrong_prices_account() {
+    let (mut ctx, mut feed, guardian) = setup_with_guardian().await;
+
+    // Create a fake prices account
+    let fake_prices_pk = Pubkey::new_unique();
+    ctx.clone_account(&feed.prices, &fake_prices_pk).await;
+    feed.prices = fake_prices_pk;
+
+    let target = FreezeTarget::Entry(TEST_PYTH_ORACLE.token as u16);
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, target, true);
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Update freeze: Unknown signer
+#[tokio::test]
+async fn test_update_freeze_unknown_signer() {
+    let (mut ctx, feed, _guardian) = setup_with_guardian().await;
+
+    let fake_guardian = Keypair::new();
+    ctx.clone_account(&ctx.admin.pubkey(), &fake_guardian.pubkey())
+        .await;
+
+    let target = FreezeTarget::Entry(TEST_PYTH_ORACLE.token as u16);
+    let ix = update_freeze_ix(&fake_guardian.pubkey(), &feed, target, true);
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_payer(&[ix], &fake_guardian).await),
+        ScopeError::UnauthorizedSigner,
+    );
+}
+
+// - [ ] Update freeze: Guardian cannot unfreeze
+#[tokio::test]
+async fn test_update_freeze_guardian_cannot_unfreeze() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, FreezeTarget::Feed, true);
+    ctx.send_transaction_with_payer(&[ix], &guardian)
+        .await
+        .unwrap();
+
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, FreezeTarget::Feed, false);
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        ScopeError::UnauthorizedSigner,
+    );
+}
+
+// - [ ] Update freeze: Guardian cannot update mappings
+#[tokio::test]
+async fn test_guardian_cannot_update_mapping() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let accounts = scope::accounts::UpdateOracleMapping {
+        admin: guardian.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+        price_info: TEST_PYTH_ORACLE.pubkey,
+    };
+    let args = scope::instruction::UpdateMapping {
+        feed_name: feed.feed_name.clone(),
+        token: 1,
+        price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+
+    assert_eq!(
+        map_anchor_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        AnchorErrorCode::ConstraintHasOne,
+    );
+}
+
+// - [ ] Update freeze: Token out of range
+#[tokio::test]
+async fn test_update_freeze_token_out_of_range() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let target = FreezeTarget::Entry(scope::MAX_ENTRIES_U16);
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, target, true);
+
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_payer(&[ix], &guardian).await),
+        ScopeError::BadTokenNb,
+    );
+}
+
+// - [ ] Refresh one of a frozen entry
+#[tokio::test]
+async fn test_refresh_one_frozen_entry() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let target = FreezeTarget::Entry(TEST_PYTH_ORACLE.token as u16);
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, target, true);
+    ctx.send_transaction_with_payer(&[ix], &guardian)
+        .await
+        .unwrap();
+
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_ORACLE,
+        &Price { value: 1, exp: 6 },
+    )
+    .await;
+
+    let ix = operations::refresh_one_ix(&feed, &TEST_PYTH_ORACLE, &[]);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_bot(&[ix]).await),
+        ScopeError::EntryFrozen,
+    );
+
+    // Consumers see the entry as frozen
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert!(prices.prices[TEST_PYTH_ORACLE.token].is_frozen());
+    assert_ne!(prices.prices[TEST_PYTH_ORACLE.token].price.value, 1);
+}
+
+// - [ ] Refresh list skips frozen entries
+#[tokio::test]
+async fn test_refresh_list_skips_frozen_entry() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let target = FreezeTarget::Entry(TEST_PYTH_ORACLE.token as u16);
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, target, true);
+    ctx.send_transaction_with_payer(&[ix], &guardian)
+        .await
+        .unwrap();
+
+    for conf in [&TEST_PYTH_ORACLE, &TEST_PYTH2_ORACLE] {
+        mock_oracles::set_price(&mut ctx, &feed, conf, &Price { value: 1, exp: 6 }).await;
+    }
+
+    let ix = refresh_list_ix(&mut ctx, &feed, &[TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE]).await;
+    ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+    let prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_ne!(prices.prices[TEST_PYTH_ORACLE.token].price.value, 1);
+    assert_eq!(prices.prices[TEST_PYTH2_ORACLE.token].price.value, 1);
+}
+
+// - [ ] Refresh of a paused feed
+#[tokio::test]
+async fn test_refresh_paused_feed() {
+    let (mut ctx, feed, guardian) = setup_with_guardian().await;
+
+    let ix = update_freeze_ix(&guardian.pubkey(), &feed, FreezeTarget::Feed, true);
+    ctx.send_transaction_with_payer(&[ix], &guardian)
+        .await
+        .unwrap();
+
+    let ix = operations::refresh_one_ix(&feed, &TEST_PYTH2_ORACLE, &[]);
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_bot(&[ix]).await),
+        ScopeError::FeedPaused,
+    );
+
+    let ix = refresh_list_ix(&mut ctx, &feed, &[TEST_PYTH2_ORACLE]).await;
+    assert_eq!(
+        map_scope_error(ctx.send_transaction_with_bot(&[ix]).await),
+        ScopeError::FeedPaused,
+    );
+
+    // Unpausing allows refreshes again
+    let ix = update_freeze_ix(&ctx.admin.pubkey(), &feed, FreezeTarget::Feed, false);
+    ctx.send_transaction(&[ix]).await.unwrap();
+
+    let ix = operations::refresh_one_ix(&feed, &TEST_PYTH2_ORACLE, &[]);
+    ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+}
@@ -2,13 +2,19 @@ mod common;
 
 use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
 use common::*;
-use scope::Price;
+use scope::{Price, ScopeError};
 use solana_program::instruction::Instruction;
 use solana_program_test::tokio;
 use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
 use types::*;
 
-use crate::{common::utils::AnchorErrorCode, utils::map_anchor_error};
+use crate::{
+    common::{
+        mock_oracles::{ctoken, spl_stake},
+        utils::AnchorErrorCode,
+    },
+    utils::{map_anchor_error, map_scope_error},
+};
 
 const TEST_PYTH_ORACLE: OracleConf = OracleConf {
     pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
@@ -16,10 +22,30 @@ const TEST_PYTH_ORACLE: OracleConf = OracleConf {
     price_type: TestOracleType::Pyth,
 };
 
+const TEST_CTOKEN_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeSo1endReserveAccount1111111111111111111"),
+    token: 1,
+    price_type: TestOracleType::CToken,
+};
+
+const TEST_SPL_STAKE_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeSp1StakePoo1Account11111111111111111111"),
+    token: 2,
+    price_type: TestOracleType::SplStake,
+};
+
+const TEST_SWITCHBOARD_V2_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomeSwitchboardV2Aggregator1111111111111111"),
+    token: 3,
+    price_type: TestOracleType::SwitchboardV2,
+};
+
 // - [x] Wrong feed name
 // - [x] Wrong config account
 // - [x] Wrong mapping account
 // - [x] Wrong admin
+// - [x] Oracle account not owned by the oracle program
+// - [x] Oracle account not initialized
 
 // Working update mapping
 #[tokio::test]
@@ -193,3 +219,94 @@ async fn test_wrong_admin() {
         AnchorErrorCode::ConstraintHasOne,
     );
 }
+
+#[tokio::test]
+async fn test_working_update_mapping_ctoken_and_spl_stake() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    for conf in [TEST_CTOKEN_ORACLE, TEST_SPL_STAKE_ORACLE] {
+        mock_oracles::set_price(
+            &mut ctx,
+            &feed,
+            &conf,
+            &Price {
+                value: 1_100_000,
+                exp: 6,
+            },
+        )
+        .await;
+        let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+        ctx.send_transaction(&[ix]).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_update_mapping_wrong_owner() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+    let clock = ctx.get_clock().await;
+    let price = Price {
+        value: 1_100_000,
+        exp: 6,
+    };
+
+    ctx.set_account(
+        &TEST_CTOKEN_ORACLE.pubkey,
+        ctoken::get_account_data_for_price(&price, &clock),
+        &Pubkey::new_unique(),
+    );
+    ctx.set_account(
+        &TEST_SPL_STAKE_ORACLE.pubkey,
+        spl_stake::get_account_data_for_price(&price, &clock),
+        &Pubkey::new_unique(),
+    );
+    ctx.set_account(
+        &TEST_SWITCHBOARD_V2_ORACLE.pubkey,
+        vec![0; 8],
+        &Pubkey::new_unique(),
+    );
+
+    for conf in [
+        TEST_CTOKEN_ORACLE,
+        TEST_SPL_STAKE_ORACLE,
+        TEST_SWITCHBOARD_V2_ORACLE,
+    ] {
+        let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+        assert_eq!(
+            map_scope_error(ctx.send_transaction(&[ix]).await),
+            ScopeError::WrongAccountOwner
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_update_mapping_uninitialized_accounts() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+    let clock = ctx.get_clock().await;
+    let price = Price {
+        value: 1_100_000,
+        exp: 6,
+    };
+
+    let mut reserve = ctoken::get_reserve(&price, &clock);
+    reserve.version = 0;
+    ctx.set_account(
+        &TEST_CTOKEN_ORACLE.pubkey,
+        ctoken::get_account_data(&reserve),
+        &ctoken::id(),
+    );
+    let mut stake_pool = spl_stake::get_stake_pool(&price, &clock);
+    stake_pool.account_type = spl_stake_pool::state::AccountType::Uninitialized;
+    ctx.set_account(
+        &TEST_SPL_STAKE_ORACLE.pubkey,
+        spl_stake::get_account_data(&stake_pool),
+        &spl_stake::id(),
+    );
+
+    for conf in [TEST_CTOKEN_ORACLE, TEST_SPL_STAKE_ORACLE] {
+        let ix = operations::update_mapping_ix(&ctx, &feed, &conf);
+        assert_eq!(
+            map_scope_error(ctx.send_transaction(&[ix]).await),
+            ScopeError::UnexpectedAccount
+        );
+    }
+}
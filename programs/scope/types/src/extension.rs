@@ -0,0 +1,178 @@
+//! Reading feed entries beyond [`MAX_ENTRIES`]
+//!
+//! When a feed is resized, its [`OraclePrices`], [`OracleMappings`] and [`OracleTwaps`] accounts
+//! keep their fixed layout and store the additional entries after it:
+//!
+//! ```text
+//! | discriminator | fixed layout (entries 0..MAX_ENTRIES) | ExtensionHeader | additional entries |
+//! ```
+//!
+//! Accounts without extension hold exactly `MAX_ENTRIES` entries.
+//!
+//! This is the only implementation of the layout, the scope program reads and writes its accounts
+//! with these functions.
+
+use std::{mem::size_of, ops::Range};
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    DatedPrice, EntryTwap, OracleMappings, OraclePrices, OracleTwaps, ScopeError,
+    GENERIC_DATA_SIZE, MAX_ENTRIES,
+};
+
+/// Version of the layout of the resized accounts
+pub const EXTENSION_VERSION: u32 = 2;
+
+/// Max number of entries of a feed
+pub const MAX_FEED_ENTRIES: usize = 4096;
+
+/// Size of the discriminator of the accounts
+pub const DISCRIMINATOR_SIZE: usize = 8;
+/// Size of the [`ExtensionHeader`] following the fixed layout of a resized account
+pub const HEADER_SIZE: usize = size_of::<ExtensionHeader>();
+
+/// Header of the additional entries of a resized account
+#[zero_copy]
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ExtensionHeader {
+    pub version: u32,
+    /// Total number of entries of the account, fixed ones included
+    pub num_entries: u32,
+}
+
+/// Configuration of an entry of [`OracleMappings`]
+///
+/// Entries of the fixed layout are stored column by column, additional entries with this layout.
+#[zero_copy]
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct MappingEntry {
+    pub price_info_account: Pubkey,
+    pub generic_data: [u8; GENERIC_DATA_SIZE],
+    pub max_age_slots: u32,
+    pub max_change_bps_per_slot: u16,
+    pub confidence_bps: u16,
+    pub price_type: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Account of a feed holding one entry per token
+pub trait FeedAccount: bytemuck::Pod {
+    /// Layout of the additional entries
+    type Entry: bytemuck::Pod;
+
+    /// Get the entry `index` of the fixed layout
+    fn fixed_entry(&self, index: usize) -> Self::Entry;
+}
+
+impl FeedAccount for OraclePrices {
+    type Entry = DatedPrice;
+
+    fn fixed_entry(&self, index: usize) -> DatedPrice {
+        self.prices[index]
+    }
+}
+
+impl FeedAccount for OracleTwaps {
+    type Entry = EntryTwap;
+
+    fn fixed_entry(&self, index: usize) -> EntryTwap {
+        self.twaps[index]
+    }
+}
+
+impl FeedAccount for OracleMappings {
+    type Entry = MappingEntry;
+
+    fn fixed_entry(&self, index: usize) -> MappingEntry {
+        MappingEntry {
+            price_info_account: self.price_info_accounts[index],
+            generic_data: self.generic_data[index],
+            max_age_slots: self.max_age_slots[index],
+            max_change_bps_per_slot: self.max_change_bps_per_slot[index],
+            confidence_bps: self.confidence_bps[index],
+            price_type: self.price_types[index],
+            _padding: [0; 7],
+        }
+    }
+}
+
+/// Number of entries of a feed account from its data (discriminator included)
+pub fn num_entries<T: FeedAccount>(data: &[u8]) -> Result<usize, ScopeError> {
+    let fixed_end = DISCRIMINATOR_SIZE + size_of::<T>();
+    let extension = data
+        .get(fixed_end..)
+        .ok_or(ScopeError::UnableToDeserializeAccount)?;
+    if extension.is_empty() {
+        return Ok(MAX_ENTRIES);
+    }
+    let header: ExtensionHeader = extension
+        .get(..HEADER_SIZE)
+        .map(bytemuck::pod_read_unaligned)
+        .ok_or(ScopeError::UnableToDeserializeAccount)?;
+    let num_entries = usize::try_from(header.num_entries)?;
+    let num_additional = num_entries.saturating_sub(MAX_ENTRIES);
+    if header.version != EXTENSION_VERSION
+        || num_additional == 0
+        || extension.len() < HEADER_SIZE + num_additional * size_of::<T::Entry>()
+    {
+        msg!("Invalid feed account extension {:?}", header);
+        return Err(ScopeError::UnableToDeserializeAccount);
+    }
+    Ok(num_entries)
+}
+
+/// Size of a feed account holding `num_entries` entries (discriminator included)
+pub fn account_size<T: FeedAccount>(num_entries: usize) -> usize {
+    let fixed_size = DISCRIMINATOR_SIZE + size_of::<T>();
+    match num_entries.saturating_sub(MAX_ENTRIES) {
+        0 => fixed_size,
+        num_additional => fixed_size + HEADER_SIZE + num_additional * size_of::<T::Entry>(),
+    }
+}
+
+/// Read the entry `index` of a feed account from its data (discriminator included)
+///
+/// Entries of the fixed layout are read in place, the data must be 8 bytes aligned as account
+/// data is.
+pub fn read_entry<T: FeedAccount>(data: &[u8], index: usize) -> Result<T::Entry, ScopeError> {
+    let (fixed, additional) = layout::<T>(data)?;
+    match index.checked_sub(MAX_ENTRIES) {
+        None => {
+            let fixed: &T = bytemuck::try_from_bytes(&data[fixed])
+                .map_err(|_| ScopeError::UnableToDeserializeAccount)?;
+            Ok(fixed.fixed_entry(index))
+        }
+        Some(additional_index) => data[additional]
+            .chunks_exact(size_of::<T::Entry>())
+            .nth(additional_index)
+            .map(bytemuck::pod_read_unaligned)
+            .ok_or(ScopeError::BadTokenNb),
+    }
+}
+
+/// Read a copy of all the entries of a feed account from its data (discriminator included)
+///
+/// The data does not need to be aligned, meant to be used off-chain.
+pub fn read_entries<T: FeedAccount>(data: &[u8]) -> Result<Vec<T::Entry>, ScopeError> {
+    let (fixed, additional) = layout::<T>(data)?;
+    let fixed: T = bytemuck::pod_read_unaligned(&data[fixed]);
+    let fixed_entries = (0..MAX_ENTRIES).map(|index| fixed.fixed_entry(index));
+    let additional_entries = data[additional]
+        .chunks_exact(size_of::<T::Entry>())
+        .map(bytemuck::pod_read_unaligned);
+    Ok(fixed_entries.chain(additional_entries).collect())
+}
+
+/// Byte ranges of the fixed layout and of the additional entries in the account data
+pub fn layout<T: FeedAccount>(data: &[u8]) -> Result<(Range<usize>, Range<usize>), ScopeError> {
+    let fixed_end = DISCRIMINATOR_SIZE + size_of::<T>();
+    let num_additional = num_entries::<T>(data)? - MAX_ENTRIES;
+    let additional = if num_additional == 0 {
+        fixed_end..fixed_end
+    } else {
+        let start = fixed_end + HEADER_SIZE;
+        start..start + num_additional * size_of::<T::Entry>()
+    };
+    Ok((DISCRIMINATOR_SIZE..fixed_end, additional))
+}
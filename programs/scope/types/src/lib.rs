@@ -1,5 +1,6 @@
 #![allow(clippy::result_large_err)] //Needed because we can't change Anchor result type
 
+pub mod extension;
 pub mod program_id;
 
 // Reexports to deal with eventual conflicts
@@ -19,6 +20,12 @@ declare_id!(PROGRAM_ID);
 pub const MAX_ENTRIES_U16: u16 = 512;
 // Note: Need to be directly integer value to not confuse the IDL generator
 pub const MAX_ENTRIES: usize = 512;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const GENERIC_DATA_SIZE: usize = 32;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const TWAP_NUM_OBSERVATIONS: usize = 64;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const MAX_ALLOWED_PRECEDING_PROGRAMS: usize = 8;
 
 #[zero_copy]
 #[derive(Debug, Eq, PartialEq, Default)]
@@ -43,9 +50,16 @@ pub struct DatedPrice {
     pub price: Price,
     pub last_updated_slot: u64,
     pub unix_timestamp: u64,
-    pub _reserved: [u64; 2],
-    pub _reserved2: [u16; 3],
-    // Current index of the dated price.
+    // Confidence interval of the price, with the price exponent (0 if not provided by the oracle)
+    pub confidence: u64,
+    // Slot of the last failed refresh of the entry by `refresh_price_list`, cleared by a successful refresh
+    pub last_error_slot: u64,
+    // Error code of the last failed refresh, meaningful only if `last_error_slot` is set
+    pub last_error_code: u32,
+    // Status flags of the entry, see `DatedPrice::FLAG_*`
+    pub flags: u16,
+    // Current index of the dated price, `u16::MAX` until the entry is refreshed for the first time
+    // (all the indexes up to `MAX_FEED_ENTRIES` are valid).
     pub index: u16,
 }
 
@@ -55,9 +69,28 @@ impl Default for DatedPrice {
             price: Default::default(),
             last_updated_slot: Default::default(),
             unix_timestamp: Default::default(),
-            _reserved: Default::default(),
-            _reserved2: Default::default(),
-            index: MAX_ENTRIES_U16,
+            confidence: 0,
+            last_error_slot: 0,
+            last_error_code: 0,
+            flags: 0,
+            index: u16::MAX,
+        }
+    }
+}
+
+impl DatedPrice {
+    /// The entry has been frozen by the admin or the guardian and is not refreshed anymore
+    pub const FLAG_FROZEN: u16 = 1 << 0;
+
+    pub fn is_frozen(&self) -> bool {
+        self.flags & Self::FLAG_FROZEN != 0
+    }
+
+    pub fn set_frozen(&mut self, frozen: bool) {
+        if frozen {
+            self.flags |= Self::FLAG_FROZEN;
+        } else {
+            self.flags &= !Self::FLAG_FROZEN;
         }
     }
 }
@@ -69,12 +102,73 @@ pub struct OraclePrices {
     pub prices: [DatedPrice; MAX_ENTRIES],
 }
 
+impl OraclePrices {
+    /// Get the price of an entry, checking it is not frozen nor older than its configured max age.
+    ///
+    /// `oracle_mappings` must be the account referenced in `self.oracle_mappings`.
+    /// Entries without a configured max age are returned without age check.
+    pub fn get_checked(
+        &self,
+        oracle_mappings: &OracleMappings,
+        index: usize,
+        clock: &Clock,
+    ) -> std::result::Result<DatedPrice, ScopeError> {
+        let price = self.prices.get(index).ok_or(ScopeError::BadTokenNb)?;
+        if price.is_frozen() {
+            return Err(ScopeError::EntryFrozen);
+        }
+        let max_age_slots = oracle_mappings.max_age_slots[index];
+        let age = clock.slot.saturating_sub(price.last_updated_slot);
+        if max_age_slots != 0 && age > u64::from(max_age_slots) {
+            return Err(ScopeError::PriceTooOld);
+        }
+        Ok(*price)
+    }
+}
+
+#[zero_copy]
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct TwapObservation {
+    // Price value, expressed with the exponent of the entry `EntryTwap`
+    pub value: u64,
+    pub unix_timestamp: u64,
+}
+
+// Ring buffer of the latest price observations of an entry
+#[zero_copy]
+#[derive(Debug, Eq, PartialEq)]
+pub struct EntryTwap {
+    // Exponent shared by all the observations
+    pub exp: u64,
+    // Index of the most recent observation
+    pub head: u16,
+    // Number of valid observations in the buffer
+    pub len: u16,
+    pub _reserved: u32,
+    pub observations: [TwapObservation; TWAP_NUM_OBSERVATIONS],
+}
+
+// Account to store the price observations used to compute TWAPs
+#[account(zero_copy)]
+pub struct OracleTwaps {
+    pub oracle_prices: Pubkey,
+    pub oracle_mappings: Pubkey,
+    pub twaps: [EntryTwap; MAX_ENTRIES],
+}
+
 // Accounts holding source of prices
 #[account(zero_copy)]
 pub struct OracleMappings {
     pub price_info_accounts: [Pubkey; MAX_ENTRIES],
     pub price_types: [u8; MAX_ENTRIES],
-    pub _reserved2: [u64; MAX_ENTRIES],
+    // Max age of the price in slots before being considered too old by consumers (0 if not set)
+    pub max_age_slots: [u32; MAX_ENTRIES],
+    // Max relative change of the price per slot elapsed since previous update, in bps (0 if not set)
+    pub max_change_bps_per_slot: [u16; MAX_ENTRIES],
+    // Max confidence interval relative to the price, in bps (0 for the default of 2%)
+    pub confidence_bps: [u16; MAX_ENTRIES],
+    // Oracle type specific configuration of each entry
+    pub generic_data: [[u8; GENERIC_DATA_SIZE]; MAX_ENTRIES],
 }
 
 // Configuration account of the program
@@ -83,7 +177,26 @@ pub struct Configuration {
     pub admin: Pubkey,
     pub oracle_mappings: Pubkey,
     pub oracle_prices: Pubkey,
-    _padding: [u64; 1267],
+    // Only feeds recording TWAPs have one, the default pubkey otherwise
+    pub oracle_twaps: Pubkey,
+    // Admin proposed by the current admin, has to approve the handover to become admin
+    pub admin_cached: Pubkey,
+    // Can only freeze entries or pause the whole feed, unfreezing requires the admin
+    pub guardian: Pubkey,
+    // Non zero if the whole feed is paused
+    pub paused: u8,
+    _padding0: [u8; 7],
+    // Programs whose instructions may precede a refresh in the transaction, besides compute budget
+    // (unused slots are set to the default pubkey)
+    pub allowed_preceding_programs: [Pubkey; MAX_ALLOWED_PRECEDING_PROGRAMS],
+    _padding: [u64; 1222],
+}
+
+impl Configuration {
+    /// Check if instructions of `program_id` may precede a refresh
+    pub fn is_allowed_preceding_program(&self, program_id: &Pubkey) -> bool {
+        *program_id != Pubkey::default() && self.allowed_preceding_programs.contains(program_id)
+    }
 }
 
 #[error_code]
@@ -133,6 +246,48 @@ pub enum ScopeError {
 
     #[msg("Refresh price instruction preceded by unexpected ixs")]
     RefreshWithUnexpectedIxs,
+
+    #[msg("Invalid generic data for the oracle type")]
+    InvalidGenericData,
+
+    #[msg("Price is older than the configured max age")]
+    PriceTooOld,
+
+    #[msg("Price change since the last update is above the configured limit")]
+    PriceChangeTooLarge,
+
+    #[msg("Signer is not allowed to perform this operation")]
+    UnauthorizedSigner,
+
+    #[msg("Price entry is frozen")]
+    EntryFrozen,
+
+    #[msg("Price feed is paused")]
+    FeedPaused,
+
+    #[msg("Invalid entry configuration")]
+    InvalidEntryConfig,
+
+    #[msg("There was an error with the Switchboard On-Demand retrieval")]
+    SwitchboardOnDemandError,
+
+    #[msg("Oracle account is not owned by the expected program")]
+    WrongAccountOwner,
+
+    #[msg("Too many prices requested to fit in the return data")]
+    TooManyPricesRequested,
+
+    #[msg("Invalid list of programs allowed to precede a refresh")]
+    InvalidAllowedPrograms,
+
+    #[msg("Invalid number of entries for the feed")]
+    InvalidFeedSize,
+
+    #[msg("The feed already has a TWAPs account")]
+    OracleTwapsAlreadySet,
+
+    #[msg("The feed does not record TWAPs")]
+    OracleTwapsNotSet,
 }
 
 impl<T> From<TryFromPrimitiveError<T>> for ScopeError
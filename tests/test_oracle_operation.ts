@@ -120,6 +120,8 @@ describe('Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.SRM].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });
@@ -146,6 +148,8 @@ describe('Scope tests', () => {
           oracleMappings: oracleMappingAccount,
           clock: SYSVAR_CLOCK_PUBKEY,
           instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+          configuration: confAccount,
+          oracleTwaps: oracleAccount,
         },
         remainingAccounts: [
           { pubkey: testTokens[HubbleTokens.ETH].account, isWritable: false, isSigner: false },
@@ -203,6 +207,8 @@ describe('Scope tests', () => {
         oracleMappings: oracleMappingAccount,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       remainingAccounts: accounts,
       signers: [],
@@ -113,6 +113,8 @@ describe('Switchboard Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.STSOLUSD].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });
@@ -129,6 +131,8 @@ describe('Switchboard Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.SABERMSOLSOL].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });
@@ -145,6 +149,8 @@ describe('Switchboard Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.USDHUSD].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });
@@ -163,6 +169,8 @@ describe('Switchboard Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.STSOLUSD].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });
@@ -180,6 +188,8 @@ describe('Switchboard Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.SABERMSOLSOL].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });
@@ -197,6 +207,8 @@ describe('Switchboard Scope tests', () => {
         priceInfo: testTokens[HubbleTokens.USDHUSD].account,
         clock: SYSVAR_CLOCK_PUBKEY,
         instructionSysvarAccountInfo: SYSVAR_INSTRUCTIONS_PUBKEY,
+        configuration: confAccount,
+        oracleTwaps: oracleAccount,
       },
       signers: [],
     });